@@ -0,0 +1,18 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! BeerJSON 1.0 (https://github.com/beerjson/beerjson) maps most BeerXML
+//! fields onto unit tagged values, but not everything survives the trip:
+//!
+//! * the Appendix A display and estimated fields are not written
+//! * hop `Aroma` and `First Wort` additions are written as boil additions
+//! * yeast `Wheat` and most style types only have a generic BeerJSON counterpart
+//! * a recipe's equipment and its priming and carbonation details are not written
+//! * equipment is written as a mash tun, brew kettle and fermenter; the boil size
+//!   becomes the kettle volume
+
+mod read;
+mod schema;
+mod write;
+
+pub use self::read::*;
+pub use self::write::*;
@@ -0,0 +1,582 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::io::{BufRead, BufReader};
+use std::fs::File;
+use std::path::Path;
+
+use serde_json;
+use serde_json::Value;
+
+use data;
+use data::{Document, Named, OpenEnum, RecordMap, RecordSet};
+use error::*;
+use units::{Color, Percent, Temperature, Time, Volume};
+use super::schema::*;
+
+fn map_o<T, F>(q: &Option<Quantity>, convert: F) -> Result<Option<T>>
+    where T: From<f64>,
+          F: Fn(&Quantity) -> Result<f64>
+{
+    match *q {
+        Some(ref q) => convert(q).map(|v| Some(v.into())),
+        None => Ok(None),
+    }
+}
+
+fn range<T, F>(r: &Option<Range>, convert: F) -> Result<(Option<T>, Option<T>)>
+    where T: From<f64>,
+          F: Fn(&Quantity) -> Result<f64>
+{
+    match *r {
+        Some(ref r) => Ok((map_o(&r.minimum, &convert)?, map_o(&r.maximum, &convert)?)),
+        None => Ok((None, None)),
+    }
+}
+
+fn timing_use(t: &Option<Timing>) -> Option<&str> {
+    t.as_ref().and_then(|t| t.use_.as_ref()).map(|u| u.as_str())
+}
+
+fn timing_minutes(t: &Option<Timing>) -> Result<f64> {
+    let duration = t.as_ref().and_then(|t| t.duration.as_ref());
+    Ok(map_o(&duration.cloned(), Quantity::minutes)?.unwrap_or_default())
+}
+
+fn amount(q: &Option<Quantity>) -> Result<(f64, bool)> {
+    match *q {
+        Some(ref q) => q.kg_or_liters(),
+        None => Ok((0.0, true)),
+    }
+}
+
+fn fermentable(f: Fermentable) -> Result<data::Fermentable> {
+    let type_ = data::FermentableType::parse_with(&f.type_, |t| match t {
+        "other" => Some(data::FermentableType::Adjunct),
+        _ => None,
+    });
+    let potential = map_o(&f.yield_.potential, Quantity::sg)?;
+    let display = potential.map(|p| {
+        data::FermentableDisplay { potential: Some(p), ..Default::default() }
+    });
+    Ok(data::Fermentable {
+        name: f.name,
+        version: 1,
+        type_,
+        amount: map_o(&f.amount, Quantity::kg)?.unwrap_or_default(),
+        yield_: map_o(&f.yield_.fine_grind, Quantity::percent)?.unwrap_or_default(),
+        color: Color(f.color.srm()?),
+        add_after_boil: timing_use(&f.timing) == Some("add_to_fermentation"),
+        origin: f.origin,
+        supplier: f.producer,
+        notes: f.notes,
+        coarse_fine_diff: map_o(&f.yield_.fine_coarse_difference, Quantity::percent)?,
+        moisture: map_o(&f.moisture, Quantity::percent)?,
+        diastatic_power: map_o(&f.diastatic_power, Quantity::lintner)?,
+        protein: map_o(&f.protein, Quantity::percent)?,
+        max_in_batch: map_o(&f.max_in_batch, Quantity::percent)?,
+        recommend_mash: f.recommend_mash.unwrap_or_default(),
+        ibu_gal_per_lb: None,
+        display,
+        extensions: Default::default(),
+    })
+}
+
+fn hop(h: Hop) -> Result<data::Hop> {
+    use data::{HopForm, HopType, HopUse};
+    let use_ = match timing_use(&h.timing) {
+        Some("add_to_mash") => HopUse::Mash,
+        Some("add_to_fermentation") | Some("add_to_package") => HopUse::DryHop,
+        Some("add_to_boil") | None => HopUse::Boil,
+        Some(u) => HopUse::parse_lenient(u),
+    };
+    let type_ = h.type_.as_ref().map(|t| {
+        HopType::parse_with(t, |t| match t {
+            "aroma/flavor" => Some(HopType::Aroma),
+            "bittering/flavor" | "aroma/bittering/flavor" => Some(HopType::Both),
+            _ => None,
+        })
+    });
+    let form = h.form.as_ref().map(|f| {
+        HopForm::parse_with(f, |f| match f {
+            "leaf (wet)" => Some(HopForm::Leaf),
+            _ => None,
+        })
+    });
+    let oil = h.oil_content.unwrap_or_default();
+    Ok(data::Hop {
+        name: h.name,
+        version: 1,
+        alpha: Percent(h.alpha_acid.percent()?),
+        amount: map_o(&h.amount, Quantity::kg)?.unwrap_or_default(),
+        use_,
+        time: Time(timing_minutes(&h.timing)?),
+        notes: h.notes,
+        type_,
+        form,
+        beta: map_o(&h.beta_acid, Quantity::percent)?,
+        hsi: map_o(&h.percent_lost, Quantity::percent)?,
+        origin: h.origin,
+        substitutes: h.substitutes,
+        humulene: map_o(&oil.humulene, Quantity::percent)?,
+        caryophyllene: map_o(&oil.caryophyllene, Quantity::percent)?,
+        cohumulone: map_o(&oil.cohumulone, Quantity::percent)?,
+        myrcene: map_o(&oil.myrcene, Quantity::percent)?,
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn culture(c: Culture) -> Result<data::Yeast> {
+    use data::{YeastFlocculation, YeastForm, YeastType};
+    // bacteria, brett and the like are not yeast types BeerXML knows, so they are kept
+    let type_ = YeastType::parse_with(&c.type_, |_| None);
+    let form = YeastForm::parse_with(&c.form, |_| None);
+    let flocculation = c.flocculation.as_ref().map(|f| {
+        YeastFlocculation::parse_with(f, |f| match f {
+            "medium low" | "medium high" => Some(YeastFlocculation::Medium),
+            _ => None,
+        })
+    });
+    let (min_temperature, max_temperature) = range(&c.temperature_range, Quantity::celsius)?;
+    let (amount, amount_is_weight) = amount(&c.amount)?;
+    Ok(data::Yeast {
+        name: c.name,
+        version: 1,
+        type_,
+        form,
+        amount,
+        amount_is_weight,
+        laboratory: c.producer,
+        product_id: c.product_id,
+        min_temperature,
+        max_temperature,
+        flocculation,
+        attenuation: map_o(&c.attenuation, Quantity::percent)?,
+        notes: c.notes,
+        best_for: c.best_for,
+        times_cultured: c.times_cultured,
+        max_reuse: c.max_reuse,
+        add_to_secondary: c.add_to_secondary.unwrap_or_default(),
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn misc(m: Misc) -> Result<data::Misc> {
+    use data::{MiscType, MiscUse};
+    let type_ = MiscType::parse_with(&m.type_, |_| None);
+    let step = m.timing.as_ref().and_then(|t| t.step);
+    let use_ = match timing_use(&m.timing) {
+        Some("add_to_mash") => MiscUse::Mash,
+        Some("add_to_boil") | None => MiscUse::Boil,
+        Some("add_to_fermentation") if step.unwrap_or(1) > 1 => MiscUse::Secondary,
+        Some("add_to_fermentation") => MiscUse::Primary,
+        Some("add_to_package") => MiscUse::Bottling,
+        Some(u) => MiscUse::parse_lenient(u),
+    };
+    let (amount, amount_is_weight) = amount(&m.amount)?;
+    Ok(data::Misc {
+        name: m.name,
+        version: 1,
+        type_,
+        use_,
+        time: Time(timing_minutes(&m.timing)?),
+        amount,
+        amount_is_weight,
+        use_for: m.use_for,
+        notes: m.notes,
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn water(w: Water) -> Result<data::Water> {
+    Ok(data::Water {
+        name: w.name,
+        version: 1,
+        amount: map_o(&w.amount, Quantity::liters)?.unwrap_or_default(),
+        calcium: w.calcium.ppm()?,
+        bicarbonate: w.bicarbonate.ppm()?,
+        sulfate: w.sulfate.ppm()?,
+        chloride: w.chloride.ppm()?,
+        sodium: w.sodium.ppm()?,
+        magnesium: w.magnesium.ppm()?,
+        ph: map_o(&w.ph, Quantity::ph)?,
+        notes: w.notes,
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn style(s: Style) -> Result<data::Style> {
+    use data::StyleType;
+    let type_ = StyleType::parse_with(&s.type_, |t| match t {
+        "beer" => Some(StyleType::Ale),
+        _ => None,
+    });
+    let category_number = match s.category_number {
+        Value::String(n) => n,
+        n => n.to_string(),
+    };
+    let (og_min, og_max) = range(&s.original_gravity, Quantity::sg)?;
+    let (fg_min, fg_max) = range(&s.final_gravity, Quantity::sg)?;
+    let (ibu_min, ibu_max) = range(&s.international_bitterness_units, Quantity::ibu)?;
+    let (color_min, color_max) = range(&s.color, Quantity::srm)?;
+    let (carb_min, carb_max) = range(&s.carbonation, Quantity::volumes)?;
+    let (abv_min, abv_max) = range(&s.alcohol_by_volume, Quantity::percent)?;
+    Ok(data::Style {
+        name: s.name,
+        version: 1,
+        category: s.category,
+        category_number,
+        style_letter: s.style_letter,
+        style_guide: s.style_guide,
+        type_,
+        og_min: og_min.unwrap_or_default(),
+        og_max: og_max.unwrap_or_default(),
+        fg_min: fg_min.unwrap_or_default(),
+        fg_max: fg_max.unwrap_or_default(),
+        ibu_min: ibu_min.unwrap_or_default(),
+        ibu_max: ibu_max.unwrap_or_default(),
+        color_min: color_min.unwrap_or_default(),
+        color_max: color_max.unwrap_or_default(),
+        carb_min,
+        carb_max,
+        abv_min,
+        abv_max,
+        notes: s.notes,
+        profile: s.flavor,
+        ingredients: s.ingredients,
+        examples: s.examples,
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn mash_step(s: MashStep) -> Result<data::MashStep> {
+    let type_ = match s.type_.to_lowercase().as_str() {
+        "sparge" | "drain mash tun" => "Infusion",
+        "souring mash" | "souring wort" => "Temperature",
+        _ => s.type_.as_str(),
+    };
+    let display = s.description.map(|d| {
+        data::MashStepDisplay { description: Some(d), ..Default::default() }
+    });
+    Ok(data::MashStep {
+        name: s.name,
+        version: 1,
+        type_: OpenEnum::parse_lenient(type_),
+        infuse_amount: map_o(&s.amount, Quantity::liters)?,
+        step_temp: Temperature(s.step_temperature.celsius()?),
+        step_time: Time(s.step_time.minutes()?),
+        ramp_time: map_o(&s.ramp_time, Quantity::minutes)?,
+        end_temp: map_o(&s.end_temperature, Quantity::celsius)?,
+        display,
+        extensions: Default::default(),
+    })
+}
+
+fn mash(m: Mash) -> Result<data::Mash> {
+    let mut mash_steps = vec![];
+    for s in m.mash_steps {
+        mash_steps.push(mash_step(s)?);
+    }
+    Ok(data::Mash {
+        name: m.name,
+        version: 1,
+        grain_temp: Temperature(m.grain_temperature.celsius()?),
+        notes: m.notes,
+        sparge_temp: map_o(&m.sparge_temperature, Quantity::celsius)?,
+        ph: map_o(&m.ph, Quantity::ph)?,
+        mash_steps,
+        ..Default::default()
+    })
+}
+
+fn equipment(e: Equipment) -> Result<data::Equipment> {
+    let mut res = data::Equipment {
+        name: e.name,
+        version: 1,
+        ..Default::default()
+    };
+    for item in e.equipment_items {
+        let volume = map_o(&item.maximum_volume, Quantity::liters)?;
+        match item.form.as_str() {
+            "Mash Tun" => {
+                res.tun_volume = volume;
+                res.tun_weight = map_o(&item.weight, Quantity::kg)?;
+                res.tun_specific_heat = map_o(&item.specific_heat, Quantity::specific_heat)?;
+                res.lauter_deadspace = Some(Volume(item.loss.liters()?));
+            }
+            "Brew Kettle" => {
+                res.boil_size = volume.unwrap_or_default();
+                res.trub_chiller_loss = Some(Volume(item.loss.liters()?));
+                let boil_rate = map_o(&item.boil_rate_per_hour, Quantity::liters)?;
+                if res.boil_size.0 > 0.0 {
+                    res.evap_rate = boil_rate.map(|r: f64| Percent(r * 100.0 / res.boil_size.0));
+                }
+                res.notes = item.notes;
+            }
+            "Fermenter" => res.batch_size = volume.unwrap_or_default(),
+            _ => warn!("Ignoring equipment item: {}", item.form),
+        }
+    }
+    Ok(res)
+}
+
+fn recipe(r: Recipe) -> Result<data::Recipe> {
+    use data::RecipeType;
+    let type_ = RecipeType::parse_with(&r.type_, |_| None);
+    let mut res = data::Recipe {
+        name: r.name,
+        version: 1,
+        type_,
+        brewer: r.author,
+        asst_brewer: r.coauthor,
+        date: r.created,
+        batch_size: Volume(r.batch_size.liters()?),
+        efficiency: match r.efficiency {
+            Some(e) => Some(Percent(e.brewhouse.percent()?)),
+            None => None,
+        },
+        notes: r.notes,
+        og: map_o(&r.original_gravity, Quantity::sg)?,
+        fg: map_o(&r.final_gravity, Quantity::sg)?,
+        carbonation: r.carbonation,
+        ..Default::default()
+    };
+    if let Some(s) = r.style {
+        res.style = style(s)?;
+    }
+    if let Some(m) = r.mash {
+        res.mash = Some(mash(m)?);
+    }
+    if let Some(b) = r.boil {
+        res.boil_size = map_o(&b.pre_boil_size, Quantity::liters)?.unwrap_or_default();
+        res.boil_time = Time(b.boil_time.minutes()?);
+    }
+    if let Some(t) = r.taste {
+        res.taste_notes = Some(t.notes);
+        res.taste_rating = Some(t.rating);
+    }
+    if let Some(f) = r.fermentation {
+        let mut stages = 0;
+        for step in f.fermentation_steps {
+            let age = map_o(&step.step_time, Quantity::days)?;
+            let temp = map_o(&step.start_temperature, Quantity::celsius)?;
+            if step.name == "Aging" {
+                res.age = age;
+                res.age_temp = temp;
+                continue;
+            }
+            stages += 1;
+            match stages {
+                1 => {
+                    res.primary_age = age;
+                    res.primary_temp = temp;
+                }
+                2 => {
+                    res.secondary_age = age;
+                    res.secondary_temp = temp;
+                }
+                3 => {
+                    res.tertiary_age = age;
+                    res.tertiary_temp = temp;
+                }
+                _ => warn!("Ignoring fermentation step: {}", step.name),
+            }
+        }
+        res.fermentation_stages = Some(stages);
+    }
+    let i = r.ingredients;
+    res.fermentables = convert(i.fermentable_additions, fermentable)?;
+    res.hops = convert(i.hop_additions, hop)?;
+    res.miscs = convert(i.miscellaneous_additions, misc)?;
+    res.yeasts = convert(i.culture_additions, culture)?;
+    res.waters = convert(i.water_additions, water)?;
+    Ok(res)
+}
+
+fn convert<T, U, F>(v: Vec<T>, convert: F) -> Result<Vec<U>>
+    where F: Fn(T) -> Result<U>
+{
+    v.into_iter().map(convert).collect()
+}
+
+fn insert<T, U, F>(map: &mut RecordMap<U>, v: Vec<T>, convert: F) -> Result<()>
+    where F: Fn(T) -> Result<U>,
+          U: Named
+{
+    for x in v {
+        let x = convert(x)?;
+        map.insert(x.name().into(), x);
+    }
+    Ok(())
+}
+
+fn document(b: BeerJson) -> Result<Document> {
+    let root = b.beerjson;
+    let mut doc = Document::default();
+    insert(&mut doc.equipments, root.equipments, equipment)?;
+    insert(&mut doc.fermentables, root.fermentables, fermentable)?;
+    insert(&mut doc.hops, root.hop_varieties, hop)?;
+    insert(&mut doc.miscs, root.miscellaneous_ingredients, misc)?;
+    insert(&mut doc.recipes, root.recipes, recipe)?;
+    insert(&mut doc.waters, root.profiles, water)?;
+    insert(&mut doc.yeasts, root.cultures, culture)?;
+    insert(&mut doc.styles, root.styles, style)?;
+    insert(&mut doc.mashs, root.mashes, mash)?;
+    Ok(doc)
+}
+
+/// try to read a `RecordSet` from a reader that contains BeerJSON
+pub fn read<B>(reader: B) -> Result<RecordSet>
+    where B: BufRead
+{
+    read_document(reader)?.into_record_set()
+}
+
+/// try to read a `RecordSet` from a BeerJSON file
+pub fn read_file(filename: &Path) -> Result<RecordSet> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read(reader)
+}
+
+/// try to read a `Document` from a reader that contains BeerJSON
+pub fn read_document<B>(reader: B) -> Result<Document>
+    where B: BufRead
+{
+    let b: BeerJson = serde_json::from_reader(reader)?;
+    document(b)
+}
+
+/// try to read a `Document` from a BeerJSON file
+pub fn read_document_file(filename: &Path) -> Result<Document> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read_document(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use beerjson;
+    use options::WriteOptions;
+    use xml;
+
+    fn round_trip(doc: &Document) -> (String, Document) {
+        let mut buf = vec![];
+        beerjson::write_document(&mut buf, doc, &WriteOptions::default()).unwrap();
+        let back = read_document(&buf[..]).unwrap();
+        (String::from_utf8(buf).unwrap(), back)
+    }
+
+    #[test]
+    fn round_trip_data_files() {
+        for entry in fs::read_dir("data").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("xml") {
+                continue;
+            }
+            let doc = xml::read_document_file(&path).unwrap();
+            let (_, back) = round_trip(&doc);
+            assert_eq!(back.kinds(), doc.kinds(), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn round_trip_recipe() {
+        let doc = xml::read_document_file(Path::new("data/recipes.xml")).unwrap();
+        let (json, back) = round_trip(&doc);
+        assert!(json.starts_with("{\n  \"beerjson\": {\n    \"version\": 1.0"));
+        assert!(json.contains("\"unit\": \"kg\""));
+        for (name, r) in doc.recipes.iter() {
+            let b = &back.recipes[name];
+            assert_eq!(b.style.name, r.style.name);
+            assert_eq!(b.style.og_max, r.style.og_max);
+            assert_eq!(b.batch_size, r.batch_size);
+            assert_eq!(b.primary_age, r.primary_age);
+            let names = |v: &[data::Hop]| v.iter().map(|h| h.name.clone()).collect::<Vec<_>>();
+            assert_eq!(names(&b.hops), names(&r.hops));
+            for (bh, rh) in b.hops.iter().zip(r.hops.iter()) {
+                assert_eq!((bh.amount, bh.time, bh.alpha), (rh.amount, rh.time, rh.alpha));
+            }
+            let steps = |m: &Option<data::Mash>| m.as_ref().map(|m| m.mash_steps.len());
+            assert_eq!(steps(&b.mash), steps(&r.mash));
+        }
+    }
+
+    #[test]
+    fn writes_what_the_schema_requires() {
+        let xml = "<EQUIPMENTS><EQUIPMENT><NAME>Pot</NAME><VERSION>1</VERSION>\
+                   <BOIL_SIZE>26</BOIL_SIZE><BATCH_SIZE>20</BATCH_SIZE>\
+                   <LAUTER_DEADSPACE>1</LAUTER_DEADSPACE></EQUIPMENT></EQUIPMENTS>\
+                   <RECIPES><RECIPE><NAME>Best</NAME><VERSION>1</VERSION>\
+                   <TYPE>Extract</TYPE><BREWER>Joost</BREWER><BATCH_SIZE>20</BATCH_SIZE>\
+                   </RECIPE></RECIPES>";
+        let doc = xml::read_document(xml.as_bytes()).unwrap();
+        let (json, _) = round_trip(&doc);
+        let json: Value = serde_json::from_str(&json).unwrap();
+        let items = json["beerjson"]["equipments"][0]["equipment_items"].as_array().unwrap();
+        assert_eq!(items[0]["form"], "Mash Tun");
+        for item in items {
+            assert!(item["maximum_volume"]["value"].is_number(), "{}", item);
+            assert!(item["loss"]["value"].is_number(), "{}", item);
+        }
+        assert_eq!(items[0]["maximum_volume"]["value"], 20.0);
+        let recipe = &json["beerjson"]["recipes"][0];
+        for field in &["name", "type", "author", "batch_size", "ingredients"] {
+            assert!(!recipe[field].is_null(), "{}", field);
+        }
+        assert_eq!(recipe["efficiency"]["brewhouse"]["value"], 75.0);
+    }
+
+    #[test]
+    fn read_units() {
+        let json = r#"{"beerjson": {"version": 1.0, "hop_varieties": [{
+            "name": "Saaz", "alpha_acid": {"unit": "%", "value": 3.5},
+            "amount": {"unit": "oz", "value": 1.0},
+            "timing": {"use": "add_to_fermentation", "duration": {"unit": "day", "value": 3}}
+        }]}}"#;
+        let doc = read_document(json.as_bytes()).unwrap();
+        let hop = &doc.hops["Saaz"];
+        assert!((hop.amount.ounces() - 1.0).abs() < 1e-6);
+        assert_eq!(hop.time.days(), 3.0);
+        assert_eq!(hop.use_.to_string(), "Dry Hop");
+
+        let json = r#"{"beerjson": {"version": 1.0, "fermentables": [{
+            "name": "Pils", "type": "grain", "yield": {}, "color": {"unit": "EBC", "value": 3.94},
+            "amount": {"unit": "lb", "value": 2}
+        }]}}"#;
+        let doc = read_document(json.as_bytes()).unwrap();
+        let pils = &doc.fermentables["Pils"];
+        assert!((pils.color.srm() - 2.0).abs() < 1e-9);
+        assert!((pils.amount.pounds() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn keeps_unknown_values() {
+        let json = r#"{"beerjson": {"version": 1.0,
+            "cultures": [{"name": "Lacto", "type": "bacteria", "form": "dregs"}],
+            "fermentables": [{"name": "Cherries", "type": "fruit", "yield": {},
+                              "color": {"unit": "SRM", "value": 2}}],
+            "hop_varieties": [{"name": "Saaz", "alpha_acid": {"unit": "%", "value": 3.5},
+                               "type": "aroma/flavor", "form": "powder"}]
+        }}"#;
+        let doc = read_document(json.as_bytes()).unwrap();
+        assert_eq!(doc.yeasts["Lacto"].type_.unknown(), Some("bacteria"));
+        assert_eq!(doc.yeasts["Lacto"].form.unknown(), Some("dregs"));
+        assert_eq!(doc.fermentables["Cherries"].type_.unknown(), Some("fruit"));
+        let hop = &doc.hops["Saaz"];
+        assert_eq!(hop.type_.as_ref().unwrap().to_string(), "Aroma");
+        assert_eq!(hop.form.as_ref().and_then(|f| f.unknown()), Some("powder"));
+
+        let (json, _) = round_trip(&doc);
+        for value in &["\"bacteria\"", "\"dregs\"", "\"fruit\"", "\"powder\""] {
+            assert!(json.contains(value), "{} in {}", value, json);
+        }
+    }
+}
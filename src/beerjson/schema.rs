@@ -0,0 +1,494 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! the subset of the BeerJSON 1.0 schema that maps onto the `data` records
+
+use serde_json::Value;
+
+use error::*;
+use units::{Dimension, Unit};
+
+/// the top-level BeerJSON object
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct BeerJson {
+    pub beerjson: Root,
+}
+
+/// the record lists of a BeerJSON document
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Root {
+    pub version: f64,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub equipments: Vec<Equipment>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub fermentables: Vec<Fermentable>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub hop_varieties: Vec<Hop>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub miscellaneous_ingredients: Vec<Misc>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub recipes: Vec<Recipe>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub profiles: Vec<Water>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub cultures: Vec<Culture>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub styles: Vec<Style>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub mashes: Vec<Mash>,
+}
+
+/// a unit tagged value, e.g. `{ "unit": "kg", "value": 1.5 }`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Quantity {
+    pub unit: String,
+    pub value: f64,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: &str) -> Quantity {
+        Quantity {
+            unit: unit.into(),
+            value,
+        }
+    }
+
+    fn unknown<T>(&self, what: &str) -> Result<T> {
+        Err(format!("BeerJSON: unknown {} unit {}", what, self.unit).into())
+    }
+
+    /// the value in the unit BeerXML stores `dimension` in, converted by `units::Unit`
+    fn base(&self, what: &str, dimension: Dimension) -> Result<f64> {
+        match self.unit.parse::<Unit>() {
+            Ok(unit) if unit.dimension() == dimension => Ok(unit.to_base(self.value)),
+            _ => self.unknown(what),
+        }
+    }
+
+    /// the value in a base unit, given the size of every known unit in that base unit; only
+    /// for what `units` has no dimension for, like concentrations or diastatic power
+    fn scaled(&self, what: &str, units: &[(&str, f64)]) -> Result<f64> {
+        match units.iter().find(|u| u.0.eq_ignore_ascii_case(&self.unit)) {
+            Some(u) => Ok(self.value * u.1),
+            None => self.unknown(what),
+        }
+    }
+
+    /// mass in kilograms
+    pub fn kg(&self) -> Result<f64> {
+        self.base("mass", Dimension::Mass)
+    }
+
+    /// volume in liters
+    pub fn liters(&self) -> Result<f64> {
+        self.base("volume", Dimension::Volume)
+    }
+
+    /// an amount that is either a mass in kilograms (`true`) or a volume in liters (`false`)
+    pub fn kg_or_liters(&self) -> Result<(f64, bool)> {
+        match self.kg() {
+            Ok(kg) => Ok((kg, true)),
+            Err(_) => self.liters().map(|l| (l, false)),
+        }
+    }
+
+    /// temperature in degrees Celsius
+    pub fn celsius(&self) -> Result<f64> {
+        self.base("temperature", Dimension::Temperature)
+    }
+
+    /// time in minutes
+    pub fn minutes(&self) -> Result<f64> {
+        self.base("time", Dimension::Time)
+    }
+
+    /// time in days
+    pub fn days(&self) -> Result<f64> {
+        self.minutes().map(|m| Unit::Day.from_base(m))
+    }
+
+    /// gravity as specific gravity
+    pub fn sg(&self) -> Result<f64> {
+        self.base("gravity", Dimension::Gravity)
+    }
+
+    /// color in SRM, which BeerXML also uses for degrees Lovibond
+    pub fn srm(&self) -> Result<f64> {
+        self.base("color", Dimension::Color)
+    }
+
+    /// percentage
+    pub fn percent(&self) -> Result<f64> {
+        self.base("percentage", Dimension::Percent)
+    }
+
+    /// concentration in parts per million
+    pub fn ppm(&self) -> Result<f64> {
+        self.scaled("concentration", &[("ppm", 1.0), ("mg/l", 1.0)])
+    }
+
+    /// carbonation in volumes of CO2
+    pub fn volumes(&self) -> Result<f64> {
+        self.scaled("carbonation", &[("vols", 1.0), ("g/l", 1.0 / 1.96)])
+    }
+
+    /// diastatic power in degrees Lintner
+    pub fn lintner(&self) -> Result<f64> {
+        match self.unit.as_str() {
+            "Lintner" => Ok(self.value),
+            "WK" => Ok((self.value + 16.0) / 3.5),
+            _ => self.unknown("diastatic power"),
+        }
+    }
+
+    /// specific heat in calories per gram-degree C
+    pub fn specific_heat(&self) -> Result<f64> {
+        self.scaled("specific heat",
+                    &[("Cal/(g C)", 1.0), ("BTU/(lb F)", 1.0), ("J/(kg K)", 1.0 / 4184.0)])
+    }
+
+    /// bitterness in IBU
+    pub fn ibu(&self) -> Result<f64> {
+        self.scaled("bitterness", &[("IBUs", 1.0)])
+    }
+
+    /// acidity in pH
+    pub fn ph(&self) -> Result<f64> {
+        self.scaled("acidity", &[("pH", 1.0)])
+    }
+}
+
+/// a range of unit tagged values
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Range {
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub minimum: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub maximum: Option<Quantity>,
+}
+
+/// when and how an ingredient is added
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Timing {
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub duration: Option<Quantity>,
+    #[serde(rename="use", skip_serializing_if="Option::is_none")]
+    pub use_: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub step: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FermentableYield {
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub fine_grind: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub fine_coarse_difference: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub potential: Option<Quantity>,
+}
+
+/// a fermentable, or a fermentable addition when `amount` is present
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Fermentable {
+    pub name: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub origin: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub producer: Option<String>,
+    #[serde(rename="yield")]
+    pub yield_: FermentableYield,
+    pub color: Quantity,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub moisture: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub diastatic_power: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub protein: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub max_in_batch: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub recommend_mash: Option<bool>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub amount: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub timing: Option<Timing>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct OilContent {
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub humulene: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub caryophyllene: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub cohumulone: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub myrcene: Option<Quantity>,
+}
+
+/// a hop variety, or a hop addition when `amount` is present
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Hop {
+    pub name: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub origin: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub form: Option<String>,
+    pub alpha_acid: Quantity,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub beta_acid: Option<Quantity>,
+    #[serde(rename="type", skip_serializing_if="Option::is_none")]
+    pub type_: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub percent_lost: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub substitutes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub oil_content: Option<OilContent>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub amount: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub timing: Option<Timing>,
+}
+
+/// a yeast or other culture, or a culture addition when `amount` is present
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Culture {
+    pub name: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    pub form: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub producer: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub product_id: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub temperature_range: Option<Range>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub flocculation: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub best_for: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub max_reuse: Option<i64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub amount: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub attenuation: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub times_cultured: Option<i64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub add_to_secondary: Option<bool>,
+}
+
+/// a miscellaneous ingredient, or an addition when `amount` is present
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Misc {
+    pub name: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub use_for: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub amount: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub timing: Option<Timing>,
+}
+
+/// a water profile, or a water addition when `amount` is present
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Water {
+    pub name: String,
+    pub calcium: Quantity,
+    pub bicarbonate: Quantity,
+    pub sulfate: Quantity,
+    pub chloride: Quantity,
+    pub sodium: Quantity,
+    pub magnesium: Quantity,
+    #[serde(rename="pH", skip_serializing_if="Option::is_none")]
+    pub ph: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub amount: Option<Quantity>,
+}
+
+/// a style; inside a recipe only the identifying fields are required
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Style {
+    pub name: String,
+    pub category: String,
+    pub category_number: Value,
+    pub style_letter: String,
+    pub style_guide: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub original_gravity: Option<Range>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub final_gravity: Option<Range>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub international_bitterness_units: Option<Range>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub color: Option<Range>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub carbonation: Option<Range>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub alcohol_by_volume: Option<Range>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub flavor: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub ingredients: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub examples: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MashStep {
+    pub name: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub amount: Option<Quantity>,
+    pub step_temperature: Quantity,
+    pub step_time: Quantity,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub ramp_time: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub end_temperature: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Mash {
+    pub name: String,
+    pub grain_temperature: Quantity,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(rename="pH", skip_serializing_if="Option::is_none")]
+    pub ph: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub sparge_temperature: Option<Quantity>,
+    #[serde(default)]
+    pub mash_steps: Vec<MashStep>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EquipmentItem {
+    pub name: String,
+    pub form: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub maximum_volume: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub weight: Option<Quantity>,
+    pub loss: Quantity,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub specific_heat: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub boil_rate_per_hour: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Equipment {
+    pub name: String,
+    #[serde(default)]
+    pub equipment_items: Vec<EquipmentItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Efficiency {
+    pub brewhouse: Quantity,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Ingredients {
+    pub fermentable_additions: Vec<Fermentable>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub hop_additions: Vec<Hop>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub miscellaneous_additions: Vec<Misc>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub culture_additions: Vec<Culture>,
+    #[serde(skip_serializing_if="Vec::is_empty")]
+    pub water_additions: Vec<Water>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Boil {
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub pre_boil_size: Option<Quantity>,
+    pub boil_time: Quantity,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FermentationStep {
+    pub name: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub start_temperature: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub step_time: Option<Quantity>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Fermentation {
+    pub name: String,
+    pub fermentation_steps: Vec<FermentationStep>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Taste {
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub rating: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Recipe {
+    pub name: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    pub author: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub coauthor: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub created: Option<String>,
+    pub batch_size: Quantity,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub efficiency: Option<Efficiency>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub style: Option<Style>,
+    pub ingredients: Ingredients,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub mash: Option<Mash>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub original_gravity: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub final_gravity: Option<Quantity>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub carbonation: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub fermentation: Option<Fermentation>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub taste: Option<Taste>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub boil: Option<Boil>,
+}
@@ -0,0 +1,451 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use serde_json;
+use serde_json::Value;
+
+use data;
+use data::{Document, RecordMap, RecordSet};
+use error::*;
+use units::{Percent, Volume};
+use options::WriteOptions;
+use super::schema::*;
+
+/// brewhouse efficiency written for a recipe that has none, as BeerJSON requires one
+const DEFAULT_EFFICIENCY: Percent = Percent(75.0);
+
+fn q<T: Into<f64>>(value: T, unit: &str) -> Quantity {
+    Quantity::new(value.into(), unit)
+}
+
+fn q_o<T: Into<f64>>(value: Option<T>, unit: &str) -> Option<Quantity> {
+    value.map(|v| q(v, unit))
+}
+
+fn range<T: Into<f64>>(minimum: Option<T>, maximum: Option<T>, unit: &str) -> Option<Range> {
+    if minimum.is_none() && maximum.is_none() {
+        return None;
+    }
+    Some(Range {
+        minimum: q_o(minimum, unit),
+        maximum: q_o(maximum, unit),
+    })
+}
+
+fn timing<T: Into<f64>>(duration: T, use_: &str, step: Option<i64>) -> Option<Timing> {
+    Some(Timing {
+        duration: Some(q(duration, "min")),
+        use_: Some(use_.into()),
+        step,
+    })
+}
+
+fn amount(amount: f64, is_weight: bool) -> Quantity {
+    if is_weight {
+        q(amount, "kg")
+    } else {
+        q(amount, "l")
+    }
+}
+
+fn fermentable(f: &data::Fermentable) -> Fermentable {
+    use data::FermentableType::*;
+    let type_ = match f.type_ {
+        Grain => "grain",
+        Sugar => "sugar",
+        Extract => "extract",
+        DryExtract => "dry extract",
+        Adjunct => "other",
+        Other(ref v) => v.as_str(),
+    };
+    let potential = f.display.as_ref().and_then(|d| d.potential);
+    Fermentable {
+        name: f.name.clone(),
+        type_: type_.into(),
+        origin: f.origin.clone(),
+        producer: f.supplier.clone(),
+        yield_: FermentableYield {
+            fine_grind: Some(q(f.yield_, "%")),
+            fine_coarse_difference: q_o(f.coarse_fine_diff, "%"),
+            potential: q_o(potential, "sg"),
+        },
+        color: q(f.color, "Lovi"),
+        notes: f.notes.clone(),
+        moisture: q_o(f.moisture, "%"),
+        diastatic_power: q_o(f.diastatic_power, "Lintner"),
+        protein: q_o(f.protein, "%"),
+        max_in_batch: q_o(f.max_in_batch, "%"),
+        recommend_mash: Some(f.recommend_mash),
+        amount: Some(q(f.amount, "kg")),
+        timing: if f.add_after_boil {
+            Some(Timing { use_: Some("add_to_fermentation".into()), ..Timing::default() })
+        } else {
+            None
+        },
+    }
+}
+
+fn hop(h: &data::Hop) -> Hop {
+    use data::HopUse;
+    let use_ = match h.use_ {
+        HopUse::Mash => "add_to_mash",
+        HopUse::DryHop => "add_to_fermentation",
+        HopUse::Aroma | HopUse::Boil | HopUse::FirstWort | HopUse::Other(_) => "add_to_boil",
+    };
+    let type_ = h.type_.as_ref().map(|t| {
+        match *t {
+            data::HopType::Bittering => "bittering",
+            data::HopType::Aroma => "aroma",
+            data::HopType::Both => "aroma/bittering",
+            data::HopType::Other(ref v) => v.as_str(),
+        }
+    });
+    let form = h.form.as_ref().map(|f| {
+        match *f {
+            data::HopForm::Pellet => "pellet",
+            data::HopForm::Plug => "plug",
+            data::HopForm::Leaf => "leaf",
+            data::HopForm::Other(ref v) => v.as_str(),
+        }
+    });
+    let oil_content = if h.humulene.is_some() || h.caryophyllene.is_some() ||
+                         h.cohumulone.is_some() || h.myrcene.is_some() {
+        Some(OilContent {
+            humulene: q_o(h.humulene, "%"),
+            caryophyllene: q_o(h.caryophyllene, "%"),
+            cohumulone: q_o(h.cohumulone, "%"),
+            myrcene: q_o(h.myrcene, "%"),
+        })
+    } else {
+        None
+    };
+    Hop {
+        name: h.name.clone(),
+        origin: h.origin.clone(),
+        form: form.map(Into::into),
+        alpha_acid: q(h.alpha, "%"),
+        beta_acid: q_o(h.beta, "%"),
+        type_: type_.map(Into::into),
+        notes: h.notes.clone(),
+        percent_lost: q_o(h.hsi, "%"),
+        substitutes: h.substitutes.clone(),
+        oil_content,
+        amount: Some(q(h.amount, "kg")),
+        timing: timing(h.time, use_, None),
+    }
+}
+
+fn culture(y: &data::Yeast) -> Culture {
+    use data::{YeastFlocculation, YeastForm, YeastType};
+    let type_ = match y.type_ {
+        YeastType::Ale | YeastType::Wheat => "ale",
+        YeastType::Lager => "lager",
+        YeastType::Wine => "wine",
+        YeastType::Champagne => "champagne",
+        YeastType::Other(ref v) => v.as_str(),
+    };
+    let form = match y.form {
+        YeastForm::Liquid => "liquid",
+        YeastForm::Dry => "dry",
+        YeastForm::Slate => "slant",
+        YeastForm::Culture => "culture",
+        YeastForm::Other(ref v) => v.as_str(),
+    };
+    let flocculation = y.flocculation.as_ref().map(|f| {
+        match *f {
+            YeastFlocculation::Low => "low",
+            YeastFlocculation::Medium => "medium",
+            YeastFlocculation::High => "high",
+            YeastFlocculation::VeryHigh => "very high",
+            YeastFlocculation::Other(ref v) => v.as_str(),
+        }
+    });
+    Culture {
+        name: y.name.clone(),
+        type_: type_.into(),
+        form: form.into(),
+        producer: y.laboratory.clone(),
+        product_id: y.product_id.clone(),
+        temperature_range: range(y.min_temperature, y.max_temperature, "C"),
+        flocculation: flocculation.map(Into::into),
+        notes: y.notes.clone(),
+        best_for: y.best_for.clone(),
+        max_reuse: y.max_reuse,
+        amount: Some(amount(y.amount, y.amount_is_weight)),
+        attenuation: q_o(y.attenuation, "%"),
+        times_cultured: y.times_cultured,
+        add_to_secondary: Some(y.add_to_secondary),
+    }
+}
+
+fn misc(m: &data::Misc) -> Misc {
+    use data::{MiscType, MiscUse};
+    let type_ = match m.type_ {
+        MiscType::Spice => "spice",
+        MiscType::Fining => "fining",
+        MiscType::WaterAgent => "water agent",
+        MiscType::Herb => "herb",
+        MiscType::Flavor => "flavor",
+        MiscType::Other => "other",
+        MiscType::Unknown(ref v) => v.as_str(),
+    };
+    let (use_, step) = match m.use_ {
+        MiscUse::Boil | MiscUse::Other(_) => ("add_to_boil", None),
+        MiscUse::Mash => ("add_to_mash", None),
+        MiscUse::Primary => ("add_to_fermentation", Some(1)),
+        MiscUse::Secondary => ("add_to_fermentation", Some(2)),
+        MiscUse::Bottling => ("add_to_package", None),
+    };
+    Misc {
+        name: m.name.clone(),
+        type_: type_.into(),
+        use_for: m.use_for.clone(),
+        notes: m.notes.clone(),
+        amount: Some(amount(m.amount, m.amount_is_weight)),
+        timing: timing(m.time, use_, step),
+    }
+}
+
+fn water(w: &data::Water) -> Water {
+    Water {
+        name: w.name.clone(),
+        calcium: q(w.calcium, "ppm"),
+        bicarbonate: q(w.bicarbonate, "ppm"),
+        sulfate: q(w.sulfate, "ppm"),
+        chloride: q(w.chloride, "ppm"),
+        sodium: q(w.sodium, "ppm"),
+        magnesium: q(w.magnesium, "ppm"),
+        ph: q_o(w.ph, "pH"),
+        notes: w.notes.clone(),
+        amount: Some(q(w.amount, "l")),
+    }
+}
+
+fn style(s: &data::Style) -> Style {
+    use data::StyleType;
+    let type_ = match s.type_ {
+        StyleType::Lager | StyleType::Ale | StyleType::Wheat | StyleType::Mixed => "beer",
+        StyleType::Mead => "mead",
+        StyleType::Cider => "cider",
+        StyleType::Other(ref v) => v.as_str(),
+    };
+    let category_number = match s.category_number.parse::<i64>() {
+        Ok(n) => Value::from(n),
+        Err(_) => Value::from(s.category_number.clone()),
+    };
+    Style {
+        name: s.name.clone(),
+        category: s.category.clone(),
+        category_number,
+        style_letter: s.style_letter.clone(),
+        style_guide: s.style_guide.clone(),
+        type_: type_.into(),
+        original_gravity: range(Some(s.og_min), Some(s.og_max), "sg"),
+        final_gravity: range(Some(s.fg_min), Some(s.fg_max), "sg"),
+        international_bitterness_units: range(Some(s.ibu_min), Some(s.ibu_max), "IBUs"),
+        color: range(Some(s.color_min), Some(s.color_max), "SRM"),
+        carbonation: range(s.carb_min, s.carb_max, "vols"),
+        alcohol_by_volume: range(s.abv_min, s.abv_max, "%"),
+        notes: s.notes.clone(),
+        flavor: s.profile.clone(),
+        ingredients: s.ingredients.clone(),
+        examples: s.examples.clone(),
+    }
+}
+
+fn mash_step(s: &data::MashStep) -> MashStep {
+    MashStep {
+        name: s.name.clone(),
+        type_: s.type_.to_string().to_lowercase(),
+        amount: q_o(s.infuse_amount, "l"),
+        step_temperature: q(s.step_temp, "C"),
+        step_time: q(s.step_time, "min"),
+        ramp_time: q_o(s.ramp_time, "min"),
+        end_temperature: q_o(s.end_temp, "C"),
+        description: s.display.as_ref().and_then(|d| d.description.clone()),
+    }
+}
+
+fn mash(m: &data::Mash) -> Mash {
+    Mash {
+        name: m.name.clone(),
+        grain_temperature: q(m.grain_temp, "C"),
+        notes: m.notes.clone(),
+        ph: q_o(m.ph, "pH"),
+        sparge_temperature: q_o(m.sparge_temp, "C"),
+        mash_steps: m.mash_steps.iter().map(mash_step).collect(),
+    }
+}
+
+fn equipment(e: &data::Equipment) -> Equipment {
+    let item = |form: &str, volume: Volume, loss: Option<Volume>| {
+        EquipmentItem {
+            name: e.name.clone(),
+            form: form.into(),
+            maximum_volume: Some(q(volume, "l")),
+            weight: None,
+            loss: q(loss.unwrap_or_default(), "l"),
+            specific_heat: None,
+            boil_rate_per_hour: None,
+            notes: None,
+        }
+    };
+    let mut items = vec![];
+    if e.tun_volume.is_some() || e.tun_weight.is_some() || e.tun_specific_heat.is_some() ||
+       e.lauter_deadspace.is_some() {
+        // BeerJSON requires a volume, so without a tun volume the batch has to fit
+        items.push(EquipmentItem {
+            maximum_volume: Some(q(e.tun_volume.unwrap_or(e.batch_size), "l")),
+            weight: q_o(e.tun_weight, "kg"),
+            specific_heat: q_o(e.tun_specific_heat, "Cal/(g C)"),
+            ..item("Mash Tun", Volume(0.0), e.lauter_deadspace)
+        });
+    }
+    items.push(EquipmentItem {
+        boil_rate_per_hour: q_o(e.evap_rate.map(|r| r.fraction() * e.boil_size.0), "l"),
+        notes: e.notes.clone(),
+        ..item("Brew Kettle", e.boil_size, e.trub_chiller_loss)
+    });
+    items.push(item("Fermenter", e.batch_size, None));
+    Equipment {
+        name: e.name.clone(),
+        equipment_items: items,
+    }
+}
+
+fn recipe(r: &data::Recipe) -> Recipe {
+    use data::RecipeType;
+    let type_ = match r.type_ {
+        RecipeType::Extract => "extract",
+        RecipeType::PartialMash => "partial mash",
+        RecipeType::AllGrain => "all grain",
+        RecipeType::Other(ref v) => v.as_str(),
+    };
+    let stages = [("Primary", r.primary_age, r.primary_temp),
+                  ("Secondary", r.secondary_age, r.secondary_temp),
+                  ("Tertiary", r.tertiary_age, r.tertiary_temp),
+                  ("Aging", r.age, r.age_temp)];
+    let steps: Vec<FermentationStep> = stages.iter()
+        .filter(|s| s.1.is_some() || s.2.is_some())
+        .map(|s| {
+            FermentationStep {
+                name: s.0.into(),
+                start_temperature: q_o(s.2, "C"),
+                step_time: q_o(s.1, "day"),
+            }
+        })
+        .collect();
+    let fermentation = if steps.is_empty() {
+        None
+    } else {
+        Some(Fermentation {
+            name: r.name.clone(),
+            fermentation_steps: steps,
+        })
+    };
+    let taste = if r.taste_notes.is_some() || r.taste_rating.is_some() {
+        Some(Taste {
+            notes: r.taste_notes.clone().unwrap_or_default(),
+            rating: r.taste_rating.unwrap_or_default(),
+        })
+    } else {
+        None
+    };
+    Recipe {
+        name: r.name.clone(),
+        type_: type_.into(),
+        author: r.brewer.clone(),
+        coauthor: r.asst_brewer.clone(),
+        created: r.date.clone(),
+        batch_size: q(r.batch_size, "l"),
+        efficiency: Some(Efficiency {
+            brewhouse: q(r.efficiency.unwrap_or(DEFAULT_EFFICIENCY), "%"),
+        }),
+        style: Some(style(&r.style)),
+        ingredients: Ingredients {
+            fermentable_additions: r.fermentables.iter().map(fermentable).collect(),
+            hop_additions: r.hops.iter().map(hop).collect(),
+            miscellaneous_additions: r.miscs.iter().map(misc).collect(),
+            culture_additions: r.yeasts.iter().map(culture).collect(),
+            water_additions: r.waters.iter().map(water).collect(),
+        },
+        mash: r.mash.as_ref().map(mash),
+        notes: r.notes.clone(),
+        original_gravity: q_o(r.og, "sg"),
+        final_gravity: q_o(r.fg, "sg"),
+        carbonation: r.carbonation,
+        fermentation,
+        taste,
+        boil: Some(Boil {
+            pre_boil_size: Some(q(r.boil_size, "l")),
+            boil_time: q(r.boil_time, "min"),
+        }),
+    }
+}
+
+fn list<T, U, F>(map: &RecordMap<T>, convert: F) -> Vec<U>
+    where F: Fn(&T) -> U
+{
+    map.iter().map(|(_, x)| convert(x)).collect()
+}
+
+fn beerjson(doc: &Document) -> BeerJson {
+    BeerJson {
+        beerjson: Root {
+            version: 1.0,
+            equipments: list(&doc.equipments, equipment),
+            fermentables: list(&doc.fermentables, fermentable),
+            hop_varieties: list(&doc.hops, hop),
+            miscellaneous_ingredients: list(&doc.miscs, misc),
+            recipes: list(&doc.recipes, recipe),
+            profiles: list(&doc.waters, water),
+            cultures: list(&doc.yeasts, culture),
+            styles: list(&doc.styles, style),
+            mashes: list(&doc.mashs, mash),
+        },
+    }
+}
+
+/// try to write a `RecordSet` to a writer as BeerJSON
+pub fn write<T>(writer: &mut T, set: &RecordSet) -> Result<()>
+    where T: Write
+{
+    write_with(writer, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` to a writer as BeerJSON using the given `WriteOptions`
+pub fn write_with<T>(writer: &mut T, set: &RecordSet, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    let doc = Document::from(options.prepare_set(set).into_owned());
+    serde_json::to_writer_pretty(writer, &beerjson(&doc))?;
+    Ok(())
+}
+
+/// try to write a `RecordSet` to a BeerJSON file
+pub fn write_file(filename: &Path, set: &RecordSet) -> Result<()> {
+    write_file_with(filename, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` to a BeerJSON file using the given `WriteOptions`
+pub fn write_file_with(filename: &Path, set: &RecordSet, options: &WriteOptions) -> Result<()> {
+    let mut f = File::create(filename)?;
+    write_with(&mut f, set, options)
+}
+
+/// try to write a `Document` to a writer as BeerJSON
+pub fn write_document<T>(writer: &mut T, doc: &Document, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    serde_json::to_writer_pretty(writer, &beerjson(&options.prepare_document(doc)))?;
+    Ok(())
+}
+
+/// try to write a `Document` to a BeerJSON file
+pub fn write_document_file(filename: &Path, doc: &Document, options: &WriteOptions) -> Result<()> {
+    let mut f = File::create(filename)?;
+    write_document(&mut f, doc, options)
+}
@@ -10,9 +10,13 @@ extern crate beerxml;
 
 use std::path::Path;
 use std::env;
+use std::io;
 
 use clap::{Arg, App};
 
+use beerxml::Format;
+use beerxml::options::{Order, WriteOptions};
+
 fn main() {
     let m = App::new("convert")
         .version(env!("CARGO_PKG_VERSION"))
@@ -21,11 +25,40 @@ fn main() {
         .arg(Arg::with_name("FILE1")
             .required(true)
             .index(1)
-            .help("filename to convert from"))
+            .help("filename to convert from, - for stdin"))
         .arg(Arg::with_name("FILE2")
             .required(true)
             .index(2)
-            .help("filename to convert to"))
+            .help("filename to convert to, - for stdout"))
+        .arg(Arg::with_name("from")
+            .long("from")
+            .takes_value(true)
+            .help("format to read, e.g. brewfather, instead of guessing it from the extension"))
+        .arg(Arg::with_name("to")
+            .long("to")
+            .takes_value(true)
+            .help("format to write, e.g. brewfather, instead of guessing it from the extension"))
+        .arg(Arg::with_name("no-display")
+            .long("no-display")
+            .help("omit the optional display and estimated fields"))
+        .arg(Arg::with_name("units")
+            .long("units")
+            .takes_value(true)
+            .help("regenerate the display fields in metric, us or imperial units"))
+        .arg(Arg::with_name("sort")
+            .long("sort")
+            .help("write the records sorted by name instead of in input order"))
+        .arg(Arg::with_name("cdata")
+            .long("cdata")
+            .help("write multiline text as CDATA sections (XML only)"))
+        .arg(Arg::with_name("indent")
+            .long("indent")
+            .takes_value(true)
+            .help("number of spaces per nesting level, 0 for a single line (XML only)"))
+        .arg(Arg::with_name("encoding")
+            .long("encoding")
+            .takes_value(true)
+            .help("output encoding, e.g. ISO-8859-1 (XML only)"))
         .get_matches();
 
     env::set_var("RUST_LOG", "info");
@@ -34,9 +67,43 @@ fn main() {
 
     let filename_from = m.value_of("FILE1").unwrap();
     let filename_to = m.value_of("FILE2").unwrap();
+    let format_from: Option<Format> = m.value_of("from").map(|f| f.parse().unwrap());
+    let format_to = match m.value_of("to") {
+        Some(f) => f.parse().unwrap(),
+        None if filename_to == "-" => Format::Xml,
+        None => Format::from_path(Path::new(filename_to)).unwrap(),
+    };
 
-    let content = beerxml::read_file(Path::new(filename_from)).unwrap();
+    let content = match (filename_from, format_from) {
+        ("-", f) => beerxml::read_document_from(io::stdin(), f).unwrap(),
+        (f, Some(format)) => beerxml::read_document_file_as(Path::new(f), format).unwrap(),
+        (f, None) => beerxml::read_document_file(Path::new(f)).unwrap(),
+    };
     info!("Content: {:?}", content);
-    beerxml::write_file(Path::new(filename_to), &content).unwrap();
-    info!("{} created.", filename_to);
+    let mut options = WriteOptions {
+        display: !m.is_present("no-display"),
+        order: if m.is_present("sort") {
+            Order::Name
+        } else {
+            Order::Input
+        },
+        cdata: m.is_present("cdata"),
+        ..WriteOptions::default()
+    };
+    if let Some(indent) = m.value_of("indent") {
+        options.indent = indent.parse().unwrap();
+    }
+    if let Some(units) = m.value_of("units") {
+        options.units = Some(units.parse().unwrap());
+    }
+    if let Some(encoding) = m.value_of("encoding") {
+        options.encoding = Some(encoding.into());
+    }
+    if filename_to == "-" {
+        beerxml::write_document_to(&mut io::stdout(), &content, format_to, &options).unwrap();
+    } else {
+        beerxml::write_document_file_as(Path::new(filename_to), &content, format_to, &options)
+            .unwrap();
+        info!("{} created.", filename_to);
+    }
 }
@@ -30,6 +30,6 @@ fn main() {
 
     let filename = m.value_of("FILE").unwrap();
 
-    let content = beerxml::xml::read_file(Path::new(filename)).unwrap();
+    let content = beerxml::xml::read_document_file(Path::new(filename)).unwrap();
     info!("Content: {:?}", content);
 }
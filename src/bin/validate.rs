@@ -0,0 +1,78 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+extern crate clap;
+extern crate env_logger;
+
+extern crate beerxml;
+
+use std::path::Path;
+use std::process;
+
+use clap::{Arg, App};
+
+use beerxml::Format;
+use beerxml::validate::{self, Severity};
+
+fn main() {
+    let m = App::new("validate")
+        .version(env!("CARGO_PKG_VERSION"))
+        .author("Joost Yervante Damad <joost@damad.be>")
+        .about("Check files against the BeerXML 1.0 specification")
+        .arg(Arg::with_name("FILE")
+            .required(true)
+            .multiple(true)
+            .index(1)
+            .help("filenames to check"))
+        .arg(Arg::with_name("from")
+            .long("from")
+            .takes_value(true)
+            .help("format to read, e.g. brewfather, instead of guessing it from the extension"))
+        .arg(Arg::with_name("errors")
+            .long("errors")
+            .help("only show errors, not warnings"))
+        .get_matches();
+
+    env_logger::init().unwrap();
+
+    let format_from: Option<Format> = match m.value_of("from") {
+        Some(f) => match f.parse() {
+            Ok(format) => Some(format),
+            Err(_) => {
+                eprintln!("error: unknown format `{}`", f);
+                process::exit(2);
+            }
+        },
+        None => None,
+    };
+    let mut failed = false;
+    for filename in m.values_of("FILE").unwrap() {
+        let path = Path::new(filename);
+        // xml is checked as read, so unknown and missing elements get a line and column
+        let violations = match format_from {
+            Some(Format::Xml) => validate::validate_xml_file(path),
+            Some(format) => beerxml::read_document_file_as(path, format)
+                .map(|doc| validate::validate_document(&doc)),
+            None if Format::from_path(path).ok() == Some(Format::Xml) => {
+                validate::validate_xml_file(path)
+            }
+            None => beerxml::read_document_file(path).map(|doc| validate::validate_document(&doc)),
+        };
+        let violations = match violations {
+            Ok(violations) => violations,
+            Err(e) => {
+                println!("{}: error: {}", filename, e);
+                failed = true;
+                continue;
+            }
+        };
+        for v in &violations {
+            if v.severity == Severity::Error || !m.is_present("errors") {
+                println!("{}: {}", filename, v);
+            }
+            failed |= v.severity == Severity::Error;
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,22 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! Brewfather exports recipes and batches as plain `.json`, so this format is
+//! selected with `Format::Brewfather` or recognised by its content. A file holds a
+//! recipe, a batch or a list of them; only recipes are written. The mapping is
+//! lossy in places:
+//!
+//! * a batch is read as the recipe it brewed, named `<recipe> #<batch number>`,
+//!   with the brewer, brew date and measured gravities of the batch
+//! * hop amounts are grams, misc and yeast amounts are written as grams or milliliters;
+//!   a count of `items` or `pkg` is read as `0`, with the count and unit kept as `amount`
+//!   and `unit` extensions that the writer uses to write the count back
+//! * only the source water profile is kept, without an amount
+//! * fermentation steps other than primary, secondary and conditioning are skipped
+//! * the Appendix A display and estimated fields are not written
+
+mod read;
+mod schema;
+mod write;
+
+pub use self::read::*;
+pub use self::write::*;
@@ -0,0 +1,504 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::io::{BufRead, BufReader};
+use std::fs::File;
+use std::path::Path;
+
+use serde_json;
+use serde_json::Value;
+
+use data;
+use data::{Document, Extension, Extensions, Named, OpenEnum, RecordSet};
+use error::*;
+use units::{Color, Dimension, Gravity, Mass, Percent, Temperature, Time, Unit, Volume};
+use super::schema::*;
+
+fn unknown<T>(what: &str, value: &str) -> Result<T> {
+    Err(ErrorKind::ParseError(format!("Brewfather {}", what), value.into()).into())
+}
+
+/// an amount in kilograms or liters and whether it is a weight; BeerXML has no unit for a
+/// number of items or packages, so `None` for a count, which is kept as it was read in the
+/// extensions of the record
+fn amount(amount: f64, unit: &str, extensions: &mut Extensions) -> Result<Option<(f64, bool)>> {
+    if unit.eq_ignore_ascii_case("items") || unit.eq_ignore_ascii_case("pkg") {
+        for &(tag, ref content) in &[("amount", amount.to_string()), ("unit", unit.into())] {
+            extensions.push(Extension {
+                tag: tag.into(),
+                after: "AMOUNT".into(),
+                content: Some(content.clone()),
+            });
+        }
+        return Ok(None);
+    }
+    match unit.parse::<Unit>().map(|u| (u, u.dimension())) {
+        Ok((u, Dimension::Mass)) => Ok(Some((u.to_base(amount), true))),
+        Ok((u, Dimension::Volume)) => Ok(Some((u.to_base(amount), false))),
+        _ => unknown("unit", unit),
+    }
+}
+
+/// the date of a unix timestamp in milliseconds as `YYYY-MM-DD`
+fn date(ms: i64) -> String {
+    // civil from days, http://howardhinnant.github.io/date_algorithms.html
+    let days = ms / 86_400_000 - if ms % 86_400_000 < 0 { 1 } else { 0 };
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn fermentable(f: Fermentable) -> Result<data::Fermentable> {
+    let type_ = OpenEnum::parse_with(&f.type_, |t| match t {
+        "honey" => Some(data::FermentableType::Sugar),
+        "other" => Some(data::FermentableType::Adjunct),
+        _ => None,
+    });
+    Ok(data::Fermentable {
+        name: f.name,
+        version: 1,
+        type_,
+        amount: Mass(f.amount),
+        yield_: Percent(f.potential_percentage),
+        color: Color(f.color),
+        add_after_boil: match f.use_ {
+            Some(u) => u.eq_ignore_ascii_case("fermentation"),
+            None => false,
+        },
+        origin: f.origin,
+        supplier: f.supplier,
+        notes: f.notes,
+        coarse_fine_diff: f.coarse_fine_diff.map(Percent),
+        moisture: f.moisture.map(Percent),
+        diastatic_power: f.diastatic_power,
+        protein: f.protein.map(Percent),
+        max_in_batch: f.max_in_batch.map(Percent),
+        recommend_mash: false,
+        ibu_gal_per_lb: None,
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn hop(h: Hop) -> Result<data::Hop> {
+    let use_ = OpenEnum::parse_with(&h.use_, |_| None);
+    let time = match (&use_, h.day) {
+        (&data::HopUse::DryHop, Some(day)) => Time::from_days(day),
+        _ => Time(h.time),
+    };
+    let form = h.type_.as_ref().map(|f| {
+        OpenEnum::parse_with(f, |f| match f {
+            "cryo" => Some(data::HopForm::Pellet),
+            _ => None,
+        })
+    });
+    let type_ = h.usage.as_ref().map(|u| OpenEnum::parse_with(u, |_| None));
+    Ok(data::Hop {
+        name: h.name,
+        version: 1,
+        alpha: Percent(h.alpha),
+        amount: Mass::from_grams(h.amount),
+        use_,
+        time,
+        notes: h.notes,
+        type_,
+        form,
+        beta: h.beta.map(Percent),
+        origin: h.origin,
+        ..Default::default()
+    })
+}
+
+fn misc(m: Misc) -> Result<data::Misc> {
+    let type_ = OpenEnum::parse_with(&m.type_, |_| None);
+    let use_ = OpenEnum::parse_with(&m.use_, |u| match u {
+        "sparge" => Some(data::MiscUse::Mash),
+        "flameout" => Some(data::MiscUse::Boil),
+        _ => None,
+    });
+    let mut extensions = Extensions::default();
+    let (amount, amount_is_weight) = amount(m.amount, &m.unit, &mut extensions)?
+        .unwrap_or((0.0, false));
+    Ok(data::Misc {
+        name: m.name,
+        version: 1,
+        type_,
+        use_,
+        time: Time(m.time),
+        amount,
+        amount_is_weight,
+        use_for: None,
+        notes: m.notes,
+        display: None,
+        extensions,
+    })
+}
+
+fn yeast(y: Yeast) -> Result<data::Yeast> {
+    let type_ = OpenEnum::parse_with(&y.type_, |t| match t {
+        "hybrid" | "kveik" | "other" => Some(data::YeastType::Ale),
+        _ => None,
+    });
+    let form = OpenEnum::parse_with(&y.form, |f| match f {
+        "slant" => Some(data::YeastForm::Slate),
+        _ => None,
+    });
+    let flocculation = y.flocculation.as_ref().map(|f| {
+        OpenEnum::parse_with(f, |f| match f {
+            "very high" => Some(data::YeastFlocculation::VeryHigh),
+            "medium-high" => Some(data::YeastFlocculation::High),
+            "medium-low" => Some(data::YeastFlocculation::Medium),
+            _ => None,
+        })
+    });
+    let mut extensions = Extensions::default();
+    let (amount, amount_is_weight) = amount(y.amount, &y.unit, &mut extensions)?
+        .unwrap_or((0.0, y.form.eq_ignore_ascii_case("dry")));
+    Ok(data::Yeast {
+        name: y.name,
+        version: 1,
+        type_,
+        form,
+        amount,
+        amount_is_weight,
+        laboratory: y.laboratory,
+        product_id: y.product_id,
+        min_temperature: y.min_temp.map(Temperature),
+        max_temperature: y.max_temp.map(Temperature),
+        flocculation,
+        attenuation: y.attenuation.map(Percent),
+        notes: y.description,
+        extensions,
+        ..Default::default()
+    })
+}
+
+fn water(w: WaterProfile) -> data::Water {
+    data::Water {
+        name: w.name,
+        version: 1,
+        amount: Volume(0.0),
+        calcium: w.calcium,
+        bicarbonate: w.bicarbonate,
+        sulfate: w.sulfate,
+        chloride: w.chloride,
+        sodium: w.sodium,
+        magnesium: w.magnesium,
+        ph: w.ph,
+        notes: None,
+        display: None,
+        extensions: Default::default(),
+    }
+}
+
+fn style(s: Style) -> Result<data::Style> {
+    let type_ = OpenEnum::parse_with(&s.type_, |t| match t {
+        "beer" => Some(data::StyleType::Ale),
+        "wine" | "kombucha" | "soda" | "other" => Some(data::StyleType::Mixed),
+        _ => None,
+    });
+    let category_number = match s.category_number {
+        Value::String(n) => n,
+        Value::Null => String::new(),
+        n => n.to_string(),
+    };
+    Ok(data::Style {
+        name: s.name,
+        version: 1,
+        category: s.category,
+        category_number,
+        style_letter: s.style_letter,
+        style_guide: s.style_guide,
+        type_,
+        og_min: Gravity(s.og_min),
+        og_max: Gravity(s.og_max),
+        fg_min: Gravity(s.fg_min),
+        fg_max: Gravity(s.fg_max),
+        ibu_min: s.ibu_min,
+        ibu_max: s.ibu_max,
+        color_min: Color(s.color_min),
+        color_max: Color(s.color_max),
+        carb_min: s.carb_min,
+        carb_max: s.carb_max,
+        abv_min: s.abv_min.map(Percent),
+        abv_max: s.abv_max.map(Percent),
+        notes: s.notes,
+        ..Default::default()
+    })
+}
+
+fn equipment(e: Equipment) -> data::Equipment {
+    let evap_rate = match e.boil_off_per_hr {
+        Some(off) if e.boil_size > 0.0 => Some(off * 100.0 / e.boil_size),
+        _ => None,
+    };
+    data::Equipment {
+        name: e.name,
+        version: 1,
+        boil_size: Volume(e.boil_size),
+        batch_size: Volume(e.batch_size),
+        top_up_water: e.top_up_water.map(Volume),
+        trub_chiller_loss: e.trub_chiller_loss.map(Volume),
+        evap_rate: evap_rate.map(Percent),
+        boil_time: Some(Time(e.boil_time)),
+        lauter_deadspace: e.mash_tun_dead_space.map(Volume),
+        hop_utilization: e.hop_utilization.map(Percent),
+        notes: e.notes,
+        ..Default::default()
+    }
+}
+
+fn mash_step(n: usize, s: MashStep) -> Result<data::MashStep> {
+    let type_ = match s.type_.to_lowercase().as_str() {
+        "infusion" => "Infusion",
+        "decoction" => "Decoction",
+        "temperature" | "sparge" | "mash out" => "Temperature",
+        t => return unknown("mash step type", t),
+    };
+    Ok(data::MashStep {
+        name: s.name.unwrap_or_else(|| format!("Step {}", n + 1)),
+        version: 1,
+        type_: type_.parse()?,
+        infuse_amount: s.infuse_amount.map(Volume),
+        step_temp: Temperature(s.step_temp),
+        step_time: Time(s.step_time),
+        ramp_time: s.ramp_time.map(Time),
+        end_temp: None,
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn mash(m: Mash) -> Result<data::Mash> {
+    let steps = m.steps.into_iter().enumerate().map(|(n, s)| mash_step(n, s));
+    Ok(data::Mash {
+        name: m.name,
+        version: 1,
+        grain_temp: Temperature(m.grain_temp.unwrap_or_default()),
+        notes: m.notes,
+        ph: m.ph,
+        mash_steps: steps.collect::<Result<_>>()?,
+        ..Default::default()
+    })
+}
+
+/// fill in the fermentation stages; cold crashes and the like are not kept
+fn fermentation(f: Fermentation, r: &mut data::Recipe) {
+    for s in f.steps {
+        let (age, temp) = match s.type_.to_lowercase().as_str() {
+            "primary" => (&mut r.primary_age, &mut r.primary_temp),
+            "secondary" if r.secondary_age.is_none() => (&mut r.secondary_age, &mut r.secondary_temp),
+            "secondary" => (&mut r.tertiary_age, &mut r.tertiary_temp),
+            "conditioning" => (&mut r.age, &mut r.age_temp),
+            _ => continue,
+        };
+        *age = Some(age.unwrap_or_default() + s.step_time);
+        *temp = Some(Temperature(s.step_temp));
+    }
+    let stages = [r.primary_age, r.secondary_age, r.tertiary_age].iter().filter(|a| a.is_some()).count();
+    if stages > 0 {
+        r.fermentation_stages = Some(stages as i64);
+    }
+}
+
+fn convert<T, U, F>(v: Vec<T>, convert: F) -> Result<Vec<U>>
+    where F: Fn(T) -> Result<U>
+{
+    v.into_iter().map(convert).collect()
+}
+
+fn recipe(r: Recipe) -> Result<data::Recipe> {
+    let type_ = OpenEnum::parse_with(&r.type_, |t| match t {
+        "biab" => Some(data::RecipeType::AllGrain),
+        _ => None,
+    });
+    let mut res = data::Recipe {
+        name: r.name,
+        version: 1,
+        type_,
+        brewer: r.author.unwrap_or_default(),
+        batch_size: Volume(r.batch_size),
+        boil_size: Volume(r.boil_size),
+        boil_time: Time(r.boil_time),
+        efficiency: r.efficiency.map(Percent),
+        notes: r.notes,
+        og: r.og.map(Gravity),
+        fg: r.fg.map(Gravity),
+        carbonation: r.carbonation,
+        ..Default::default()
+    };
+    if let Some(s) = r.style {
+        res.style = style(s)?;
+    }
+    res.equipment = r.equipment.map(equipment);
+    if let Some(m) = r.mash {
+        res.mash = Some(mash(m)?);
+    }
+    if let Some(f) = r.fermentation {
+        fermentation(f, &mut res);
+    }
+    res.fermentables = convert(r.fermentables, fermentable)?;
+    res.hops = convert(r.hops, hop)?;
+    res.miscs = convert(r.miscs, misc)?;
+    res.yeasts = convert(r.yeasts, yeast)?;
+    res.waters = r.water.and_then(|w| w.source).map(water).into_iter().collect();
+    Ok(res)
+}
+
+/// a batch becomes the recipe as brewed, named after the batch number
+fn batch(b: Batch) -> Result<data::Recipe> {
+    let mut r = recipe(b.recipe)?;
+    if let Some(n) = b.batch_no {
+        r.name = format!("{} #{}", r.name, n);
+    }
+    if let Some(brewer) = b.brewer {
+        r.brewer = brewer;
+    }
+    r.date = b.brew_date.map(date);
+    r.og = b.measured_og.map(Gravity).or(r.og);
+    r.fg = b.measured_fg.map(Gravity).or(r.fg);
+    Ok(r)
+}
+
+/// a recipe or batch; batches are recognised by their embedded recipe
+fn entry(v: Value) -> Result<data::Recipe> {
+    let is_batch = match v.get("recipe") {
+        Some(r) => r.is_object(),
+        None => false,
+    };
+    if is_batch {
+        batch(serde_json::from_value(v)?)
+    } else {
+        recipe(serde_json::from_value(v)?)
+    }
+}
+
+fn document(v: Value) -> Result<Document> {
+    let entries = match v {
+        Value::Array(v) => v,
+        v => vec![v],
+    };
+    let mut doc = Document::default();
+    for e in entries {
+        let r = entry(e)?;
+        doc.recipes.insert(r.name().into(), r);
+    }
+    Ok(doc)
+}
+
+/// try to read a `RecordSet` from a reader that contains a Brewfather recipe, batch or a list of them
+pub fn read<B>(reader: B) -> Result<RecordSet>
+    where B: BufRead
+{
+    read_document(reader)?.into_record_set()
+}
+
+/// try to read a `RecordSet` from a Brewfather json file
+pub fn read_file(filename: &Path) -> Result<RecordSet> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read(reader)
+}
+
+/// try to read a `Document` from a reader that contains a Brewfather recipe, batch or a list of them
+pub fn read_document<B>(reader: B) -> Result<Document>
+    where B: BufRead
+{
+    let v: Value = serde_json::from_reader(reader)?;
+    document(v)
+}
+
+/// try to read a `Document` from a Brewfather json file
+pub fn read_document_file(filename: &Path) -> Result<Document> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read_document(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use brewfather;
+    use options::WriteOptions;
+    use xml;
+
+    #[test]
+    fn round_trip_recipe() {
+        let doc = xml::read_document_file(Path::new("data/recipes.xml")).unwrap();
+        let mut buf = vec![];
+        brewfather::write_document(&mut buf, &doc, &WriteOptions::default()).unwrap();
+        let back = read_document(&buf[..]).unwrap();
+        assert_eq!(back.recipes.len(), doc.recipes.len());
+        for (name, r) in doc.recipes.iter() {
+            let b = &back.recipes[name];
+            assert_eq!(b.style.name, r.style.name);
+            assert_eq!(b.batch_size, r.batch_size);
+            assert_eq!((b.primary_age, b.secondary_age), (r.primary_age, r.secondary_age));
+            for (bh, rh) in b.hops.iter().zip(r.hops.iter()) {
+                assert_eq!(bh.name, rh.name);
+                assert!((bh.amount.0 - rh.amount.0).abs() < 1e-9);
+                assert!((bh.time.0 - rh.time.0).abs() < 1e-9);
+            }
+            assert_eq!(b.miscs.len(), r.miscs.len());
+            let steps = |m: &Option<data::Mash>| m.as_ref().map(|m| m.mash_steps.len());
+            assert_eq!(steps(&b.mash), steps(&r.mash));
+        }
+    }
+
+    #[test]
+    fn read_batch() {
+        let json = r#"[{
+            "name": "Batch", "batchNo": 7, "brewer": "Joost", "brewDate": 1500076800000,
+            "measuredOg": 1.052, "notes": [],
+            "recipe": {
+                "name": "Pale Ale", "type": "BIAB", "batchSize": 20, "boilSize": 25, "boilTime": 60,
+                "fermentables": [{"name": "Pale", "type": "Grain", "amount": 4.5, "color": 3,
+                                  "potentialPercentage": 80}],
+                "hops": [{"name": "Cascade", "alpha": 6.5, "amount": 30, "use": "Dry Hop",
+                          "time": 0, "day": 4, "type": "Pellet"}],
+                "miscs": [{"name": "Whirlfloc", "type": "Fining", "use": "Boil", "amount": 1,
+                           "unit": "items", "time": 10}],
+                "yeasts": [{"name": "US-05", "type": "Ale", "form": "Dry", "amount": 11.5,
+                            "unit": "g", "flocculation": "Medium"}],
+                "mash": {"name": "Single", "steps": [{"type": "Temperature", "stepTemp": 66,
+                                                      "stepTime": 60}]},
+                "fermentation": {"name": "Ale", "steps": [
+                    {"type": "Primary", "stepTemp": 18, "stepTime": 10},
+                    {"type": "Cold Crash", "stepTemp": 2, "stepTime": 2}]},
+                "water": {"source": {"name": "Tap", "calcium": 40, "bicarbonate": 120}}
+            }
+        }]"#;
+        let doc = read_document(json.as_bytes()).unwrap();
+        let r = &doc.recipes["Pale Ale #7"];
+        assert_eq!(r.brewer, "Joost");
+        assert_eq!(r.date.as_ref().unwrap(), "2017-07-15");
+        assert_eq!(r.og, Some(Gravity(1.052)));
+        assert_eq!(r.type_.to_string(), "All Grain");
+        assert_eq!(r.hops[0].time.days(), 4.0);
+        assert!((r.hops[0].amount.grams() - 30.0).abs() < 1e-9);
+        assert!((r.yeasts[0].amount - 0.0115).abs() < 1e-9 && r.yeasts[0].amount_is_weight);
+        let whirlfloc = &r.miscs[0];
+        assert_eq!(whirlfloc.amount, 0.0);
+        let raw: Vec<_> = whirlfloc.extensions
+            .iter()
+            .map(|e| (e.tag.as_str(), e.content.as_ref().unwrap().as_str()))
+            .collect();
+        assert_eq!(raw, [("amount", "1"), ("unit", "items")]);
+        let mut buf = vec![];
+        brewfather::write_document(&mut buf, &doc, &WriteOptions::default()).unwrap();
+        let written: Value = serde_json::from_slice(&buf).unwrap();
+        let misc = &written["miscs"][0];
+        assert_eq!((misc["amount"].as_f64(), misc["unit"].as_str()), (Some(1.0), Some("items")));
+        assert_eq!(r.mash.as_ref().unwrap().mash_steps[0].name, "Step 1");
+        assert_eq!((r.primary_age, r.fermentation_stages), (Some(10.0), Some(1)));
+        assert_eq!(r.waters[0].name, "Tap");
+    }
+}
@@ -0,0 +1,285 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! the part of the Brewfather recipe and batch json that maps onto the `data` records
+
+use serde_json::Value;
+
+/// a Brewfather batch; the recipe brewed is embedded in full
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Batch {
+    pub name: Option<String>,
+    pub batch_no: Option<i64>,
+    pub brewer: Option<String>,
+    /// milliseconds since the unix epoch
+    pub brew_date: Option<i64>,
+    pub measured_og: Option<f64>,
+    pub measured_fg: Option<f64>,
+    pub recipe: Recipe,
+}
+
+/// a Brewfather recipe; volumes in liters, temperatures in °C and times in minutes
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Recipe {
+    pub name: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub author: Option<String>,
+    #[serde(rename="type")]
+    pub type_: String,
+    pub batch_size: f64,
+    pub boil_size: f64,
+    pub boil_time: f64,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub efficiency: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub og: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub fg: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub carbonation: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub style: Option<Style>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub equipment: Option<Equipment>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub mash: Option<Mash>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub fermentation: Option<Fermentation>,
+    pub fermentables: Vec<Fermentable>,
+    pub hops: Vec<Hop>,
+    pub miscs: Vec<Misc>,
+    pub yeasts: Vec<Yeast>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub water: Option<Water>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Style {
+    pub name: String,
+    pub category: String,
+    /// a number or a string, depending on the style guide
+    pub category_number: Value,
+    pub style_letter: String,
+    pub style_guide: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    pub og_min: f64,
+    pub og_max: f64,
+    pub fg_min: f64,
+    pub fg_max: f64,
+    pub ibu_min: f64,
+    pub ibu_max: f64,
+    /// SRM
+    pub color_min: f64,
+    pub color_max: f64,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub abv_min: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub abv_max: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub carb_min: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub carb_max: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Equipment {
+    pub name: String,
+    pub batch_size: f64,
+    pub boil_size: f64,
+    pub boil_time: f64,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub efficiency: Option<f64>,
+    /// liters per hour
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub boil_off_per_hr: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub mash_tun_dead_space: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub trub_chiller_loss: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub top_up_water: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub hop_utilization: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Fermentable {
+    pub name: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    /// kg
+    pub amount: f64,
+    /// °L
+    pub color: f64,
+    /// percent
+    pub potential_percentage: f64,
+    #[serde(rename="use", skip_serializing_if="Option::is_none")]
+    pub use_: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub origin: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub supplier: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub moisture: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub protein: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub diastatic_power: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub max_in_batch: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub coarse_fine_diff: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Hop {
+    pub name: String,
+    pub alpha: f64,
+    /// g
+    pub amount: f64,
+    #[serde(rename="use")]
+    pub use_: String,
+    /// minutes
+    pub time: f64,
+    /// days, for dry hops
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub day: Option<f64>,
+    /// the form of the hop, e.g. `Pellet`
+    #[serde(rename="type", skip_serializing_if="Option::is_none")]
+    pub type_: Option<String>,
+    /// `Bittering`, `Aroma` or `Both`
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub usage: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub beta: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub origin: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Misc {
+    pub name: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    #[serde(rename="use")]
+    pub use_: String,
+    pub amount: f64,
+    pub unit: String,
+    pub time: f64,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Yeast {
+    pub name: String,
+    #[serde(rename="type")]
+    pub type_: String,
+    pub form: String,
+    pub amount: f64,
+    /// `pkg`, `g` or `ml`
+    pub unit: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub laboratory: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub product_id: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub min_temp: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub max_temp: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub attenuation: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub flocculation: Option<String>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct MashStep {
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename="type")]
+    pub type_: String,
+    pub step_temp: f64,
+    pub step_time: f64,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub ramp_time: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub infuse_amount: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Mash {
+    pub name: String,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub grain_temp: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub ph: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub notes: Option<String>,
+    pub steps: Vec<MashStep>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct FermentationStep {
+    /// `Primary`, `Secondary`, `Conditioning`, ...
+    #[serde(rename="type")]
+    pub type_: String,
+    pub step_temp: f64,
+    /// days
+    pub step_time: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Fermentation {
+    pub name: String,
+    pub steps: Vec<FermentationStep>,
+}
+
+/// a water profile, ions in ppm
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct WaterProfile {
+    pub name: String,
+    pub calcium: f64,
+    pub magnesium: f64,
+    pub sodium: f64,
+    pub sulfate: f64,
+    pub chloride: f64,
+    pub bicarbonate: f64,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub ph: Option<f64>,
+}
+
+/// the water section of a recipe
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all="camelCase")]
+pub struct Water {
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub source: Option<WaterProfile>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub target: Option<WaterProfile>,
+}
@@ -0,0 +1,290 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use serde_json;
+use serde_json::Value;
+
+use data;
+use data::{Document, RecordSet};
+use error::*;
+use options::WriteOptions;
+use units::Unit;
+use super::schema::*;
+
+/// an amount as grams or milliliters, the units Brewfather uses for small additions, or the
+/// count of items or packages it was read with
+fn amount(amount: f64, is_weight: bool, extensions: &data::Extensions) -> (f64, String) {
+    let raw = |tag| extensions.iter().find(|e| e.tag == tag).and_then(|e| e.content.clone());
+    if let (Some(count), Some(unit)) = (raw("amount").and_then(|a| a.parse().ok()), raw("unit")) {
+        return (count, unit);
+    }
+    let unit = if is_weight { Unit::Gram } else { Unit::Milliliter };
+    (unit.from_base(amount), unit.symbol().to_lowercase())
+}
+
+fn fermentable(f: &data::Fermentable) -> Fermentable {
+    Fermentable {
+        name: f.name.clone(),
+        type_: f.type_.to_string(),
+        amount: f.amount.0,
+        color: f.color.0,
+        potential_percentage: f.yield_.0,
+        use_: if f.add_after_boil {
+            Some("Fermentation".into())
+        } else {
+            None
+        },
+        origin: f.origin.clone(),
+        supplier: f.supplier.clone(),
+        notes: f.notes.clone(),
+        moisture: f.moisture.map(f64::from),
+        protein: f.protein.map(f64::from),
+        diastatic_power: f.diastatic_power,
+        max_in_batch: f.max_in_batch.map(f64::from),
+        coarse_fine_diff: f.coarse_fine_diff.map(f64::from),
+    }
+}
+
+fn hop(h: &data::Hop) -> Hop {
+    let day = match h.use_ {
+        data::HopUse::DryHop => Some(h.time.days()),
+        _ => None,
+    };
+    Hop {
+        name: h.name.clone(),
+        alpha: h.alpha.0,
+        amount: h.amount.grams(),
+        use_: h.use_.to_string(),
+        time: h.time.0,
+        day,
+        type_: h.form.as_ref().map(ToString::to_string),
+        usage: h.type_.as_ref().map(ToString::to_string),
+        beta: h.beta.map(f64::from),
+        origin: h.origin.clone(),
+        notes: h.notes.clone(),
+    }
+}
+
+fn misc(m: &data::Misc) -> Misc {
+    let (amount, unit) = amount(m.amount, m.amount_is_weight, &m.extensions);
+    Misc {
+        name: m.name.clone(),
+        type_: m.type_.to_string(),
+        use_: m.use_.to_string(),
+        amount,
+        unit,
+        time: m.time.0,
+        notes: m.notes.clone(),
+    }
+}
+
+fn yeast(y: &data::Yeast) -> Yeast {
+    let (amount, unit) = amount(y.amount, y.amount_is_weight, &y.extensions);
+    let form = match y.form {
+        data::YeastForm::Slate => "Slant".into(),
+        ref f => f.to_string(),
+    };
+    Yeast {
+        name: y.name.clone(),
+        type_: y.type_.to_string(),
+        form,
+        amount,
+        unit,
+        laboratory: y.laboratory.clone(),
+        product_id: y.product_id.clone(),
+        min_temp: y.min_temperature.map(f64::from),
+        max_temp: y.max_temperature.map(f64::from),
+        attenuation: y.attenuation.map(f64::from),
+        flocculation: y.flocculation.as_ref().map(ToString::to_string),
+        description: y.notes.clone(),
+    }
+}
+
+fn water(w: &data::Water) -> WaterProfile {
+    WaterProfile {
+        name: w.name.clone(),
+        calcium: w.calcium,
+        magnesium: w.magnesium,
+        sodium: w.sodium,
+        sulfate: w.sulfate,
+        chloride: w.chloride,
+        bicarbonate: w.bicarbonate,
+        ph: w.ph,
+    }
+}
+
+fn style(s: &data::Style) -> Style {
+    Style {
+        name: s.name.clone(),
+        category: s.category.clone(),
+        category_number: Value::String(s.category_number.clone()),
+        style_letter: s.style_letter.clone(),
+        style_guide: s.style_guide.clone(),
+        type_: s.type_.to_string(),
+        og_min: s.og_min.0,
+        og_max: s.og_max.0,
+        fg_min: s.fg_min.0,
+        fg_max: s.fg_max.0,
+        ibu_min: s.ibu_min,
+        ibu_max: s.ibu_max,
+        color_min: s.color_min.0,
+        color_max: s.color_max.0,
+        abv_min: s.abv_min.map(f64::from),
+        abv_max: s.abv_max.map(f64::from),
+        carb_min: s.carb_min,
+        carb_max: s.carb_max,
+        notes: s.notes.clone(),
+    }
+}
+
+fn equipment(e: &data::Equipment, efficiency: Option<f64>) -> Equipment {
+    Equipment {
+        name: e.name.clone(),
+        batch_size: e.batch_size.0,
+        boil_size: e.boil_size.0,
+        boil_time: e.boil_time.unwrap_or_default().0,
+        efficiency,
+        boil_off_per_hr: e.evap_rate.map(|r| r.fraction() * e.boil_size.0),
+        mash_tun_dead_space: e.lauter_deadspace.map(f64::from),
+        trub_chiller_loss: e.trub_chiller_loss.map(f64::from),
+        top_up_water: e.top_up_water.map(f64::from),
+        hop_utilization: e.hop_utilization.map(f64::from),
+        notes: e.notes.clone(),
+    }
+}
+
+fn mash(m: &data::Mash) -> Mash {
+    let steps = m.mash_steps
+        .iter()
+        .map(|s| {
+            MashStep {
+                name: Some(s.name.clone()),
+                type_: s.type_.to_string(),
+                step_temp: s.step_temp.0,
+                step_time: s.step_time.0,
+                ramp_time: s.ramp_time.map(f64::from),
+                infuse_amount: s.infuse_amount.map(f64::from),
+            }
+        })
+        .collect();
+    Mash {
+        name: m.name.clone(),
+        grain_temp: Some(m.grain_temp.0),
+        ph: m.ph,
+        notes: m.notes.clone(),
+        steps,
+    }
+}
+
+fn fermentation(r: &data::Recipe) -> Option<Fermentation> {
+    let stages = [("Primary", r.primary_age, r.primary_temp),
+                  ("Secondary", r.secondary_age, r.secondary_temp),
+                  ("Secondary", r.tertiary_age, r.tertiary_temp),
+                  ("Conditioning", r.age, r.age_temp)];
+    let steps: Vec<_> = stages.iter()
+        .filter_map(|&(type_, age, temp)| {
+            age.map(|age| {
+                FermentationStep {
+                    type_: type_.into(),
+                    step_temp: temp.unwrap_or_default().0,
+                    step_time: age,
+                }
+            })
+        })
+        .collect();
+    if steps.is_empty() {
+        return None;
+    }
+    Some(Fermentation {
+        name: r.name.clone(),
+        steps,
+    })
+}
+
+fn recipe(r: &data::Recipe) -> Recipe {
+    Recipe {
+        name: r.name.clone(),
+        author: Some(r.brewer.clone()),
+        type_: r.type_.to_string(),
+        batch_size: r.batch_size.0,
+        boil_size: r.boil_size.0,
+        boil_time: r.boil_time.0,
+        efficiency: r.efficiency.map(f64::from),
+        og: r.og.map(f64::from),
+        fg: r.fg.map(f64::from),
+        carbonation: r.carbonation,
+        notes: r.notes.clone(),
+        style: Some(style(&r.style)),
+        equipment: r.equipment.as_ref().map(|e| equipment(e, r.efficiency.map(f64::from))),
+        mash: r.mash.as_ref().map(mash),
+        fermentation: fermentation(r),
+        fermentables: r.fermentables.iter().map(fermentable).collect(),
+        hops: r.hops.iter().map(hop).collect(),
+        miscs: r.miscs.iter().map(misc).collect(),
+        yeasts: r.yeasts.iter().map(yeast).collect(),
+        water: r.waters.first().map(|w| {
+            Water {
+                source: Some(water(w)),
+                target: None,
+            }
+        }),
+    }
+}
+
+/// a single recipe is written as an object, several as a list
+fn brewfather<T>(writer: &mut T, doc: &Document) -> Result<()>
+    where T: Write
+{
+    if doc.kinds() > 1 || (doc.kinds() == 1 && doc.recipes.is_empty()) {
+        return Err("Brewfather files only hold recipes".into());
+    }
+    let recipes: Vec<_> = doc.recipes.iter().map(|(_, r)| recipe(r)).collect();
+    if recipes.len() == 1 {
+        serde_json::to_writer_pretty(writer, &recipes[0])?;
+    } else {
+        serde_json::to_writer_pretty(writer, &recipes)?;
+    }
+    Ok(())
+}
+
+/// try to write a `RecordSet` of recipes to a writer as Brewfather json
+pub fn write<T>(writer: &mut T, set: &RecordSet) -> Result<()>
+    where T: Write
+{
+    write_with(writer, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` of recipes to a writer as Brewfather json using the given `WriteOptions`
+pub fn write_with<T>(writer: &mut T, set: &RecordSet, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    let doc = Document::from(options.prepare_set(set).into_owned());
+    brewfather(writer, &doc)
+}
+
+/// try to write a `RecordSet` of recipes to a Brewfather json file
+pub fn write_file(filename: &Path, set: &RecordSet) -> Result<()> {
+    write_file_with(filename, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` of recipes to a Brewfather json file using the given `WriteOptions`
+pub fn write_file_with(filename: &Path, set: &RecordSet, options: &WriteOptions) -> Result<()> {
+    let mut f = File::create(filename)?;
+    write_with(&mut f, set, options)
+}
+
+/// try to write the recipes of a `Document` to a writer as Brewfather json
+pub fn write_document<T>(writer: &mut T, doc: &Document, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    brewfather(writer, &options.prepare_document(doc))
+}
+
+/// try to write the recipes of a `Document` to a Brewfather json file
+pub fn write_document_file(filename: &Path, doc: &Document, options: &WriteOptions) -> Result<()> {
+    let mut f = File::create(filename)?;
+    write_document(&mut f, doc, options)
+}
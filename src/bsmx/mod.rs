@@ -0,0 +1,17 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! BeerSmith 2 keeps its recipes and ingredients in `.bsmx` files: XML-like
+//! documents with `F_<kind>_<FIELD>` elements, US units and HTML entities in
+//! the text fields. Only reading is supported:
+//!
+//! * amounts, volumes and temperatures are converted to kilograms, liters and °C
+//! * enumerations are stored as indices and mapped onto the BeerXML names
+//! * yeast and misc amounts are read in the unit picked for them; an amount counted in
+//!   items, which BeerXML has no unit for, is read as `0` and its `F_Y_AMOUNT` and
+//!   `F_Y_UNITS` (or `F_M_AMOUNT` and `F_M_UNITS`) are kept in the record's extensions
+//! * other fields without a BeerXML counterpart are ignored
+
+mod read;
+mod tree;
+
+pub use self::read::*;
@@ -0,0 +1,595 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::io::{BufRead, BufReader};
+use std::fs::File;
+use std::path::Path;
+
+use data::*;
+use error::*;
+use units::{Color, Dimension, Gravity, Mass, Percent, Temperature, Time, Unit, Volume};
+use super::tree::{self, Element};
+
+/// the units BeerSmith numbers amounts in from 0 to 12; 13 is a count of items
+const UNITS: [Unit; 13] = [Unit::Milligram,
+                           Unit::Gram,
+                           Unit::Ounce,
+                           Unit::Pound,
+                           Unit::Kilogram,
+                           Unit::Milliliter,
+                           Unit::Teaspoon,
+                           Unit::Tablespoon,
+                           Unit::Cup,
+                           Unit::Pint,
+                           Unit::Quart,
+                           Unit::Liter,
+                           Unit::Gallon];
+
+/// BeerSmith stores enumerations as indices
+fn index(e: &Element, field: &str) -> Result<i64> {
+    Ok(e.i64(field)?.unwrap_or_default())
+}
+
+fn unknown<T>(what: &str, value: i64) -> Result<T> {
+    Err(ErrorKind::ParseError(format!("BeerSmith {}", what), value.to_string()).into())
+}
+
+/// an amount in the unit the brewer picked, as kilograms or liters and whether it is a
+/// weight; BeerXML has no unit for a number of items or packages, so `None` for a count,
+/// which is kept as it was read in the extensions of the record
+fn amount(e: &Element,
+          amount_field: &str,
+          units_field: &str,
+          extensions: &mut Extensions)
+          -> Result<Option<(f64, bool)>> {
+    let amount = e.f64(amount_field)?.unwrap_or_default();
+    let unit = match e.i64(units_field)? {
+        Some(u) if u >= 0 && (u as usize) < UNITS.len() => UNITS[u as usize],
+        Some(13) | None => {
+            for field in &[amount_field, units_field] {
+                if let Some(raw) = e.text(field) {
+                    extensions.push(Extension {
+                        tag: field.to_string(),
+                        after: "AMOUNT".into(),
+                        content: Some(raw),
+                    });
+                }
+            }
+            return Ok(None);
+        }
+        Some(u) => return unknown("unit", u),
+    };
+    Ok(Some((unit.to_base(amount), unit.dimension() == Dimension::Mass)))
+}
+
+fn fermentable(e: &Element) -> Result<Fermentable> {
+    use data::FermentableType::*;
+    let type_ = match index(e, "F_G_TYPE")? {
+        0 => Grain,
+        1 => Extract,
+        2 => Sugar,
+        3 => Adjunct,
+        4 => DryExtract,
+        t => return unknown("grain type", t),
+    };
+    Ok(Fermentable {
+        name: e.string("F_G_NAME"),
+        version: 1,
+        type_,
+        amount: e.f64("F_G_AMOUNT")?.map(Mass::from_ounces).unwrap_or_default(),
+        yield_: Percent(e.f64("F_G_YIELD")?.unwrap_or_default()),
+        color: Color(e.f64("F_G_COLOR")?.unwrap_or_default()),
+        add_after_boil: e.flag("F_G_ADD_AFTER_BOIL")?,
+        origin: e.text("F_G_ORIGIN"),
+        supplier: e.text("F_G_SUPPLIER"),
+        notes: e.text("F_G_NOTES"),
+        coarse_fine_diff: e.f64("F_G_COARSE_FINE_DIFF")?.map(Percent),
+        moisture: e.f64("F_G_MOISTURE")?.map(Percent),
+        diastatic_power: e.f64("F_G_DIASTATIC_POWER")?,
+        protein: e.f64("F_G_PROTEIN")?.map(Percent),
+        max_in_batch: e.f64("F_G_MAX_IN_BATCH")?.map(Percent),
+        recommend_mash: e.flag("F_G_RECOMMEND_MASH")?,
+        ibu_gal_per_lb: e.f64("F_G_IBU_GAL_PER_LB")?,
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn hop(e: &Element) -> Result<Hop> {
+    let use_ = match index(e, "F_H_USE")? {
+        0 => HopUse::Boil,
+        1 => HopUse::DryHop,
+        2 => HopUse::Mash,
+        3 => HopUse::FirstWort,
+        4 => HopUse::Aroma,
+        u => return unknown("hop use", u),
+    };
+    // dry hop times are kept in days
+    let time = match use_ {
+        HopUse::DryHop => e.f64("F_H_DRY_HOP_TIME")?.map(Time::from_days),
+        _ => e.f64("F_H_BOIL_TIME")?.map(Time),
+    };
+    let type_ = match e.i64("F_H_TYPE")? {
+        Some(0) => Some(HopType::Bittering),
+        Some(1) => Some(HopType::Aroma),
+        Some(2) => Some(HopType::Both),
+        Some(t) => return unknown("hop type", t),
+        None => None,
+    };
+    let form = match e.i64("F_H_FORM")? {
+        Some(0) => Some(HopForm::Pellet),
+        Some(1) => Some(HopForm::Plug),
+        Some(2) => Some(HopForm::Leaf),
+        Some(f) => return unknown("hop form", f),
+        None => None,
+    };
+    Ok(Hop {
+        name: e.string("F_H_NAME"),
+        version: 1,
+        alpha: Percent(e.f64("F_H_ALPHA")?.unwrap_or_default()),
+        amount: e.f64("F_H_AMOUNT")?.map(Mass::from_ounces).unwrap_or_default(),
+        use_,
+        time: time.unwrap_or_default(),
+        notes: e.text("F_H_NOTES"),
+        type_,
+        form,
+        beta: e.f64("F_H_BETA")?.map(Percent),
+        hsi: e.f64("F_H_HSI")?.map(Percent),
+        origin: e.text("F_H_ORIGIN"),
+        substitutes: e.text("F_H_SUBSTITUTES"),
+        humulene: e.f64("F_H_HUMULENE")?.map(Percent),
+        caryophyllene: e.f64("F_H_CARYOPHYLLENE")?.map(Percent),
+        cohumulone: e.f64("F_H_COHUMULONE")?.map(Percent),
+        myrcene: e.f64("F_H_MYRCENE")?.map(Percent),
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn yeast(e: &Element) -> Result<Yeast> {
+    let type_ = match index(e, "F_Y_TYPE")? {
+        0 => YeastType::Ale,
+        1 => YeastType::Lager,
+        2 => YeastType::Wine,
+        3 => YeastType::Champagne,
+        4 => YeastType::Wheat,
+        t => return unknown("yeast type", t),
+    };
+    let (form, is_dry) = match index(e, "F_Y_FORM")? {
+        0 => (YeastForm::Liquid, false),
+        1 => (YeastForm::Dry, true),
+        2 => (YeastForm::Slate, false),
+        3 => (YeastForm::Culture, false),
+        f => return unknown("yeast form", f),
+    };
+    let flocculation = match e.i64("F_Y_FLOCCULATION")? {
+        Some(0) => Some(YeastFlocculation::Low),
+        Some(1) => Some(YeastFlocculation::Medium),
+        Some(2) => Some(YeastFlocculation::High),
+        Some(3) => Some(YeastFlocculation::VeryHigh),
+        Some(f) => return unknown("yeast flocculation", f),
+        None => None,
+    };
+    let attenuation = match (e.f64("F_Y_MIN_ATTENUATION")?, e.f64("F_Y_MAX_ATTENUATION")?) {
+        (Some(min), Some(max)) => Some((min + max) / 2.0),
+        (min, max) => min.or(max),
+    };
+    let mut extensions = Extensions::default();
+    // BeerSmith usually counts packages, a BeerXML amount is a volume or weight
+    let (amount, amount_is_weight) = amount(e, "F_Y_AMOUNT", "F_Y_UNITS", &mut extensions)?
+        .unwrap_or((0.0, is_dry));
+    Ok(Yeast {
+        name: e.string("F_Y_NAME"),
+        version: 1,
+        type_,
+        form,
+        amount,
+        amount_is_weight,
+        laboratory: e.text("F_Y_LAB"),
+        product_id: e.text("F_Y_PRODUCT_ID"),
+        min_temperature: e.f64("F_Y_MIN_TEMP")?.map(Temperature::from_fahrenheit),
+        max_temperature: e.f64("F_Y_MAX_TEMP")?.map(Temperature::from_fahrenheit),
+        flocculation,
+        attenuation: attenuation.map(Percent),
+        notes: e.text("F_Y_NOTES"),
+        best_for: e.text("F_Y_BEST_FOR"),
+        times_cultured: e.i64("F_Y_TIMES_CULTURED")?,
+        max_reuse: e.i64("F_Y_MAX_REUSE")?,
+        add_to_secondary: e.flag("F_Y_ADD_TO_SECONDARY")?,
+        display: None,
+        extensions,
+    })
+}
+
+fn misc(e: &Element) -> Result<Misc> {
+    let type_ = match index(e, "F_M_TYPE")? {
+        0 => MiscType::Spice,
+        1 => MiscType::Fining,
+        2 => MiscType::Herb,
+        3 => MiscType::Flavor,
+        4 => MiscType::Other,
+        5 => MiscType::WaterAgent,
+        t => return unknown("misc type", t),
+    };
+    let use_ = match index(e, "F_M_USE")? {
+        0 => MiscUse::Boil,
+        1 => MiscUse::Mash,
+        2 => MiscUse::Primary,
+        3 => MiscUse::Secondary,
+        4 => MiscUse::Bottling,
+        u => return unknown("misc use", u),
+    };
+    let mut extensions = Extensions::default();
+    let (amount, amount_is_weight) = amount(e, "F_M_AMOUNT", "F_M_UNITS", &mut extensions)?
+        .unwrap_or((0.0, false));
+    Ok(Misc {
+        name: e.string("F_M_NAME"),
+        version: 1,
+        type_,
+        use_,
+        time: Time(e.f64("F_M_TIME")?.unwrap_or_default()),
+        amount,
+        amount_is_weight,
+        use_for: e.text("F_M_USE_FOR"),
+        notes: e.text("F_M_NOTES"),
+        display: None,
+        extensions,
+    })
+}
+
+fn water(e: &Element) -> Result<Water> {
+    Ok(Water {
+        name: e.string("F_W_NAME"),
+        version: 1,
+        amount: e.f64("F_W_AMOUNT")?.map(Volume::from_fluid_ounces).unwrap_or_default(),
+        calcium: e.f64("F_W_CALCIUM")?.unwrap_or_default(),
+        bicarbonate: e.f64("F_W_BICARB")?.unwrap_or_default(),
+        sulfate: e.f64("F_W_SULFATE")?.unwrap_or_default(),
+        chloride: e.f64("F_W_CHLORIDE")?.unwrap_or_default(),
+        sodium: e.f64("F_W_SODIUM")?.unwrap_or_default(),
+        magnesium: e.f64("F_W_MAGNESIUM")?.unwrap_or_default(),
+        ph: e.f64("F_W_PH")?,
+        notes: e.text("F_W_NOTES"),
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn style(e: &Element) -> Result<Style> {
+    let type_ = match index(e, "F_S_TYPE")? {
+        0 => StyleType::Ale,
+        1 => StyleType::Lager,
+        2 => StyleType::Mead,
+        3 => StyleType::Wheat,
+        4 => StyleType::Mixed,
+        5 => StyleType::Cider,
+        t => return unknown("style type", t),
+    };
+    Ok(Style {
+        name: e.string("F_S_NAME"),
+        version: 1,
+        category: e.string("F_S_CATEGORY"),
+        category_number: e.string("F_S_NUMBER"),
+        style_letter: e.string("F_S_LETTER"),
+        style_guide: e.string("F_S_GUIDE"),
+        type_,
+        og_min: Gravity(e.f64("F_S_MIN_OG")?.unwrap_or_default()),
+        og_max: Gravity(e.f64("F_S_MAX_OG")?.unwrap_or_default()),
+        fg_min: Gravity(e.f64("F_S_MIN_FG")?.unwrap_or_default()),
+        fg_max: Gravity(e.f64("F_S_MAX_FG")?.unwrap_or_default()),
+        ibu_min: e.f64("F_S_MIN_IBU")?.unwrap_or_default(),
+        ibu_max: e.f64("F_S_MAX_IBU")?.unwrap_or_default(),
+        color_min: Color(e.f64("F_S_MIN_COLOR")?.unwrap_or_default()),
+        color_max: Color(e.f64("F_S_MAX_COLOR")?.unwrap_or_default()),
+        carb_min: e.f64("F_S_MIN_CARB")?,
+        carb_max: e.f64("F_S_MAX_CARB")?,
+        abv_min: e.f64("F_S_MIN_ABV")?.map(Percent),
+        abv_max: e.f64("F_S_MAX_ABV")?.map(Percent),
+        notes: e.text("F_S_DESCRIPTION"),
+        profile: e.text("F_S_PROFILE"),
+        ingredients: e.text("F_S_INGREDIENTS"),
+        examples: e.text("F_S_EXAMPLES"),
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn equipment(e: &Element) -> Result<Equipment> {
+    let boil_size = e.f64("F_E_BOIL_VOL")?.map(Volume::from_fluid_ounces).unwrap_or_default();
+    // BeerSmith stores the boil off as a volume per hour
+    let evap_rate = match e.f64("F_E_BOIL_OFF")?.map(Volume::from_fluid_ounces) {
+        Some(off) if boil_size.0 > 0.0 => Some(off.0 * 100.0 / boil_size.0),
+        _ => None,
+    };
+    Ok(Equipment {
+        name: e.string("F_E_NAME"),
+        version: 1,
+        boil_size,
+        batch_size: e.f64("F_E_BATCH_VOL")?.map(Volume::from_fluid_ounces).unwrap_or_default(),
+        tun_volume: e.f64("F_E_TUN_VOL")?.map(Volume::from_fluid_ounces),
+        tun_weight: e.f64("F_E_TUN_MASS")?.map(Mass::from_ounces),
+        tun_specific_heat: e.f64("F_E_TUN_SPECIFIC_HEAT")?,
+        top_up_water: e.f64("F_E_TOP_UP")?.map(Volume::from_fluid_ounces),
+        trub_chiller_loss: e.f64("F_E_TRUB_LOSS")?.map(Volume::from_fluid_ounces),
+        evap_rate: evap_rate.map(Percent),
+        boil_time: e.f64("F_E_BOIL_TIME")?.map(Time),
+        calc_boil_volume: Some(e.flag("F_E_CALC_BOIL")?),
+        lauter_deadspace: e.f64("F_E_TUN_DEADSPACE")?.map(Volume::from_fluid_ounces),
+        top_up_kettle: e.f64("F_E_TOP_UP_KETTLE")?.map(Volume::from_fluid_ounces),
+        hop_utilization: e.f64("F_E_HOP_UTIL")?.map(Percent),
+        notes: e.text("F_E_NOTES"),
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn mash_step(e: &Element) -> Result<MashStep> {
+    let type_ = match index(e, "F_MS_TYPE")? {
+        0 => "Infusion",
+        1 => "Decoction",
+        2 => "Temperature",
+        t => return unknown("mash step type", t),
+    };
+    Ok(MashStep {
+        name: e.string("F_MS_NAME"),
+        version: 1,
+        type_: type_.parse()?,
+        infuse_amount: e.f64("F_MS_INFUSION")?.map(Volume::from_fluid_ounces),
+        step_temp: e.f64("F_MS_STEP_TEMP")?.map(Temperature::from_fahrenheit).unwrap_or_default(),
+        step_time: Time(e.f64("F_MS_STEP_TIME")?.unwrap_or_default()),
+        ramp_time: e.f64("F_MS_RISE_TIME")?.map(Time),
+        end_temp: e.f64("F_MS_END_TEMP")?.map(Temperature::from_fahrenheit),
+        display: None,
+        extensions: Default::default(),
+    })
+}
+
+fn mash(e: &Element) -> Result<Mash> {
+    let mut steps = vec![];
+    e.records("F_MS_", &mut steps);
+    Ok(Mash {
+        name: e.string("F_MH_NAME"),
+        version: 1,
+        grain_temp: e.f64("F_MH_GRAIN_TEMP")?.map(Temperature::from_fahrenheit).unwrap_or_default(),
+        notes: e.text("F_MH_NOTES"),
+        tun_temp: e.f64("F_MH_TUN_TEMP")?.map(Temperature::from_fahrenheit),
+        sparge_temp: e.f64("F_MH_SPARGE_TEMP")?.map(Temperature::from_fahrenheit),
+        ph: e.f64("F_MH_PH")?,
+        tun_weight: e.f64("F_MH_TUN_WEIGHT")?.map(Mass::from_ounces),
+        tun_specific_heat: e.f64("F_MH_TUN_SPECIFIC_HEAT")?,
+        equip_adjust: Some(e.flag("F_MH_EQUIP_ADJUST")?),
+        display: None,
+        mash_steps: steps.into_iter().map(mash_step).collect::<Result<_>>()?,
+        extensions: Default::default(),
+    })
+}
+
+fn list<T, F>(e: &Element, prefix: &str, read_element: F) -> Result<Vec<T>>
+    where F: Fn(&Element) -> Result<T>
+{
+    let mut records = vec![];
+    e.records(prefix, &mut records);
+    records.into_iter().map(read_element).collect()
+}
+
+fn recipe(e: &Element) -> Result<Recipe> {
+    let type_ = match index(e, "F_R_TYPE")? {
+        0 => RecipeType::Extract,
+        1 => RecipeType::PartialMash,
+        2 => RecipeType::AllGrain,
+        t => return unknown("recipe type", t),
+    };
+    let mut r = Recipe {
+        name: e.string("F_R_NAME"),
+        version: 1,
+        type_,
+        brewer: e.string("F_R_BREWER"),
+        asst_brewer: e.text("F_R_ASST_BREWER"),
+        date: e.text("F_R_DATE"),
+        notes: e.text("F_R_NOTES"),
+        og: e.f64("F_R_OG_MEASURED")?.map(Gravity),
+        fg: e.f64("F_R_FG_MEASURED")?.map(Gravity),
+        carbonation: e.f64("F_R_CARB_VOLS")?,
+        taste_notes: e.text("F_R_TASTE_NOTES"),
+        taste_rating: e.f64("F_R_TASTE_RATING")?,
+        ..Recipe::default()
+    };
+    if let Some(s) = e.child("F_R_STYLE") {
+        r.style = style(s)?;
+    }
+    if let Some(el) = e.child("F_R_EQUIPMENT") {
+        let eq = equipment(el)?;
+        r.batch_size = eq.batch_size;
+        r.boil_size = eq.boil_size;
+        r.boil_time = eq.boil_time.unwrap_or_default();
+        r.efficiency = el.f64("F_E_EFFICIENCY")?.map(Percent);
+        r.equipment = Some(eq);
+    }
+    if let Some(m) = e.child("F_R_MASH") {
+        r.mash = Some(mash(m)?);
+    }
+    if let Some(a) = e.child("F_R_AGE") {
+        r.fermentation_stages = a.i64("F_A_TYPE")?.map(|t| t + 1);
+        r.primary_age = a.f64("F_A_PRIM_DAYS")?;
+        r.primary_temp = a.f64("F_A_PRIM_TEMP")?.map(Temperature::from_fahrenheit);
+        r.secondary_age = a.f64("F_A_SEC_DAYS")?;
+        r.secondary_temp = a.f64("F_A_SEC_TEMP")?.map(Temperature::from_fahrenheit);
+        r.tertiary_age = a.f64("F_A_TERT_DAYS")?;
+        r.tertiary_temp = a.f64("F_A_TERT_TEMP")?.map(Temperature::from_fahrenheit);
+        r.age = a.f64("F_A_AGE")?;
+        r.age_temp = a.f64("F_A_AGE_TEMP")?.map(Temperature::from_fahrenheit);
+    }
+    if let Some(i) = e.child("Ingredients") {
+        r.fermentables = list(i, "F_G_", fermentable)?;
+        r.hops = list(i, "F_H_", hop)?;
+        r.miscs = list(i, "F_M_", misc)?;
+        r.yeasts = list(i, "F_Y_", yeast)?;
+        r.waters = list(i, "F_W_", water)?;
+    }
+    Ok(r)
+}
+
+fn insert<T, F>(map: &mut RecordMap<T>, e: &Element, read_element: F) -> Result<()>
+    where F: Fn(&Element) -> Result<T>,
+          T: Named
+{
+    let x = read_element(e)?;
+    map.insert(x.name().into(), x);
+    Ok(())
+}
+
+/// collect the records below `e`; the ingredients inside a recipe belong to that recipe
+fn collect(e: &Element, doc: &mut Document) -> Result<()> {
+    for c in &e.children {
+        if c.is_record("F_R_") {
+            insert(&mut doc.recipes, c, recipe)?;
+        } else if c.is_record("F_G_") {
+            insert(&mut doc.fermentables, c, fermentable)?;
+        } else if c.is_record("F_H_") {
+            insert(&mut doc.hops, c, hop)?;
+        } else if c.is_record("F_M_") {
+            insert(&mut doc.miscs, c, misc)?;
+        } else if c.is_record("F_Y_") {
+            insert(&mut doc.yeasts, c, yeast)?;
+        } else if c.is_record("F_W_") {
+            insert(&mut doc.waters, c, water)?;
+        } else if c.is_record("F_S_") {
+            insert(&mut doc.styles, c, style)?;
+        } else if c.is_record("F_E_") {
+            insert(&mut doc.equipments, c, equipment)?;
+        } else if c.is_record("F_MH_") {
+            insert(&mut doc.mashs, c, mash)?;
+        } else {
+            collect(c, doc)?;
+        }
+    }
+    Ok(())
+}
+
+/// try to read a `RecordSet` from a reader that contains a BeerSmith file
+pub fn read<B>(reader: B) -> Result<RecordSet>
+    where B: BufRead
+{
+    read_document(reader)?.into_record_set()
+}
+
+/// try to read a `RecordSet` from a BeerSmith `.bsmx` file
+pub fn read_file(filename: &Path) -> Result<RecordSet> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read(reader)
+}
+
+/// try to read a `Document` from a reader that contains a BeerSmith file
+pub fn read_document<B>(reader: B) -> Result<Document>
+    where B: BufRead
+{
+    let root = tree::parse(reader)?;
+    let mut doc = Document::default();
+    collect(&root, &mut doc)?;
+    Ok(doc)
+}
+
+/// try to read a `Document` from a BeerSmith `.bsmx` file
+pub fn read_document_file(filename: &Path) -> Result<Document> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read_document(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BSMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Recipes><Data>
+<Recipe><F_R_NAME>Brewer&rsquo;s Best&nbsp;Bitter</F_R_NAME>
+<F_R_BREWER>Joost</F_R_BREWER>
+<F_R_TYPE>2</F_R_TYPE>
+<F_R_STYLE><F_S_NAME>Best Bitter</F_S_NAME><F_S_TYPE>0</F_S_TYPE>
+<F_S_MIN_OG>1.0400000</F_S_MIN_OG><F_S_MAX_OG>1.0480000</F_S_MAX_OG></F_R_STYLE>
+<F_R_EQUIPMENT><F_E_NAME>Pot</F_E_NAME><F_E_BATCH_VOL>640.0000000</F_E_BATCH_VOL>
+<F_E_BOIL_VOL>768.0000000</F_E_BOIL_VOL><F_E_BOIL_TIME>60.0000000</F_E_BOIL_TIME>
+<F_E_EFFICIENCY>72.0000000</F_E_EFFICIENCY></F_R_EQUIPMENT>
+<F_R_MASH><F_MH_NAME>Single Infusion</F_MH_NAME><F_MH_GRAIN_TEMP>68.0000000</F_MH_GRAIN_TEMP>
+<steps><Data><MashStep><F_MS_NAME>Saccharification</F_MS_NAME><F_MS_TYPE>0</F_MS_TYPE>
+<F_MS_STEP_TEMP>152.6000000</F_MS_STEP_TEMP><F_MS_STEP_TIME>60.0000000</F_MS_STEP_TIME>
+</MashStep></Data></steps></F_R_MASH>
+<Ingredients><Data>
+<Grain><F_G_NAME>Pale Malt (2 Row) UK</F_G_NAME><F_G_TYPE>0</F_G_TYPE>
+<F_G_AMOUNT>128.0000000</F_G_AMOUNT><F_G_YIELD>78.0000000</F_G_YIELD><F_G_COLOR>3.0000000</F_G_COLOR></Grain>
+<Hops><F_H_NAME>Fuggles</F_H_NAME><F_H_USE>0</F_H_USE><F_H_FORM>0</F_H_FORM>
+<F_H_ALPHA>4.5000000</F_H_ALPHA><F_H_AMOUNT>1.0000000</F_H_AMOUNT><F_H_BOIL_TIME>60.0000000</F_H_BOIL_TIME></Hops>
+<Hops><F_H_NAME>Goldings</F_H_NAME><F_H_USE>1</F_H_USE>
+<F_H_ALPHA>5.0000000</F_H_ALPHA><F_H_AMOUNT>0.5000000</F_H_AMOUNT><F_H_DRY_HOP_TIME>3.0000000</F_H_DRY_HOP_TIME></Hops>
+<Misc><F_M_NAME>Irish Moss</F_M_NAME><F_M_TYPE>1</F_M_TYPE><F_M_USE>0</F_M_USE>
+<F_M_UNITS>6</F_M_UNITS><F_M_AMOUNT>1.0000000</F_M_AMOUNT><F_M_TIME>10.0000000</F_M_TIME></Misc>
+<Misc><F_M_NAME>Whirlfloc</F_M_NAME><F_M_TYPE>1</F_M_TYPE><F_M_USE>0</F_M_USE>
+<F_M_UNITS>13</F_M_UNITS><F_M_AMOUNT>1.0000000</F_M_AMOUNT><F_M_TIME>15.0000000</F_M_TIME></Misc>
+<Yeast><F_Y_NAME>London Ale</F_Y_NAME><F_Y_TYPE>0</F_Y_TYPE><F_Y_FORM>0</F_Y_FORM>
+<F_Y_LAB>Wyeast Labs</F_Y_LAB><F_Y_MIN_TEMP>64.0000000</F_Y_MIN_TEMP>
+<F_Y_AMOUNT>2.0000000</F_Y_AMOUNT></Yeast>
+<Yeast><F_Y_NAME>Nottingham</F_Y_NAME><F_Y_TYPE>0</F_Y_TYPE><F_Y_FORM>1</F_Y_FORM>
+<F_Y_UNITS>1</F_Y_UNITS><F_Y_AMOUNT>11.0000000</F_Y_AMOUNT></Yeast>
+</Data></Ingredients>
+</Recipe>
+</Data></Recipes>"#;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    #[test]
+    fn read_recipe() {
+        let doc = read_document(BSMX.as_bytes()).unwrap();
+        assert!(doc.fermentables.is_empty() && doc.hops.is_empty());
+        let r = &doc.recipes["Brewer’s Best\u{a0}Bitter"];
+        assert_eq!(r.brewer, "Joost");
+        assert_eq!(r.style.name, "Best Bitter");
+        assert!(close(r.batch_size.0, 18.927));
+        assert_eq!(r.efficiency, Some(Percent(72.0)));
+        let mash = r.mash.as_ref().unwrap();
+        assert_eq!(mash.mash_steps.len(), 1);
+        assert!(close(mash.mash_steps[0].step_temp.0, 67.0));
+        assert!(close(r.fermentables[0].amount.0, 3.629));
+        let names: Vec<&str> = r.hops.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, ["Fuggles", "Goldings"]);
+        assert!(close(r.hops[1].time.0, 4320.0));
+        assert!(close(r.miscs[0].amount, 0.004929));
+        assert!(!r.miscs[0].amount_is_weight);
+        assert!(close(r.yeasts[0].min_temperature.unwrap().0, 17.778));
+
+        // counted items and packages are kept as read instead of passing for liters
+        let whirlfloc = &r.miscs[1];
+        assert_eq!(whirlfloc.amount, 0.0);
+        let raw = |e: &Extension| (e.tag.clone(), e.content.clone().unwrap());
+        assert_eq!(whirlfloc.extensions.iter().map(raw).collect::<Vec<_>>(),
+                   [("F_M_AMOUNT".to_string(), "1.0000000".to_string()),
+                    ("F_M_UNITS".to_string(), "13".to_string())]);
+        assert_eq!(r.yeasts[0].amount, 0.0);
+        assert_eq!(r.yeasts[0].extensions.iter().map(raw).collect::<Vec<_>>(),
+                   [("F_Y_AMOUNT".to_string(), "2.0000000".to_string())]);
+        assert!(close(r.yeasts[1].amount, 0.011));
+        assert!(r.yeasts[1].amount_is_weight && r.yeasts[1].extensions.is_empty());
+    }
+
+    #[test]
+    fn read_ingredients() {
+        let set = read(r#"<Hops><Data>
+<Hops><F_H_NAME>Saaz</F_H_NAME><F_H_USE>4</F_H_USE><F_H_ORIGIN>Czech&#x20;Republic</F_H_ORIGIN></Hops>
+<Hops><F_H_NAME>Tettnang</F_H_NAME><F_H_USE>7</F_H_USE></Hops>
+</Data></Hops>"#
+            .as_bytes());
+        assert!(set.is_err());
+        let set = read(r#"<Hops><Data>
+<Hops><F_H_NAME>Saaz</F_H_NAME><F_H_USE>4</F_H_USE><F_H_ORIGIN>Czech&#x20;Republic</F_H_ORIGIN></Hops>
+</Data></Hops>"#
+            .as_bytes())
+            .unwrap();
+        match set {
+            RecordSet::Hops(hops) => {
+                assert_eq!(hops["Saaz"].origin.as_ref().unwrap(), "Czech Republic")
+            }
+            _ => panic!("expected hops"),
+        }
+    }
+}
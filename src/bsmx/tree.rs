@@ -0,0 +1,159 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::io::BufRead;
+
+use quick_xml::reader::Reader;
+use quick_xml::events::Event;
+
+use error::*;
+
+/// an element of a BeerSmith file with its text and child elements
+#[derive(Debug, Default)]
+pub struct Element {
+    pub name: String,
+    pub text: String,
+    pub children: Vec<Element>,
+}
+
+impl Element {
+    fn new(name: &str) -> Element {
+        Element { name: name.into(), ..Element::default() }
+    }
+
+    /// the first child element with the given name
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// the text of a child element, if present and not empty
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.child(name).map(|c| c.text.trim()).filter(|t| !t.is_empty()).map(Into::into)
+    }
+
+    /// the text of a child element, or an empty string
+    pub fn string(&self, name: &str) -> String {
+        self.text(name).unwrap_or_default()
+    }
+
+    /// the numeric value of a child element, if present
+    pub fn f64(&self, name: &str) -> Result<Option<f64>> {
+        match self.text(name) {
+            Some(t) => Ok(Some(t.parse::<f64>()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// the integer value of a child element, if present; BeerSmith writes these as `3.0000000`
+    pub fn i64(&self, name: &str) -> Result<Option<i64>> {
+        self.f64(name).map(|v| v.map(|v| v as i64))
+    }
+
+    /// the value of a `0`/`1` flag, `false` if absent
+    pub fn flag(&self, name: &str) -> Result<bool> {
+        Ok(self.f64(name)?.unwrap_or(0.0) != 0.0)
+    }
+
+    /// `true` if this element is a record with the given field prefix, e.g. `F_H_` for a hop
+    pub fn is_record(&self, prefix: &str) -> bool {
+        let name = format!("{}NAME", prefix);
+        self.children.iter().any(|c| c.name == name)
+    }
+
+    /// all descendant records with the given field prefix, in document order
+    pub fn records<'a>(&'a self, prefix: &str, res: &mut Vec<&'a Element>) {
+        for c in &self.children {
+            if c.is_record(prefix) {
+                res.push(c);
+            } else {
+                c.records(prefix, res);
+            }
+        }
+    }
+}
+
+/// HTML entities BeerSmith uses in its text fields that are not part of XML
+const ENTITIES: &[(&str, &str)] = &[("amp", "&"), ("lt", "<"), ("gt", ">"), ("quot", "\""),
+                                    ("apos", "'"), ("nbsp", "\u{a0}"), ("lsquo", "‘"),
+                                    ("rsquo", "’"), ("ldquo", "“"), ("rdquo", "”"),
+                                    ("ndash", "–"), ("mdash", "—"), ("hellip", "…"),
+                                    ("deg", "°"), ("frac12", "½"), ("frac14", "¼"),
+                                    ("frac34", "¾"), ("copy", "©"), ("reg", "®"),
+                                    ("trade", "™"), ("auml", "ä"), ("ouml", "ö"),
+                                    ("uuml", "ü"), ("Auml", "Ä"), ("Ouml", "Ö"),
+                                    ("Uuml", "Ü"), ("szlig", "ß"), ("eacute", "é"),
+                                    ("egrave", "è"), ("aacute", "á"), ("oacute", "ó"),
+                                    ("ccedil", "ç"), ("ntilde", "ñ")];
+
+/// replace XML, HTML and numeric character references; unknown ones are kept as is
+pub fn unescape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) if end < 10 => end,
+            _ => {
+                res.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let entity = &rest[1..end];
+        let c = if let Some(hex) = entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok().and_then(::std::char::from_u32)
+        } else if let Some(dec) = entity.strip_prefix('#') {
+            dec.parse::<u32>().ok().and_then(::std::char::from_u32)
+        } else {
+            None
+        };
+        let named = ENTITIES.iter().find(|e| e.0 == entity).map(|e| e.1);
+        match (c, named) {
+            (Some(c), _) => res.push(c),
+            (None, Some(s)) => res.push_str(s),
+            (None, None) => res.push_str(&rest[..end + 1]),
+        }
+        rest = &rest[end + 1..];
+    }
+    res.push_str(rest);
+    res
+}
+
+/// read a BeerSmith file into a tree of elements below a nameless root
+pub fn parse<B>(reader: B) -> Result<Element>
+    where B: BufRead
+{
+    let mut reader = Reader::from_reader(reader);
+    reader.trim_text(true);
+    let mut buf = vec![];
+    let mut stack = vec![Element::default()];
+    loop {
+        match reader.read_event(&mut buf)? {
+            Event::Start(ref e) => stack.push(Element::new(&reader.decode(e.name()))),
+            Event::Empty(ref e) => {
+                let el = Element::new(&reader.decode(e.name()));
+                stack.last_mut().unwrap().children.push(el);
+            }
+            Event::End(_) if stack.len() > 1 => {
+                let el = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(el);
+            }
+            Event::Text(ref e) => {
+                let text = unescape(&reader.decode(e));
+                stack.last_mut().unwrap().text.push_str(&text);
+            }
+            Event::CData(ref e) => {
+                let text = reader.decode(e).into_owned();
+                stack.last_mut().unwrap().text.push_str(&text);
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    while stack.len() > 1 {
+        let el = stack.pop().unwrap();
+        stack.last_mut().unwrap().children.push(el);
+    }
+    Ok(stack.pop().unwrap())
+}
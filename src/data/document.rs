@@ -0,0 +1,170 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use error::*;
+use super::*;
+use super::sort_records;
+
+/// a document holding several kinds of records at once
+///
+/// The serialized field names match the `RecordSet` variants, so a
+/// serialized single-kind `RecordSet` is also a valid `Document`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Document {
+    /// named equipments
+    #[serde(rename="Equipments", skip_serializing_if="RecordMap::is_empty",
+            deserialize_with="named_map::deserialize")]
+    pub equipments: RecordMap<Equipment>,
+    /// named fermentables
+    #[serde(rename="Fermentables", skip_serializing_if="RecordMap::is_empty",
+            deserialize_with="named_map::deserialize")]
+    pub fermentables: RecordMap<Fermentable>,
+    /// named hops
+    #[serde(rename="Hops", skip_serializing_if="RecordMap::is_empty",
+            deserialize_with="named_map::deserialize")]
+    pub hops: RecordMap<Hop>,
+    /// named miscelaneous items
+    #[serde(rename="Miscs", skip_serializing_if="RecordMap::is_empty",
+            deserialize_with="named_map::deserialize")]
+    pub miscs: RecordMap<Misc>,
+    /// named recipes
+    #[serde(rename="Recipes", skip_serializing_if="RecordMap::is_empty",
+            deserialize_with="named_map::deserialize")]
+    pub recipes: RecordMap<Recipe>,
+    /// named water profiles
+    #[serde(rename="Waters", skip_serializing_if="RecordMap::is_empty",
+            deserialize_with="named_map::deserialize")]
+    pub waters: RecordMap<Water>,
+    /// named yeasts
+    #[serde(rename="Yeasts", skip_serializing_if="RecordMap::is_empty",
+            deserialize_with="named_map::deserialize")]
+    pub yeasts: RecordMap<Yeast>,
+    /// named styles
+    #[serde(rename="Styles", skip_serializing_if="RecordMap::is_empty",
+            deserialize_with="named_map::deserialize")]
+    pub styles: RecordMap<Style>,
+    /// named mash profiles
+    #[serde(rename="Mashs", skip_serializing_if="RecordMap::is_empty",
+            deserialize_with="named_map::deserialize")]
+    pub mashs: RecordMap<Mash>,
+}
+
+impl Document {
+    /// number of record kinds that have at least one record
+    pub fn kinds(&self) -> usize {
+        [self.equipments.is_empty(),
+         self.fermentables.is_empty(),
+         self.hops.is_empty(),
+         self.miscs.is_empty(),
+         self.recipes.is_empty(),
+         self.waters.is_empty(),
+         self.yeasts.is_empty(),
+         self.styles.is_empty(),
+         self.mashs.is_empty()]
+            .iter()
+            .filter(|empty| !**empty)
+            .count()
+    }
+
+    /// `true` if the document contains no records at all
+    pub fn is_empty(&self) -> bool {
+        self.kinds() == 0
+    }
+
+    /// add all records of a `RecordSet`, replacing records with the same name
+    pub fn add(&mut self, set: RecordSet) {
+        match set {
+            RecordSet::Empty => (),
+            RecordSet::Equipments(v) => self.equipments.extend(v),
+            RecordSet::Fermentables(v) => self.fermentables.extend(v),
+            RecordSet::Hops(v) => self.hops.extend(v),
+            RecordSet::Miscs(v) => self.miscs.extend(v),
+            RecordSet::Recipes(v) => self.recipes.extend(v),
+            RecordSet::Waters(v) => self.waters.extend(v),
+            RecordSet::Yeasts(v) => self.yeasts.extend(v),
+            RecordSet::Styles(v) => self.styles.extend(v),
+            RecordSet::Mashs(v) => self.mashs.extend(v),
+        }
+    }
+
+    /// convert into a single-kind `RecordSet`; fails if the document holds several record kinds
+    pub fn into_record_set(self) -> Result<RecordSet> {
+        if self.kinds() > 1 {
+            return Err("document contains several record kinds, read it as a `Document`".into());
+        }
+        let set = if !self.equipments.is_empty() {
+            RecordSet::Equipments(self.equipments)
+        } else if !self.fermentables.is_empty() {
+            RecordSet::Fermentables(self.fermentables)
+        } else if !self.hops.is_empty() {
+            RecordSet::Hops(self.hops)
+        } else if !self.miscs.is_empty() {
+            RecordSet::Miscs(self.miscs)
+        } else if !self.recipes.is_empty() {
+            RecordSet::Recipes(self.recipes)
+        } else if !self.waters.is_empty() {
+            RecordSet::Waters(self.waters)
+        } else if !self.yeasts.is_empty() {
+            RecordSet::Yeasts(self.yeasts)
+        } else if !self.styles.is_empty() {
+            RecordSet::Styles(self.styles)
+        } else if !self.mashs.is_empty() {
+            RecordSet::Mashs(self.mashs)
+        } else {
+            RecordSet::Empty
+        };
+        Ok(set)
+    }
+
+    /// sort the records of every kind by name
+    pub fn sort_by_name(&mut self) {
+        sort_records(&mut self.equipments);
+        sort_records(&mut self.fermentables);
+        sort_records(&mut self.hops);
+        sort_records(&mut self.miscs);
+        sort_records(&mut self.recipes);
+        sort_records(&mut self.waters);
+        sort_records(&mut self.yeasts);
+        sort_records(&mut self.styles);
+        sort_records(&mut self.mashs);
+    }
+
+    /// remove all optional display and estimated fields (BeerXML Appendix A)
+    pub fn strip_display(&mut self) {
+        for (_, x) in self.equipments.iter_mut() {
+            x.display = None;
+        }
+        for (_, x) in self.fermentables.iter_mut() {
+            x.display = None;
+        }
+        for (_, x) in self.hops.iter_mut() {
+            x.display = None;
+        }
+        for (_, x) in self.miscs.iter_mut() {
+            x.display = None;
+        }
+        for (_, x) in self.recipes.iter_mut() {
+            x.strip_display();
+        }
+        for (_, x) in self.waters.iter_mut() {
+            x.display = None;
+        }
+        for (_, x) in self.yeasts.iter_mut() {
+            x.display = None;
+        }
+        for (_, x) in self.styles.iter_mut() {
+            x.display = None;
+        }
+        for (_, x) in self.mashs.iter_mut() {
+            x.strip_display();
+        }
+    }
+}
+
+impl From<RecordSet> for Document {
+    fn from(set: RecordSet) -> Document {
+        let mut doc = Document::default();
+        doc.add(set);
+        doc
+    }
+}
@@ -1,5 +1,8 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
+use super::Extensions;
+use units::{Mass, Percent, Time, Volume};
+
 /// equipment
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Equipment {
@@ -9,37 +12,78 @@ pub struct Equipment {
     /// version of the style format (normally 1)
     pub version: i64,
     /// pre-boil volume in liters used in this particular instance for this equipment setup.  Note that this may be a calculated value depending on the `calc_boil_volume` parameter
-    pub boil_size: f64,
+    pub boil_size: Volume,
     /// targer volume in liters of the batch at the start of fermentation
-    pub batch_size: f64,
+    pub batch_size: Volume,
     /// volume of the mash tun in liters.  This parameter can be used to calculate if a particular mash and grain profile will fit in the mash tun.  It may also be used for thermal calculations in the case of a partially full mash tun
     #[serde(skip_serializing_if="Option::is_none")]
-    pub tun_volume: Option<f64>,
+    pub tun_volume: Option<Volume>,
     /// weight of the mash tun in kilograms.  Used primarily to calculate the thermal parameters of the mash tun – in conjunction with the volume and specific heat
     #[serde(skip_serializing_if="Option::is_none")]
-    pub tun_weight: Option<f64>,
+    pub tun_weight: Option<Mass>,
     /// specific heat of the mash tun which is usually a function of the material it is made of.  Typical ranges are 0.1-0.25 for metal and 0.2-0.5 for plastic materials , in Cal/(gram-deg C)
     #[serde(skip_serializing_if="Option::is_none")]
     pub tun_specific_heat: Option<f64>,
     /// amount of top up water in liters normally added just prior to starting fermentation.  Usually used for extract brewing
     #[serde(skip_serializing_if="Option::is_none")]
-    pub top_up_water: Option<f64>,
+    pub top_up_water: Option<Volume>,
+    /// the amount of wort normally lost during transition from the boiler to the fermentation vessel, in liters.  Includes both unusable wort due to trub and wort lost to the chiller and transfer systems
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub trub_chiller_loss: Option<Volume>,
     /// percentage of wort lost to evaporation per hour of the boil
     #[serde(skip_serializing_if="Option::is_none")]
-    pub evap_rate: Option<f64>,
-    /// normal amount of time in hours one boils for this equipment setup.  This can be used with the evaporation rate to calculate the evaporation loss
+    pub evap_rate: Option<Percent>,
+    /// normal amount of time in minutes one boils for this equipment setup.  This can be used with the evaporation rate to calculate the evaporation loss
     #[serde(skip_serializing_if="Option::is_none")]
-    pub boil_time: Option<f64>,
+    pub boil_time: Option<Time>,
     /// flag denoting that the program should calculate the boil size.  Flag may be `true` or `false`.  If `true`, then `boil_size` = (`batch_size` – `top_up_water` – `trub_chiller_loss`) * (1+`boil_time` * `evap_rate` )  If set then the boil size should match this value
     #[serde(skip_serializing_if="Option::is_none")]
     pub calc_boil_volume: Option<bool>,
     /// amount lost in liters to the lauter tun and equipment associated with the lautering process
     #[serde(skip_serializing_if="Option::is_none")]
-    pub lauter_deadspace: Option<f64>,
+    pub lauter_deadspace: Option<Volume>,
     /// amount in liters normally added to the boil kettle before the boil
     #[serde(skip_serializing_if="Option::is_none")]
-    pub top_up_kettle: Option<f64>,
+    pub top_up_kettle: Option<Volume>,
+    /// large batch hop utilization in percent.  This value should be 100% for batches less than 20 gallons, but may be higher (200% or more) for very large batch equipment
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub hop_utilization: Option<Percent>,
     /// notes
     #[serde(skip_serializing_if="Option::is_none")]
     pub notes: Option<String>,
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<EquipmentDisplay>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+/// optional display fields of an equipment record (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct EquipmentDisplay {
+    /// the pre-boil volume normally used for a batch of this size shown in display volume units such as “5.5 gal”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_boil_size: Option<String>,
+    /// the target volume of the batch at the start of fermentation in display volume units such as “5.0 gal”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_batch_size: Option<String>,
+    /// volume of the mash tun in display units such as “10.0 gal” or “20.0 l”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_tun_volume: Option<String>,
+    /// weight of the mash tun in display units such as “3.0 kg” or “6.0 lb”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_tun_weight: Option<String>,
+    /// the amount of top up water normally added just prior to starting fermentation in display volume such as “1.0 gal”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_top_up_water: Option<String>,
+    /// the amount of wort normally lost during transition from the boiler to the fermentation vessel in display volume units such as “1.0 gal”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_trub_chiller_loss: Option<String>,
+    /// the amount of liquid lost in the lauter tun and equipment associated with the lautering process in display volume units such as “1.0 gal”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_lauter_deadspace: Option<String>,
+    /// the amount of top up water normally added to the boil kettle before the boil in display volume units such as “1.0 gal”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_top_up_kettle: Option<String>,
 }
@@ -1,7 +1,10 @@
 // (c) 2017 Joost Yervante Damad
 
+use super::Extensions;
+use units::{Color, Gravity, Mass, Percent};
+
 /// a fermentable type
-#[derive(ToString, EnumString, Debug, Serialize, Deserialize,Clone)]
+#[derive(EnumString, EnumMessage, Debug, Serialize, Deserialize, Clone)]
 pub enum FermentableType {
     /// grain
     Grain,
@@ -15,8 +18,16 @@ pub enum FermentableType {
     DryExtract,
     /// adjunct
     Adjunct,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(FermentableType, [Grain, Sugar, Extract, DryExtract, Adjunct],
+           &[("malt", "Grain"), ("honey", "Sugar"),
+             ("liquid extract", "Extract"), ("lme", "Extract"), ("dme", "Dry Extract")]);
+
 impl Default for FermentableType {
     fn default() -> FermentableType {
         FermentableType::Grain
@@ -35,12 +46,12 @@ pub struct Fermentable {
     #[serde(rename = "type")]
     pub type_: FermentableType,
     /// amount in kg
-    pub amount: f64,
+    pub amount: Mass,
     /// percent dry yield (fine grain) for the grain,
     /// or the raw yield by weight if this is an extract adjunct or sugar
-    pub yield_: f64,
+    pub yield_: Percent,
     /// the color of the item in Lovibond Units (SRM for liquid extracts)
-    pub color: f64,
+    pub color: Color,
     /// may be true if this item is normally added after the boil.
     /// The default value is false since most grains are added
     /// during the mash or boil
@@ -59,11 +70,11 @@ pub struct Fermentable {
     /// fine grain yield.  Only appropriate for a "Grain" or
     /// "Adjunct" type, otherwise this value is ignored
     #[serde(skip_serializing_if="Option::is_none")]
-    pub coarse_fine_diff: Option<f64>,
+    pub coarse_fine_diff: Option<Percent>,
     /// percent moisture in the grain.  Only appropriate for a
     /// "Grain" or "Adjunct" type, otherwise this value is ignored
     #[serde(skip_serializing_if="Option::is_none")]
-    pub moisture: Option<f64>,
+    pub moisture: Option<Percent>,
     /// the diastatic power of the grain as measured in "Lintner"
     /// units. Only appropriate for a "Grain" or "Adjunct" type,
     /// otherwise this value is ignored
@@ -72,11 +83,11 @@ pub struct Fermentable {
     /// the percent protein in the grain.  Only appropriate for
     /// a "Grain" or "Adjunct" type, otherwise this value is ignored
     #[serde(skip_serializing_if="Option::is_none")]
-    pub protein: Option<f64>,
+    pub protein: Option<Percent>,
     /// the recommended maximum percentage (by weight) this
     /// ingredient should represent in a batch of beer
     #[serde(skip_serializing_if="Option::is_none")]
-    pub max_in_batch: Option<f64>,
+    pub max_in_batch: Option<Percent>,
     /// true if it is recommended the grain be mashed, false
     /// if it can be steeped.  A value of true is only appropriate
     /// for a "Grain" or "Adjunct" types.  The default value is false.
@@ -91,16 +102,27 @@ pub struct Fermentable {
     /// type, otherwise this value is ignored
     #[serde(skip_serializing_if="Option::is_none")]
     pub ibu_gal_per_lb: Option<f64>,
-    /// a display version of the amount (BeerSmith extension?)
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<FermentableDisplay>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+/// optional display fields of a fermentable (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct FermentableDisplay {
+    /// the amount of fermentables in this record along with the units formatted for easy display in the current user defined units.  For example “1.5 lbs” or “2.1 kg”
     #[serde(skip_serializing_if="Option::is_none")]
     pub display_amount: Option<String>,
-    /// inventory information (BeerSmith extension?)
+    /// the yield of the fermentable converted to specific gravity units for display.  For example “1.036” or “1.040” might be valid potentials
     #[serde(skip_serializing_if="Option::is_none")]
-    pub inventory: Option<String>,
+    pub potential: Option<Gravity>,
+    /// amount in inventory for this item along with the units – for example “10.0 lb”
     #[serde(skip_serializing_if="Option::is_none")]
-    /// extract potential (BeerSmith extension?)
-    pub potential: Option<f64>,
-    /// a display version of the color (BeerSmith extension?)
+    pub inventory: Option<String>,
+    /// color in user defined color units along with the unit identified – for example “200L” or “40 ebc”
     #[serde(skip_serializing_if="Option::is_none")]
     pub display_color: Option<String>,
 }
@@ -1,5 +1,8 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
+use super::Extensions;
+use units::{Mass, Percent, Time};
+
 /// a hop
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Hop {
@@ -9,14 +12,14 @@ pub struct Hop {
     /// version of the fermentable format (normally 1)
     pub version: i64,
     /// percent alpha of hops
-    pub alpha: f64,
+    pub alpha: Percent,
     /// amount of hops, in kg
-    pub amount: f64,
+    pub amount: Mass,
     /// use of the hop
     #[serde(rename="use")]
     pub use_: HopUse,
     /// time in minutes
-    pub time: f64,
+    pub time: Time,
     /// notes
     #[serde(skip_serializing_if="Option::is_none")]
     pub notes: Option<String>,
@@ -28,10 +31,10 @@ pub struct Hop {
     pub form: Option<HopForm>,
     /// percent beta of hops
     #[serde(skip_serializing_if="Option::is_none")]
-    pub beta: Option<f64>,
+    pub beta: Option<Percent>,
     /// Hop Stability Index - percent of hop alpha lost in 6 months of storage
     #[serde(skip_serializing_if="Option::is_none")]
-    pub hsi: Option<f64>,
+    pub hsi: Option<Percent>,
     /// Place of origin for the hops
     #[serde(skip_serializing_if="Option::is_none")]
     pub origin: Option<String>,
@@ -40,20 +43,40 @@ pub struct Hop {
     pub substitutes: Option<String>,
     /// Humelene leven in percent
     #[serde(skip_serializing_if="Option::is_none")]
-    pub humulene: Option<f64>,
+    pub humulene: Option<Percent>,
     /// Caryophyllene leven in percent
     #[serde(skip_serializing_if="Option::is_none")]
-    pub caryophyllene: Option<f64>,
+    pub caryophyllene: Option<Percent>,
     /// Cohumylone leven in percent
     #[serde(skip_serializing_if="Option::is_none")]
-    pub cohumulone: Option<f64>,
+    pub cohumulone: Option<Percent>,
     /// Myrcene leven in percent
     #[serde(skip_serializing_if="Option::is_none")]
-    pub myrcene: Option<f64>,
+    pub myrcene: Option<Percent>,
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<HopDisplay>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+/// optional display fields of a hop (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct HopDisplay {
+    /// the amount of hops in this record along with the units formatted for easy display in the current user defined units.  For example “100 g” or “1.5 oz”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_amount: Option<String>,
+    /// amount in inventory for this item along with the units – for example “10.0 oz”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub inventory: Option<String>,
+    /// time displayed in minutes for all uses except for the dry hop which is in days.  For example “60 min”, “3 days”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_time: Option<String>,
 }
 
 /// the usage of the hop
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum HopUse {
     /// aroma hop usage
     Aroma,
@@ -69,8 +92,15 @@ pub enum HopUse {
     FirstWort,
     /// mash hop usage
     Mash,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(HopUse, [Aroma, Boil, DryHop, FirstWort, Mash],
+           &[("fwh", "First Wort"), ("first wort hop", "First Wort")]);
+
 impl Default for HopUse {
     fn default() -> HopUse {
         HopUse::Aroma
@@ -78,7 +108,7 @@ impl Default for HopUse {
 }
 
 /// the type of a hop
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum HopType {
     /// a bittering hop
     Bittering,
@@ -86,10 +116,18 @@ pub enum HopType {
     Aroma,
     /// a dual-purpose hop
     Both,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(HopType, [Bittering, Aroma, Both],
+           &[("dual purpose", "Both"), ("aroma/bittering", "Both"), ("bittering/aroma", "Both"),
+             ("flavor", "Aroma")]);
+
 /// the form of a hop
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum HopForm {
     /// pellet hop
     Pellet,
@@ -97,4 +135,12 @@ pub enum HopForm {
     Plug,
     /// leaf hop
     Leaf,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
+
+open_enum!(HopForm, [Pellet, Plug, Leaf],
+           &[("pellets", "Pellet"), ("plugs", "Plug"), ("whole", "Leaf"), ("whole leaf", "Leaf"),
+             ("cone", "Leaf")]);
@@ -1,6 +1,7 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use super::MashStep;
+use super::{Extensions, MashStep};
+use units::{Mass, Temperature};
 
 /// a mash profile
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -11,37 +12,71 @@ pub struct Mash {
     /// version of the style format (normally 1)
     pub version: i64,
     /// the temperature of the grain before adding it to the mash in degrees Celsius
-    pub grain_temp: f64,
+    pub grain_temp: Temperature,
     /// notes
     #[serde(skip_serializing_if="Option::is_none")]
     pub notes: Option<String>,
     /// grain tun temperature – may be used to adjust the infusion temperature for equipment if the program supports it.  Measured in degrees C
     #[serde(skip_serializing_if="Option::is_none")]
-    pub tun_temp: Option<f64>,
+    pub tun_temp: Option<Temperature>,
     /// temperature of the sparge water used in degrees Celsius
     #[serde(skip_serializing_if="Option::is_none")]
-    pub sparge_temp: Option<f64>,
+    pub sparge_temp: Option<Temperature>,
     #[serde(skip_serializing_if="Option::is_none")]
     /// PH of the sparge
     pub ph: Option<f64>,
     /// weight of the mash tun in kilograms
     #[serde(skip_serializing_if="Option::is_none")]
-    pub tun_weight: Option<f64>,
+    pub tun_weight: Option<Mass>,
     /// specific heat of the tun material in calories per gram-degree C
     #[serde(skip_serializing_if="Option::is_none")]
     pub tun_specific_heat: Option<f64>,
     /// if `true`, mash infusion and decoction calculations should take into account the temperature effects of the equipment (tun specific heat and tun weight), if `false`, the tun is assumed to be pre-heated
     #[serde(skip_serializing_if="Option::is_none")]
     pub equip_adjust: Option<bool>,
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<MashDisplay>,
 
     // put the list at the end in the data structure
     
     /// mash steps
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
     pub mash_steps: Vec<MashStep>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+impl Mash {
+    /// remove the optional display fields of the mash and its steps
+    pub fn strip_display(&mut self) {
+        self.display = None;
+        for step in &mut self.mash_steps {
+            step.display = None;
+        }
+    }
+}
+
+/// optional display fields of a mash profile (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct MashDisplay {
+    /// grain temperature in user display units with the units.  For example: “72 F”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_grain_temp: Option<String>,
+    /// tun temperature in user display units.  For example “68 F”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_tun_temp: Option<String>,
+    /// sparge temperature in user defined units.  For example “178 F”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_sparge_temp: Option<String>,
+    /// tun weight in user defined units – for example “10 lb”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_tun_weight: Option<String>,
 }
 
 /// type of the mash step
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum MashStepType {
     /// adding hot water
     Infusion,
@@ -49,8 +84,14 @@ pub enum MashStepType {
     Temperature,
     /// drawing of some mash for boiling
     Decoction,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(MashStepType, [Infusion, Temperature, Decoction], &[("temp", "Temperature")]);
+
 impl Default for MashStepType {
     fn default() -> MashStepType {
         MashStepType::Infusion
@@ -1,5 +1,8 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
+use super::Extensions;
+use units::{Temperature, Time, Volume};
+
 /// a mash step is an internal record used within a mash profile to denote a separate step in a multi-step mash.  A mash step is not intended for use outside of a mash profile
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct MashStep {
@@ -12,21 +15,50 @@ pub struct MashStep {
     pub type_: MashStepType,
     /// the volume of water in liters to infuse in this step.  Required only for infusion steps, though one may also add water for temperature mash steps.  One should not have an infusion amount for decoction steps
     #[serde(skip_serializing_if="Option::is_none")]
-    pub infuse_amount: Option<f64>,
+    pub infuse_amount: Option<Volume>,
     /// target temperature for this step in degrees Celsius
-    pub step_temp: f64,
+    pub step_temp: Temperature,
     /// number of minutes to spend at this step – i.e. the amount of time we are to hold this particular step temperature
-    pub step_time: f64,
+    pub step_time: Time,
     /// time in minutes to achieve the desired step temperature – useful particularly for temperature mashes where it may take some time to achieve the step temperature
     #[serde(skip_serializing_if="Option::is_none")]
-    pub ramp_time: Option<f64>,
+    pub ramp_time: Option<Time>,
     /// the temperature you can expect the mash to fall to after a long mash step, measured in degrees Celsius
     #[serde(skip_serializing_if="Option::is_none")]
-    pub end_temp: Option<f64>,
+    pub end_temp: Option<Temperature>,
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<MashStepDisplay>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+/// optional display fields of a mash step (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct MashStepDisplay {
+    /// step temperature in user defined temperature units such as “154 F”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_step_temp: Option<String>,
+    /// infusion amount along with the volume units as in “20 l” or “13 qt”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_infuse_amt: Option<String>,
+    /// textual description of this step such as “Increase temperature to 152 F over 2 minutes”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub description: Option<String>,
+    /// the total ratio of water to grain for this step, including the units such as “1.5 qt/lb”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub water_grain_ratio: Option<String>,
+    /// calculated volume of mash to decoct, only applicable for a decoction step, including the units such as “1.2 gal”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub decoction_amt: Option<String>,
+    /// the calculated infusion temperature based on the current step, grain, and other settings, including the units such as “154 F”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub infuse_temp: Option<String>,
 }
 
 /// type of the mash step
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum MashStepType {
     /// adding hot water
     Infusion,
@@ -34,8 +66,14 @@ pub enum MashStepType {
     Temperature,
     /// drawing of some mash for boiling
     Decoction,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(MashStepType, [Infusion, Temperature, Decoction], &[("temp", "Temperature")]);
+
 impl Default for MashStepType {
     fn default() -> MashStepType {
         MashStepType::Infusion
@@ -1,5 +1,8 @@
 // (c) 2017 Joost Yervante Damad
 
+use super::Extensions;
+use units::{Mass, Time, Volume};
+
 /// misc beer ingredient
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Misc {
@@ -15,25 +18,61 @@ pub struct Misc {
     #[serde(rename="use")]
     pub use_: MiscUse,
     /// time in minutes it is used
-    pub time: f64,
-    /// amount (liter or kg)
+    pub time: Time,
+    /// amount (liter or kg), see `mass` and `volume`
     pub amount: f64,
     /// if amount is in kg
     pub amount_is_weight: bool,
     /// short description what the ingredient is used for
+    #[serde(skip_serializing_if="Option::is_none")]
     pub use_for: Option<String>,
     /// detailed notes
+    #[serde(skip_serializing_if="Option::is_none")]
     pub notes: Option<String>,
-    /// display string for time
-    pub display_time: Option<String>,
-    /// display string for amount
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<MiscDisplay>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+impl Misc {
+    /// the amount as a mass, if it is given in kg
+    pub fn mass(&self) -> Option<Mass> {
+        if self.amount_is_weight {
+            Some(Mass(self.amount))
+        } else {
+            None
+        }
+    }
+
+    /// the amount as a volume, if it is given in liters
+    pub fn volume(&self) -> Option<Volume> {
+        if self.amount_is_weight {
+            None
+        } else {
+            Some(Volume(self.amount))
+        }
+    }
+}
+
+/// optional display fields of a misc item (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct MiscDisplay {
+    /// the amount of the item in this record along with the units formatted for easy display in the current user defined units.  For example “1.5 lbs” or “2.1 kg”
+    #[serde(skip_serializing_if="Option::is_none")]
     pub display_amount: Option<String>,
-    /// inventory
+    /// amount in inventory for this item along with the units – for example “10.0 lb”
+    #[serde(skip_serializing_if="Option::is_none")]
     pub inventory: Option<String>,
+    /// time in appropriate units along with the units as in “10 min” or “3 days”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_time: Option<String>,
 }
 
 /// misc type
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum MiscType {
     /// a spice
     Spice,
@@ -49,8 +88,16 @@ pub enum MiscType {
     Flavor,
     /// an other misc item
     Other,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Unknown(String),
 }
 
+open_enum!(MiscType, Unknown, [Spice, Fining, WaterAgent, Herb, Flavor, Other],
+           &[("spices", "Spice"), ("finings", "Fining"), ("herbs", "Herb"),
+             ("flavour", "Flavor"), ("water treatment", "Water Agent")]);
+
 impl Default for MiscType {
     fn default() -> MiscType {
         MiscType::Spice
@@ -58,7 +105,7 @@ impl Default for MiscType {
 }
 
 /// usage for a misc item
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum MiscUse {
     /// use in boil
     Boil,
@@ -70,8 +117,15 @@ pub enum MiscUse {
     Secondary,
     /// use at bottling
     Bottling,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(MiscUse, [Boil, Mash, Primary, Secondary, Bottling],
+           &[("fermentation", "Primary"), ("bottle", "Bottling"), ("packaging", "Bottling")]);
+
 impl Default for MiscUse {
     fn default() -> MiscUse {
         MiscUse::Boil
@@ -1,49 +1,155 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::collections::HashMap;
+use linked_hash_map::LinkedHashMap;
 
+pub use self::document::*;
 pub use self::equipment::*;
 pub use self::fermentable::*;
 pub use self::hop::*;
 pub use self::mash::*;
 pub use self::mash_step::*;
 pub use self::misc::*;
+pub use self::named::*;
+pub use self::open_enum::OpenEnum;
 pub use self::recipe::*;
 pub use self::style::*;
 pub use self::water::*;
 pub use self::yeast::*;
 
+/// a collection of records keyed by name, that remembers the order the records were added in
+pub type RecordMap<T> = LinkedHashMap<String, T>;
+
+/// a child element of a record that this library does not know, e.g. a vendor extension
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Extension {
+    /// tag of the element
+    pub tag: String,
+    /// tag of the known element it came after, empty if it came first
+    #[serde(default, skip_serializing_if="String::is_empty")]
+    pub after: String,
+    /// raw xml content of the element, `None` if it was written as `<TAG/>`
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    pub content: Option<String>,
+}
+
+/// unknown child elements of a record, in input order
+///
+/// this is a list rather than a map keyed by tag: a record can repeat an unknown tag, and each
+/// element is written back in place, after its `after` element, so the order has to be kept
+pub type Extensions = Vec<Extension>;
+
 /// a record set
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RecordSet {
     /// the default empty record set
     Empty,
     /// a set of named equipments
-    Equipments(HashMap<String, Equipment>),
+    Equipments(#[serde(deserialize_with="named_map::deserialize")] RecordMap<Equipment>),
     /// a set of named fermentables
-    Fermentables(HashMap<String, Fermentable>),
+    Fermentables(#[serde(deserialize_with="named_map::deserialize")] RecordMap<Fermentable>),
     /// a set of named hops
-    Hops(HashMap<String, Hop>),
+    Hops(#[serde(deserialize_with="named_map::deserialize")] RecordMap<Hop>),
     /// a set of named miscelaneous items
-    Miscs(HashMap<String, Misc>),
+    Miscs(#[serde(deserialize_with="named_map::deserialize")] RecordMap<Misc>),
     /// a set of recipes
-    Recipes(HashMap<String, Recipe>),
+    Recipes(#[serde(deserialize_with="named_map::deserialize")] RecordMap<Recipe>),
     /// a set of named water profiles
-    Waters(HashMap<String, Water>),
+    Waters(#[serde(deserialize_with="named_map::deserialize")] RecordMap<Water>),
     /// a set of named yeasts
-    Yeasts(HashMap<String, Yeast>),
+    Yeasts(#[serde(deserialize_with="named_map::deserialize")] RecordMap<Yeast>),
     /// a set of named styles
-    Styles(HashMap<String, Style>),
+    Styles(#[serde(deserialize_with="named_map::deserialize")] RecordMap<Style>),
     /// a set of named mash profiles
-    Mashs(HashMap<String, Mash>),
+    Mashs(#[serde(deserialize_with="named_map::deserialize")] RecordMap<Mash>),
+}
+
+impl RecordSet {
+    /// sort the records by name
+    pub fn sort_by_name(&mut self) {
+        match *self {
+            RecordSet::Empty => (),
+            RecordSet::Equipments(ref mut v) => sort_records(v),
+            RecordSet::Fermentables(ref mut v) => sort_records(v),
+            RecordSet::Hops(ref mut v) => sort_records(v),
+            RecordSet::Miscs(ref mut v) => sort_records(v),
+            RecordSet::Recipes(ref mut v) => sort_records(v),
+            RecordSet::Waters(ref mut v) => sort_records(v),
+            RecordSet::Yeasts(ref mut v) => sort_records(v),
+            RecordSet::Styles(ref mut v) => sort_records(v),
+            RecordSet::Mashs(ref mut v) => sort_records(v),
+        }
+    }
+
+    /// remove all optional display and estimated fields (BeerXML Appendix A)
+    pub fn strip_display(&mut self) {
+        match *self {
+            RecordSet::Empty => (),
+            RecordSet::Equipments(ref mut v) => {
+                for (_, x) in v.iter_mut() {
+                    x.display = None;
+                }
+            }
+            RecordSet::Fermentables(ref mut v) => {
+                for (_, x) in v.iter_mut() {
+                    x.display = None;
+                }
+            }
+            RecordSet::Hops(ref mut v) => {
+                for (_, x) in v.iter_mut() {
+                    x.display = None;
+                }
+            }
+            RecordSet::Miscs(ref mut v) => {
+                for (_, x) in v.iter_mut() {
+                    x.display = None;
+                }
+            }
+            RecordSet::Recipes(ref mut v) => {
+                for (_, x) in v.iter_mut() {
+                    x.strip_display();
+                }
+            }
+            RecordSet::Waters(ref mut v) => {
+                for (_, x) in v.iter_mut() {
+                    x.display = None;
+                }
+            }
+            RecordSet::Yeasts(ref mut v) => {
+                for (_, x) in v.iter_mut() {
+                    x.display = None;
+                }
+            }
+            RecordSet::Styles(ref mut v) => {
+                for (_, x) in v.iter_mut() {
+                    x.display = None;
+                }
+            }
+            RecordSet::Mashs(ref mut v) => {
+                for (_, x) in v.iter_mut() {
+                    x.strip_display();
+                }
+            }
+        }
+    }
 }
 
+fn sort_records<T>(map: &mut RecordMap<T>) {
+    let mut records: Vec<(String, T)> = map.drain().collect();
+    records.sort_by(|a, b| a.0.cmp(&b.0));
+    map.extend(records);
+}
+
+#[macro_use]
+mod open_enum;
+
+mod document;
 mod equipment;
 mod fermentable;
 mod hop;
 mod mash;
 mod mash_step;
 mod misc;
+mod named;
 mod recipe;
 mod style;
 mod water;
@@ -0,0 +1,241 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use super::*;
+
+/// a record that is identified by its name
+pub trait Named {
+    /// name of the record
+    fn name(&self) -> &str;
+    /// set the name of the record
+    fn set_name(&mut self, name: String);
+}
+
+macro_rules! impl_named {
+    ($($t:ty),*) => {
+        $(
+            impl Named for $t {
+                fn name(&self) -> &str {
+                    &self.name
+                }
+                fn set_name(&mut self, name: String) {
+                    self.name = name;
+                }
+            }
+        )*
+    }
+}
+
+impl_named!(Equipment, Fermentable, Hop, Mash, MashStep, Misc, Recipe, Style, Water, Yeast);
+
+/// find the first record with the given name in a list of records
+pub fn find_named<'a, T>(list: &'a [T], name: &str) -> Option<&'a T>
+    where T: Named
+{
+    list.iter().find(|x| x.name() == name)
+}
+
+/// serde helper to keep an ordered list of named records, where names may repeat
+///
+/// The list is written as a sequence of single entry `name: record` maps,
+/// mirroring the name keyed maps used for top-level record collections.
+pub mod named_list {
+    use std::collections::HashMap;
+    use std::result::Result;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::ser::{SerializeMap, SerializeSeq};
+
+    use super::Named;
+
+    struct Entry<'a, T: 'a>(&'a T);
+
+    impl<'a, T> Serialize for Entry<'a, T>
+        where T: Serialize + Named
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry(self.0.name(), self.0)?;
+            map.end()
+        }
+    }
+
+    /// serialize a list of named records
+    pub fn serialize<S, T>(v: &[T], serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer,
+              T: Serialize + Named
+    {
+        let mut seq = serializer.serialize_seq(Some(v.len()))?;
+        for x in v {
+            seq.serialize_element(&Entry(x))?;
+        }
+        seq.end()
+    }
+
+    /// deserialize a list of named records
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+        where D: Deserializer<'de>,
+              T: Deserialize<'de> + Named
+    {
+        let entries: Vec<HashMap<String, T>> = Deserialize::deserialize(deserializer)?;
+        let mut res = vec![];
+        for entry in entries {
+            for (name, mut x) in entry {
+                x.set_name(name);
+                res.push(x);
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// serde helper to keep the name of a single nested record
+///
+/// The record is written with its name as an extra `name` field, as there is
+/// no map key to carry it.
+pub mod named_record {
+    use std::result::Result;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Named;
+
+    #[derive(Serialize)]
+    struct Borrowed<'a, T: 'a> {
+        name: &'a str,
+        #[serde(flatten)]
+        record: &'a T,
+    }
+
+    #[derive(Deserialize)]
+    struct Owned<T> {
+        #[serde(default)]
+        name: String,
+        #[serde(flatten)]
+        record: T,
+    }
+
+    impl<T> Owned<T>
+        where T: Named
+    {
+        fn into_record(self) -> T {
+            let mut record = self.record;
+            record.set_name(self.name);
+            record
+        }
+    }
+
+    /// serialize a named record
+    pub fn serialize<S, T>(x: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer,
+              T: Serialize + Named
+    {
+        Borrowed {
+                name: x.name(),
+                record: x,
+            }
+            .serialize(serializer)
+    }
+
+    /// deserialize a named record
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+        where D: Deserializer<'de>,
+              T: Deserialize<'de> + Named
+    {
+        let x: Owned<T> = Deserialize::deserialize(deserializer)?;
+        Ok(x.into_record())
+    }
+
+    /// serde helper for an optional named record
+    pub mod option {
+        use std::result::Result;
+
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        use super::{Borrowed, Owned};
+        use super::super::Named;
+
+        /// serialize an optional named record
+        pub fn serialize<S, T>(x: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+            where S: Serializer,
+                  T: Serialize + Named
+        {
+            x.as_ref()
+                .map(|x| {
+                    Borrowed {
+                        name: x.name(),
+                        record: x,
+                    }
+                })
+                .serialize(serializer)
+        }
+
+        /// deserialize an optional named record
+        pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+            where D: Deserializer<'de>,
+                  T: Deserialize<'de> + Named
+        {
+            let x: Option<Owned<T>> = Deserialize::deserialize(deserializer)?;
+            Ok(x.map(Owned::into_record))
+        }
+    }
+}
+
+/// serde helper for name keyed record collections that fills in the record names from the keys
+pub mod named_map {
+    use std::result::Result;
+
+    use serde::{Deserialize, Deserializer};
+
+    use super::Named;
+    use super::super::RecordMap;
+
+    /// deserialize a name keyed collection of records
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<RecordMap<T>, D::Error>
+        where D: Deserializer<'de>,
+              T: Deserialize<'de> + Named
+    {
+        let mut map: RecordMap<T> = Deserialize::deserialize(deserializer)?;
+        for (name, x) in map.iter_mut() {
+            x.set_name(name.clone());
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+    use {json, toml, xml, yaml};
+
+    fn as_xml(set: &RecordSet) -> Vec<u8> {
+        let mut buf = vec![];
+        xml::write(&mut buf, set).unwrap();
+        buf
+    }
+
+    #[test]
+    fn nested_names_survive_serde_formats() {
+        let set = xml::read_file(Path::new("data/recipes.xml")).unwrap();
+        let expected = as_xml(&set);
+
+        let mut buf = vec![];
+        json::write(&mut buf, &set).unwrap();
+        assert_eq!(as_xml(&json::read(&buf[..]).unwrap()), expected);
+
+        let mut buf = vec![];
+        yaml::write(&mut buf, &set).unwrap();
+        assert_eq!(as_xml(&yaml::read(&buf[..]).unwrap()), expected);
+
+        let path = env::temp_dir().join("beerxml-nested-names.toml");
+        toml::write_file(&path, &set).unwrap();
+        let back = toml::read_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(as_xml(&back), expected);
+    }
+}
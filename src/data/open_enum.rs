@@ -0,0 +1,128 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::fmt;
+
+/// an enum of record values that keeps the values it does not know, so they are written
+/// back out as they were read
+///
+/// The fallback variant is `Other(String)`, except for `MiscType` where BeerXML already
+/// defines `Other` and it is called `Unknown(String)`.
+pub trait OpenEnum: fmt::Display + Sized {
+    /// all known values
+    fn known() -> Vec<Self>;
+
+    /// spellings used by other programs, with the value they stand for
+    fn aliases() -> &'static [(&'static str, &'static str)];
+
+    /// the value as it was read, if it is not a known one
+    fn unknown(&self) -> Option<&str>;
+
+    /// keep a value that is not a known one
+    fn from_unknown(value: String) -> Self;
+
+    /// the known value written exactly as `value`, or else an unknown one
+    fn parse_exact(value: &str) -> Self {
+        Self::known()
+            .into_iter()
+            .find(|x| x.to_string() == value)
+            .unwrap_or_else(|| Self::from_unknown(value.into()))
+    }
+
+    /// the known value `value` stands for, ignoring case, spaces, dashes and underscores and
+    /// looking through the aliases, or else an unknown one
+    fn parse_lenient(value: &str) -> Self {
+        let key = normalize(value);
+        let key = match Self::aliases().iter().find(|&&(alias, _)| normalize(alias) == key) {
+            Some(&(_, known)) => normalize(known),
+            None => key,
+        };
+        Self::known()
+            .into_iter()
+            .find(|x| normalize(&x.to_string()) == key)
+            .unwrap_or_else(|| Self::from_unknown(value.into()))
+    }
+
+    /// the known value written exactly as `value`, or else the one `extra` gives for the
+    /// lowercase `value`, e.g. for a name another format uses, or else `parse_lenient`
+    fn parse_with<F>(value: &str, extra: F) -> Self
+        where F: Fn(&str) -> Option<Self>
+    {
+        let exact = Self::parse_exact(value);
+        if exact.unknown().is_none() {
+            return exact;
+        }
+        extra(&value.to_lowercase()).unwrap_or_else(|| Self::parse_lenient(value))
+    }
+}
+
+/// lowercase letters and digits only, so `Dry-Hop`, `dry_hop` and `DRY HOP` compare equal
+fn normalize(value: &str) -> String {
+    value.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// implement `Display` and `OpenEnum` for an enum deriving `EnumMessage`, given its known
+/// variants: known values are written with their strum name, unknown ones as they were read
+macro_rules! open_enum {
+    ($t:ident, $unknown:ident, [$($known:ident),*], $aliases:expr) => {
+        impl ::std::fmt::Display for $t {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match *self {
+                    $t::$unknown(ref value) => f.write_str(value),
+                    ref x => f.write_str(::strum::EnumMessage::get_serializations(x)[0]),
+                }
+            }
+        }
+
+        impl $crate::data::OpenEnum for $t {
+            fn known() -> Vec<Self> {
+                vec![$($t::$known),*]
+            }
+
+            fn aliases() -> &'static [(&'static str, &'static str)] {
+                $aliases
+            }
+
+            fn unknown(&self) -> Option<&str> {
+                match *self {
+                    $t::$unknown(ref value) => Some(value),
+                    _ => None,
+                }
+            }
+
+            fn from_unknown(value: String) -> Self {
+                $t::$unknown(value)
+            }
+        }
+    };
+    ($t:ident, [$($known:ident),*], $aliases:expr) => {
+        open_enum!($t, Other, [$($known),*], $aliases);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use data::*;
+
+    #[test]
+    fn lenient_parsing() {
+        assert_eq!(HopUse::parse_exact("Dry Hop").to_string(), "Dry Hop");
+        assert_eq!(HopUse::parse_exact("dry hop").unknown(), Some("dry hop"));
+        assert_eq!(HopUse::parse_lenient("Dry-Hop").to_string(), "Dry Hop");
+        assert_eq!(HopUse::parse_lenient("Whirlpool").unknown(), Some("Whirlpool"));
+        assert_eq!(HopUse::parse_lenient("FWH").to_string(), "First Wort");
+        assert_eq!(HopForm::parse_lenient("pellet").to_string(), "Pellet");
+        assert_eq!(YeastType::parse_lenient("Kveik").to_string(), "Ale");
+        assert_eq!(YeastType::parse_lenient("Bacteria").unknown(), Some("Bacteria"));
+        assert_eq!(FermentableType::parse_lenient("Fruit").unknown(), Some("Fruit"));
+        assert_eq!(FermentableType::parse_with("juice", |_| None).unknown(), Some("juice"));
+        assert_eq!(HopForm::parse_with("Leaf (wet)", |f| match f {
+                       "leaf (wet)" => Some(HopForm::Leaf),
+                       _ => None,
+                   })
+                       .to_string(),
+                   "Leaf");
+        assert_eq!(MiscType::parse_lenient("other").to_string(), "Other");
+        assert_eq!(MiscType::parse_lenient("Salt").unknown(), Some("Salt"));
+        assert_eq!(StyleType::parse_lenient("Kombucha").to_string(), "Kombucha");
+    }
+}
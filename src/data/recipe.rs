@@ -1,7 +1,7 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::collections::HashMap;
 use super::*;
+use units::{Gravity, Percent, Temperature, Time, Volume};
 
 /// a beer recipe
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -14,51 +14,245 @@ pub struct Recipe {
     /// type of the recipe
     #[serde(rename="type")]
     pub type_: RecipeType,
-    /// style of the recipe
-    pub style:Style,
     /// name of the brewer
     pub brewer: String,
     /// optional name of the assistant brewer
+    #[serde(skip_serializing_if="Option::is_none")]
     pub asst_brewer: Option<String>,
     /// target size of the finished batch in liters
-    pub batch_size: f64,
+    pub batch_size: Volume,
     /// starting size for the main boil of the wort in liters
-    pub boil_size: f64,
+    pub boil_size: Volume,
     /// total boil time in minutes
-    pub boil_time: f64,
+    pub boil_time: Time,
     /// the percent brewhouse efficiency to be used for estimating the starting gravity of the beer; not required for “Extract” recipes, but is required for “Partial Mash” and “All Grain” recipes
-    pub efficiency: Option<f64>,
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub efficiency: Option<Percent>,
     /// notes
     #[serde(skip_serializing_if="Option::is_none")]
     pub notes: Option<String>,
     /// original gravity (pre-fermentation)
     #[serde(skip_serializing_if="Option::is_none")]
-    pub og: Option<f64>,
+    pub og: Option<Gravity>,
     /// final gravity of the finished beer
     #[serde(skip_serializing_if="Option::is_none")]
-    pub fg: Option<f64>,
-    /// an optional equipment record
+    pub fg: Option<Gravity>,
+    /// number of fermentation stages used – typically a number between one and three
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub fermentation_stages: Option<i64>,
+    /// time spent in the primary in days
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub primary_age: Option<f64>,
+    /// temperature in degrees Celsius for the primary fermentation
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub primary_temp: Option<Temperature>,
+    /// time spent in the secondary in days
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub secondary_age: Option<f64>,
+    /// temperature in degrees Celsius for the secondary fermentation
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub secondary_temp: Option<Temperature>,
+    /// time spent in the third fermenter in days
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub tertiary_age: Option<f64>,
+    /// temperature in degrees Celsius for the third fermentation stage
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub tertiary_temp: Option<Temperature>,
+    /// time to age the beer in days after bottling
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub age: Option<f64>,
+    /// temperature in degrees Celsius for bottle aging
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub age_temp: Option<Temperature>,
+    /// date brewed in an easily recognizable format such as “3 Dec 04”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub date: Option<String>,
+    /// carbonation for this recipe in volumes of CO2
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub carbonation: Option<f64>,
+    /// `true` if the batch was force carbonated using CO2 pressure, `false` if the batch was carbonated using a priming agent
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub forced_carbonation: Option<bool>,
+    /// text describing the priming agent such as “Honey” or “Corn Sugar” – used only for bottle conditioned beers
     #[serde(skip_serializing_if="Option::is_none")]
+    pub priming_sugar_name: Option<String>,
+    /// temperature in degrees Celsius for either bottling or forced carbonation
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub carbonation_temp: Option<Temperature>,
+    /// factor used to convert this priming agent to an equivalent amount of corn sugar for a bottled scenario
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub priming_sugar_equiv: Option<f64>,
+    /// used to factor in the smaller amount of sugar needed for large containers, such as a keg, compared to bottles
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub keg_priming_factor: Option<f64>,
+    /// tasting notes – may be multiline
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub taste_notes: Option<String>,
+    /// number between zero and fifty denoting the taste rating – corresponds to the 50 point BJCP rating system
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub taste_rating: Option<f64>,
+    /// optional estimated values (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub estimates: Option<RecipeEstimates>,
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<RecipeDisplay>,
+    /// style of the recipe
+    #[serde(with="named_record")]
+    pub style:Style,
+    /// an optional equipment record
+    #[serde(default, skip_serializing_if="Option::is_none", with="named_record::option")]
     pub equipment:Option<Equipment>,
     /// mash profile
-    #[serde(skip_serializing_if="Option::is_none")]
+    #[serde(default, skip_serializing_if="Option::is_none", with="named_record::option")]
     pub mash: Option<Mash>,
 
     // TABLES NEED TO BE LAST TO WORK WITH TOML
+    // ingredient lists keep the order of the recipe and may contain the same name more than once
     /// hop ingredient records
-    pub hops: HashMap<String, Hop>,
+    #[serde(default, with="named_list", skip_serializing_if="Vec::is_empty")]
+    pub hops: Vec<Hop>,
     /// fermentable ingredient records
-    pub fermentables: HashMap<String, Fermentable>,
+    #[serde(default, with="named_list", skip_serializing_if="Vec::is_empty")]
+    pub fermentables: Vec<Fermentable>,
     /// misc ingredient records
-    pub miscs: HashMap<String, Misc>,
+    #[serde(default, with="named_list", skip_serializing_if="Vec::is_empty")]
+    pub miscs: Vec<Misc>,
     /// yeast ingredient records
-    pub yeasts: HashMap<String, Yeast>,
+    #[serde(default, with="named_list", skip_serializing_if="Vec::is_empty")]
+    pub yeasts: Vec<Yeast>,
     /// water info records
-    pub waters: HashMap<String, Water>, // TODO: complete
+    #[serde(default, with="named_list", skip_serializing_if="Vec::is_empty")]
+    pub waters: Vec<Water>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+impl Recipe {
+    /// first hop addition with the given name
+    pub fn hop(&self, name: &str) -> Option<&Hop> {
+        find_named(&self.hops, name)
+    }
+
+    /// first fermentable with the given name
+    pub fn fermentable(&self, name: &str) -> Option<&Fermentable> {
+        find_named(&self.fermentables, name)
+    }
+
+    /// first misc item with the given name
+    pub fn misc(&self, name: &str) -> Option<&Misc> {
+        find_named(&self.miscs, name)
+    }
+
+    /// first yeast with the given name
+    pub fn yeast(&self, name: &str) -> Option<&Yeast> {
+        find_named(&self.yeasts, name)
+    }
+
+    /// first water profile with the given name
+    pub fn water(&self, name: &str) -> Option<&Water> {
+        find_named(&self.waters, name)
+    }
+
+    /// remove the optional display and estimated fields of the recipe and all its records
+    pub fn strip_display(&mut self) {
+        self.estimates = None;
+        self.display = None;
+        self.style.display = None;
+        if let Some(ref mut e) = self.equipment {
+            e.display = None;
+        }
+        if let Some(ref mut m) = self.mash {
+            m.strip_display();
+        }
+        for x in &mut self.hops {
+            x.display = None;
+        }
+        for x in &mut self.fermentables {
+            x.display = None;
+        }
+        for x in &mut self.miscs {
+            x.display = None;
+        }
+        for x in &mut self.yeasts {
+            x.display = None;
+        }
+        for x in &mut self.waters {
+            x.display = None;
+        }
+    }
+}
+
+/// estimated values of a recipe as calculated by the brewing program (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct RecipeEstimates {
+    /// calculated estimate of the original gravity for this recipe along with the units
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub est_og: Option<String>,
+    /// calculated estimate for the final specific gravity of this recipe along with the units as in “1.015 sg”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub est_fg: Option<String>,
+    /// the estimated color of the beer in user defined color units
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub est_color: Option<String>,
+    /// the estimated bitterness level of the beer in IBUs
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub ibu: Option<String>,
+    /// may be “Rager”, “Tinseth” or “Garetz” corresponding to the method/equation used to estimate IBUs for this recipe
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub ibu_method: Option<String>,
+    /// estimated percent alcohol by volume for this recipe
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub est_abv: Option<String>,
+    /// actual alcohol by volume calculated from the OG and FG measured
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub abv: Option<String>,
+    /// the actual efficiency as calculated using the measured original and final gravity
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub actual_efficiency: Option<String>,
+    /// calorie estimate based on the measured starting and ending gravity, in “Cal/pint”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub calories: Option<String>,
+}
+
+/// optional display fields of a recipe (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct RecipeDisplay {
+    /// batch size in user defined units along with the units as in “5.0 gal”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_batch_size: Option<String>,
+    /// boil size in user defined units such as “6.5 gal”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_boil_size: Option<String>,
+    /// measured original gravity in user defined units such as “6.4 plato”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_og: Option<String>,
+    /// measured final gravity in user defined units such as “1.035 sg”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_fg: Option<String>,
+    /// primary fermentation temperature in user defined units such as “64 F”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_primary_temp: Option<String>,
+    /// secondary fermentation temperature in user defined units such as “56 F”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_secondary_temp: Option<String>,
+    /// tertiary temperature in user defined units such as “20 C”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_tertiary_temp: Option<String>,
+    /// temperature to use when aging the beer in user units such as “55 F”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_age_temp: Option<String>,
+    /// carbonation/bottling temperature in appropriate units such as “40 F”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_carb_temp: Option<String>,
+    /// text description of the carbonation used such as “50g corn sugar” or “Kegged at 20psi”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub carbonation_used: Option<String>,
 }
 
 /// recipe type
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum RecipeType {
     /// extract recipe
     Extract,
@@ -70,8 +264,15 @@ pub enum RecipeType {
     #[serde(rename = "All Grain")]
     #[strum(serialize = "All Grain")]
     AllGrain,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(RecipeType, [Extract, PartialMash, AllGrain],
+           &[("partial", "Partial Mash"), ("biab", "All Grain")]);
+
 impl Default for RecipeType {
     fn default() -> RecipeType {
         RecipeType::AllGrain
@@ -1,5 +1,8 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
+use super::Extensions;
+use units::{Color, Gravity, Percent};
+
 /// a beer style
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Style {
@@ -20,21 +23,21 @@ pub struct Style {
     #[serde(rename="type")]
     pub type_: StyleType,
     /// minimum specific gravity as measured relative to water.  For example “1.040” might be a reasonable minimum for a Pale Ale
-    pub og_min: f64,
+    pub og_min: Gravity,
     /// maximum specific gravity as measured relative to water
-    pub og_max: f64,
+    pub og_max: Gravity,
     /// minimum final gravity as measured relative to water
-    pub fg_min: f64,
+    pub fg_min: Gravity,
     /// maximum final gravity as measured relative to water
-    pub fg_max: f64,
+    pub fg_max: Gravity,
     /// recommended minimum bitterness for this style as measured in International Bitterness Units (IBUs)
     pub ibu_min: f64,
     /// recommended maximum bitterness for this style as measured in International Bitterness Units (IBUs)
     pub ibu_max: f64,
     /// minimum recommended color in SRM
-    pub color_min: f64,
+    pub color_min: Color,
     /// maximum recommended color in SRM
-    pub color_max: f64,
+    pub color_max: Color,
     /// minimum recommended carbonation for this style in volumes of CO2
     #[serde(skip_serializing_if="Option::is_none")]
     pub carb_min: Option<f64>,
@@ -43,10 +46,10 @@ pub struct Style {
     pub carb_max: Option<f64>,
     /// minimum recommended alcohol by volume as a percentage
     #[serde(skip_serializing_if="Option::is_none")]
-    pub abv_min: Option<f64>,
+    pub abv_min: Option<Percent>,
     /// maximum recommended alcohol by volume as a percentage
     #[serde(skip_serializing_if="Option::is_none")]
-    pub abv_max: Option<f64>,
+    pub abv_max: Option<Percent>,
     /// description of the style, history
     #[serde(skip_serializing_if="Option::is_none")]
     pub notes: Option<String>,
@@ -59,10 +62,57 @@ pub struct Style {
     /// example beers of this style
     #[serde(skip_serializing_if="Option::is_none")]
     pub examples: Option<String>,
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<StyleDisplay>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+/// optional display fields of a style (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct StyleDisplay {
+    /// original gravity minimum in user defined units such as “1.036 sg”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_og_min: Option<String>,
+    /// original gravity maximum in user defined units such as “1.056 sg”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_og_max: Option<String>,
+    /// final gravity minimum in user defined units such as “1.010 sg”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_fg_min: Option<String>,
+    /// final gravity maximum in user defined units such as “1.019 sg”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_fg_max: Option<String>,
+    /// minimum color in user defined units such as “30 srm”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_color_min: Option<String>,
+    /// maximum color in user defined units such as “20 srm”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_color_max: Option<String>,
+    /// original gravity range for the style such as “1.030-1.040 sg”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub og_range: Option<String>,
+    /// final gravity range such as “1.010-1.015 sg”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub fg_range: Option<String>,
+    /// bitterness range in IBUs such as “10-20 IBU”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub ibu_range: Option<String>,
+    /// carbonation range in volumes such as “2.0-2.6 vols”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub carb_range: Option<String>,
+    /// color range such as “10-20 SRM”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub color_range: Option<String>,
+    /// ABV range such as “4.5-5.5%”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub abv_range: Option<String>,
 }
 
 /// defines the type of beverage associated with this style
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum StyleType {
     /// Lager beer
     Lager,
@@ -76,8 +126,14 @@ pub enum StyleType {
     Mixed,
     /// Cider
     Cider,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(StyleType, [Lager, Ale, Mead, Wheat, Mixed, Cider], &[("hybrid", "Mixed")]);
+
 impl Default for StyleType {
     fn default() -> StyleType {
         StyleType::Ale
@@ -1,5 +1,8 @@
 // (c) 2017 Joost Yervante Damad
 
+use super::Extensions;
+use units::Volume;
+
 /// water information
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Water {
@@ -9,7 +12,7 @@ pub struct Water {
     /// version of the record, should be 1
     pub version: i64,
     /// Volume of water to use in a recipe in liters
-    pub amount: f64,
+    pub amount: Volume,
     /// the amount of Ca in ppm
     pub calcium: f64,
     /// the amount of HCO3 in ppm
@@ -28,4 +31,18 @@ pub struct Water {
     /// notes
     #[serde(skip_serializing_if="Option::is_none")]
     pub notes: Option<String>,
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<WaterDisplay>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+/// optional display fields of a water profile (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct WaterDisplay {
+    /// the amount of water in this record along with the units formatted for easy display in the current user defined units.  For example “5.0 gal” or “20.0 l”
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display_amount: Option<String>,
 }
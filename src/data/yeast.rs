@@ -1,5 +1,8 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
+use super::Extensions;
+use units::{Mass, Percent, Temperature, Volume};
+
 /// a yeast
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Yeast {
@@ -13,7 +16,7 @@ pub struct Yeast {
     pub type_: YeastType,
     /// yeast form
     pub form: YeastForm,
-    /// amount (liter or kg)
+    /// amount (liter or kg), see `mass` and `volume`
     pub amount: f64,
     /// if amount is in kg
     pub amount_is_weight: bool,
@@ -25,16 +28,16 @@ pub struct Yeast {
     pub product_id: Option<String>,
     /// minimum recommended temperature for fermenting this yeast strain in degrees Celsius
     #[serde(skip_serializing_if="Option::is_none")]
-    pub min_temperature: Option<f64>,
+    pub min_temperature: Option<Temperature>,
     /// maximum recommended temperature for fermenting this yeast strain in Celsius
     #[serde(skip_serializing_if="Option::is_none")]
-    pub max_temperature: Option<f64>,
+    pub max_temperature: Option<Temperature>,
     /// yeast flocculation
     #[serde(skip_serializing_if="Option::is_none")]
     pub flocculation: Option<YeastFlocculation>,
     /// attenuation of the yeast in percent
     #[serde(skip_serializing_if="Option::is_none")]
-    pub attenuation: Option<f64>,
+    pub attenuation: Option<Percent>,
     /// notes
     #[serde(skip_serializing_if="Option::is_none")]
     pub notes: Option<String>,
@@ -49,25 +52,56 @@ pub struct Yeast {
     pub max_reuse: Option<i64>,
     /// flag denoting that this yeast was added for a secondary (or later) fermentation as opposed to the primary fermentation.  Useful if one uses two or more yeast strains for a single brew (eg: Lambic).  Default value is false
     pub add_to_secondary: bool,
-    /// amount
+    /// optional display fields (BeerXML Appendix A)
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub display: Option<YeastDisplay>,
+    /// child elements this library does not know, e.g. vendor extensions, as raw xml
+    #[serde(default, skip_serializing_if="Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+impl Yeast {
+    /// the amount as a mass, if it is given in kg
+    pub fn mass(&self) -> Option<Mass> {
+        if self.amount_is_weight {
+            Some(Mass(self.amount))
+        } else {
+            None
+        }
+    }
+
+    /// the amount as a volume, if it is given in liters
+    pub fn volume(&self) -> Option<Volume> {
+        if self.amount_is_weight {
+            None
+        } else {
+            Some(Volume(self.amount))
+        }
+    }
+}
+
+/// optional display fields of a yeast (BeerXML Appendix A)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct YeastDisplay {
+    /// the amount of yeast or starter in this record along with the units formatted for easy display in the current user defined units.  For example “1.5 oz” or “100 g”
     #[serde(skip_serializing_if="Option::is_none")]
     pub display_amount: Option<String>,
-    /// recommended minimum temperature
+    /// minimum fermentation temperature converted to current user units along with the units.  For example “54.0 F” or “24.2 C”
     #[serde(skip_serializing_if="Option::is_none")]
     pub display_min_temp: Option<String>,
-    /// recommended maximum temperature
+    /// maximum fermentation temperature converted to current user units along with the units.  For example “54.0 F” or “24.2 C”
     #[serde(skip_serializing_if="Option::is_none")]
     pub display_max_temp: Option<String>,
-    /// inventory
+    /// amount in inventory for this hop along with the units – for example “10.0 pkgs”
     #[serde(skip_serializing_if="Option::is_none")]
     pub inventory: Option<String>,
-    /// date the culture was made
+    /// date sample was last cultured in a neutral date form such as “10 Dec 04”
     #[serde(skip_serializing_if="Option::is_none")]
     pub culture_date: Option<String>,
 }
 
 /// the type of a yeast
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum YeastType {
     /// ale (top-fermenting) yeast
     Ale,
@@ -79,8 +113,14 @@ pub enum YeastType {
     Wine,
     /// champage yeast
     Champagne,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(YeastType, [Ale, Lager, Wheat, Wine, Champagne], &[("kveik", "Ale"), ("saison", "Ale")]);
+
 impl Default for YeastType {
     fn default() -> YeastType {
         YeastType::Ale
@@ -88,7 +128,7 @@ impl Default for YeastType {
 }
 
 /// the form of the yeast
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum YeastForm {
     /// liquid yeast
     Liquid,
@@ -98,8 +138,15 @@ pub enum YeastForm {
     Slate,
     /// a yeast culture
     Culture,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(YeastForm, [Liquid, Dry, Slate, Culture],
+           &[("slant", "Slate"), ("dried", "Dry"), ("powder", "Dry")]);
+
 impl Default for YeastForm {
     fn default() -> YeastForm {
         YeastForm::Liquid
@@ -107,7 +154,7 @@ impl Default for YeastForm {
 }
 
 /// flocculation of a yeast
-#[derive(ToString, EnumString, Serialize, Deserialize, Debug, Clone)]
+#[derive(EnumString, EnumMessage, Serialize, Deserialize, Debug, Clone)]
 pub enum YeastFlocculation {
     /// low flocculation
     Low,
@@ -119,8 +166,15 @@ pub enum YeastFlocculation {
     #[serde(rename = "Very High")]
     #[strum(serialize = "Very High")]
     VeryHigh,
+    /// a value BeerXML does not know, kept as read
+    #[serde(untagged)]
+    #[strum(default="true")]
+    Other(String),
 }
 
+open_enum!(YeastFlocculation, [Low, Medium, High, VeryHigh],
+           &[("med", "Medium"), ("very low", "Low")]);
+
 impl Default for YeastFlocculation {
     fn default() -> YeastFlocculation {
         YeastFlocculation::Low
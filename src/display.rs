@@ -0,0 +1,364 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! showing quantities in the units a brewer prefers, and generating the display fields
+//! (BeerXML Appendix A)
+//!
+//! BeerXML stores everything in metric base units, the display fields repeat some of the
+//! values in the units of the program that wrote them, e.g. `DISPLAY_AMOUNT` `5.00 oz`.
+//! Here they are generated from the stored values for a `System`, replacing what was read,
+//! so they never disagree with the values.
+
+use std::mem;
+
+use data::*;
+use units::{Mass, Quantity, System, Volume};
+
+/// a record with quantities that can be shown to a brewer
+pub trait Quantities {
+    /// the quantities of the record by BeerXML field, leaving out the optional ones that
+    /// are not set and the ones of the records it contains
+    fn quantities(&self) -> Vec<(&'static str, Quantity)>;
+
+    /// the quantities of the record by BeerXML field, formatted in the units of `system`
+    fn render(&self, system: System) -> Vec<(&'static str, String)> {
+        self.quantities()
+            .into_iter()
+            .map(|(field, quantity)| (field, system.format(quantity)))
+            .collect()
+    }
+}
+
+fn q<Q: Into<Quantity>>(value: Q) -> Option<Quantity> {
+    Some(value.into())
+}
+
+fn q_o<Q: Into<Quantity>>(value: Option<Q>) -> Option<Quantity> {
+    value.map(Into::into)
+}
+
+fn present(fields: Vec<(&'static str, Option<Quantity>)>) -> Vec<(&'static str, Quantity)> {
+    fields.into_iter().filter_map(|(field, quantity)| quantity.map(|q| (field, q))).collect()
+}
+
+/// the amount of a yeast or misc, which is a mass or a volume
+fn amount(mass: Option<Mass>, volume: Option<Volume>) -> Option<Quantity> {
+    q_o(mass).or_else(|| q_o(volume))
+}
+
+impl Quantities for Hop {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("ALPHA", q(self.alpha)),
+                     ("AMOUNT", q(self.amount)),
+                     ("TIME", q(self.time)),
+                     ("BETA", q_o(self.beta)),
+                     ("HSI", q_o(self.hsi)),
+                     ("HUMULENE", q_o(self.humulene)),
+                     ("CARYOPHYLLENE", q_o(self.caryophyllene)),
+                     ("COHUMULONE", q_o(self.cohumulone)),
+                     ("MYRCENE", q_o(self.myrcene))])
+    }
+}
+
+impl Quantities for Fermentable {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("AMOUNT", q(self.amount)),
+                     ("YIELD", q(self.yield_)),
+                     ("COLOR", q(self.color)),
+                     ("COARSE_FINE_DIFF", q_o(self.coarse_fine_diff)),
+                     ("MOISTURE", q_o(self.moisture)),
+                     ("PROTEIN", q_o(self.protein)),
+                     ("MAX_IN_BATCH", q_o(self.max_in_batch))])
+    }
+}
+
+impl Quantities for Yeast {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("AMOUNT", amount(self.mass(), self.volume())),
+                     ("MIN_TEMPERATURE", q_o(self.min_temperature)),
+                     ("MAX_TEMPERATURE", q_o(self.max_temperature)),
+                     ("ATTENUATION", q_o(self.attenuation))])
+    }
+}
+
+impl Quantities for Misc {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("AMOUNT", amount(self.mass(), self.volume())), ("TIME", q(self.time))])
+    }
+}
+
+impl Quantities for Water {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("AMOUNT", q(self.amount))])
+    }
+}
+
+impl Quantities for Style {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("OG_MIN", q(self.og_min)),
+                     ("OG_MAX", q(self.og_max)),
+                     ("FG_MIN", q(self.fg_min)),
+                     ("FG_MAX", q(self.fg_max)),
+                     ("COLOR_MIN", q(self.color_min)),
+                     ("COLOR_MAX", q(self.color_max)),
+                     ("ABV_MIN", q_o(self.abv_min)),
+                     ("ABV_MAX", q_o(self.abv_max))])
+    }
+}
+
+impl Quantities for MashStep {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("INFUSE_AMOUNT", q_o(self.infuse_amount)),
+                     ("STEP_TEMP", q(self.step_temp)),
+                     ("STEP_TIME", q(self.step_time)),
+                     ("RAMP_TIME", q_o(self.ramp_time)),
+                     ("END_TEMP", q_o(self.end_temp))])
+    }
+}
+
+impl Quantities for Mash {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("GRAIN_TEMP", q(self.grain_temp)),
+                     ("TUN_TEMP", q_o(self.tun_temp)),
+                     ("SPARGE_TEMP", q_o(self.sparge_temp)),
+                     ("TUN_WEIGHT", q_o(self.tun_weight))])
+    }
+}
+
+impl Quantities for Equipment {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("BOIL_SIZE", q(self.boil_size)),
+                     ("BATCH_SIZE", q(self.batch_size)),
+                     ("TUN_VOLUME", q_o(self.tun_volume)),
+                     ("TUN_WEIGHT", q_o(self.tun_weight)),
+                     ("TOP_UP_WATER", q_o(self.top_up_water)),
+                     ("TRUB_CHILLER_LOSS", q_o(self.trub_chiller_loss)),
+                     ("EVAP_RATE", q_o(self.evap_rate)),
+                     ("BOIL_TIME", q_o(self.boil_time)),
+                     ("LAUTER_DEADSPACE", q_o(self.lauter_deadspace)),
+                     ("TOP_UP_KETTLE", q_o(self.top_up_kettle)),
+                     ("HOP_UTILIZATION", q_o(self.hop_utilization))])
+    }
+}
+
+impl Quantities for Recipe {
+    fn quantities(&self) -> Vec<(&'static str, Quantity)> {
+        present(vec![("BATCH_SIZE", q(self.batch_size)),
+                     ("BOIL_SIZE", q(self.boil_size)),
+                     ("BOIL_TIME", q(self.boil_time)),
+                     ("EFFICIENCY", q_o(self.efficiency)),
+                     ("OG", q_o(self.og)),
+                     ("FG", q_o(self.fg)),
+                     ("PRIMARY_TEMP", q_o(self.primary_temp)),
+                     ("SECONDARY_TEMP", q_o(self.secondary_temp)),
+                     ("TERTIARY_TEMP", q_o(self.tertiary_temp)),
+                     ("AGE_TEMP", q_o(self.age_temp)),
+                     ("CARBONATION_TEMP", q_o(self.carbonation_temp))])
+    }
+}
+
+/// fills in the display fields while walking the records
+struct Generator {
+    system: System,
+}
+
+impl Generator {
+    fn show<Q: Into<Quantity>>(&self, value: Q) -> Option<String> {
+        Some(self.system.format(value))
+    }
+
+    fn show_o<Q: Into<Quantity>>(&self, value: Option<Q>) -> Option<String> {
+        value.map(|v| self.system.format(v))
+    }
+
+    fn hop(&self, h: &mut Hop) {
+        let d = h.display.get_or_insert_with(HopDisplay::default);
+        d.display_amount = self.show(h.amount);
+        d.display_time = self.show(h.time);
+    }
+
+    fn fermentable(&self, f: &mut Fermentable) {
+        let d = f.display.get_or_insert_with(FermentableDisplay::default);
+        d.display_amount = self.show(f.amount);
+        d.display_color = self.show(f.color);
+    }
+
+    fn yeast(&self, y: &mut Yeast) {
+        let amount = amount(y.mass(), y.volume());
+        let d = y.display.get_or_insert_with(YeastDisplay::default);
+        d.display_amount = self.show_o(amount);
+        d.display_min_temp = self.show_o(y.min_temperature);
+        d.display_max_temp = self.show_o(y.max_temperature);
+    }
+
+    fn misc(&self, m: &mut Misc) {
+        let amount = amount(m.mass(), m.volume());
+        let d = m.display.get_or_insert_with(MiscDisplay::default);
+        d.display_amount = self.show_o(amount);
+        d.display_time = self.show(m.time);
+    }
+
+    fn water(&self, w: &mut Water) {
+        let d = w.display.get_or_insert_with(WaterDisplay::default);
+        d.display_amount = self.show(w.amount);
+    }
+
+    fn style(&self, s: &mut Style) {
+        let d = s.display.get_or_insert_with(StyleDisplay::default);
+        d.display_og_min = self.show(s.og_min);
+        d.display_og_max = self.show(s.og_max);
+        d.display_fg_min = self.show(s.fg_min);
+        d.display_fg_max = self.show(s.fg_max);
+        d.display_color_min = self.show(s.color_min);
+        d.display_color_max = self.show(s.color_max);
+    }
+
+    fn mash_step(&self, s: &mut MashStep) {
+        let d = s.display.get_or_insert_with(MashStepDisplay::default);
+        d.display_step_temp = self.show(s.step_temp);
+        d.display_infuse_amt = self.show_o(s.infuse_amount);
+    }
+
+    fn mash(&self, m: &mut Mash) {
+        {
+            let d = m.display.get_or_insert_with(MashDisplay::default);
+            d.display_grain_temp = self.show(m.grain_temp);
+            d.display_tun_temp = self.show_o(m.tun_temp);
+            d.display_sparge_temp = self.show_o(m.sparge_temp);
+            d.display_tun_weight = self.show_o(m.tun_weight);
+        }
+        for s in &mut m.mash_steps {
+            self.mash_step(s);
+        }
+    }
+
+    fn equipment(&self, e: &mut Equipment) {
+        let d = e.display.get_or_insert_with(EquipmentDisplay::default);
+        d.display_boil_size = self.show(e.boil_size);
+        d.display_batch_size = self.show(e.batch_size);
+        d.display_tun_volume = self.show_o(e.tun_volume);
+        d.display_tun_weight = self.show_o(e.tun_weight);
+        d.display_top_up_water = self.show_o(e.top_up_water);
+        d.display_trub_chiller_loss = self.show_o(e.trub_chiller_loss);
+        d.display_lauter_deadspace = self.show_o(e.lauter_deadspace);
+        d.display_top_up_kettle = self.show_o(e.top_up_kettle);
+    }
+
+    fn recipe(&self, r: &mut Recipe) {
+        {
+            let d = r.display.get_or_insert_with(RecipeDisplay::default);
+            d.display_batch_size = self.show(r.batch_size);
+            d.display_boil_size = self.show(r.boil_size);
+            d.display_og = self.show_o(r.og);
+            d.display_fg = self.show_o(r.fg);
+            d.display_primary_temp = self.show_o(r.primary_temp);
+            d.display_secondary_temp = self.show_o(r.secondary_temp);
+            d.display_tertiary_temp = self.show_o(r.tertiary_temp);
+            d.display_age_temp = self.show_o(r.age_temp);
+            d.display_carb_temp = self.show_o(r.carbonation_temp);
+        }
+        self.style(&mut r.style);
+        if let Some(ref mut e) = r.equipment {
+            self.equipment(e);
+        }
+        if let Some(ref mut m) = r.mash {
+            self.mash(m);
+        }
+        for h in &mut r.hops {
+            self.hop(h);
+        }
+        for f in &mut r.fermentables {
+            self.fermentable(f);
+        }
+        for m in &mut r.miscs {
+            self.misc(m);
+        }
+        for y in &mut r.yeasts {
+            self.yeast(y);
+        }
+        for w in &mut r.waters {
+            self.water(w);
+        }
+    }
+}
+
+/// generate the display fields of all records of a `Document` in the units of `system`
+pub fn generate_document(doc: &mut Document, system: System) {
+    let g = Generator { system };
+    for (_, x) in doc.equipments.iter_mut() {
+        g.equipment(x);
+    }
+    for (_, x) in doc.fermentables.iter_mut() {
+        g.fermentable(x);
+    }
+    for (_, x) in doc.hops.iter_mut() {
+        g.hop(x);
+    }
+    for (_, x) in doc.miscs.iter_mut() {
+        g.misc(x);
+    }
+    for (_, x) in doc.recipes.iter_mut() {
+        g.recipe(x);
+    }
+    for (_, x) in doc.waters.iter_mut() {
+        g.water(x);
+    }
+    for (_, x) in doc.yeasts.iter_mut() {
+        g.yeast(x);
+    }
+    for (_, x) in doc.styles.iter_mut() {
+        g.style(x);
+    }
+    for (_, x) in doc.mashs.iter_mut() {
+        g.mash(x);
+    }
+}
+
+/// generate the display fields of all records of a `RecordSet` in the units of `system`
+pub fn generate(set: &mut RecordSet, system: System) {
+    let mut doc = Document::from(mem::replace(set, RecordSet::Empty));
+    generate_document(&mut doc, system);
+    // a document made from a single record set converts back to one
+    *set = doc.into_record_set().unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reconcile::{reconcile_document, Policy};
+    use xml;
+
+    #[test]
+    fn generate_display_fields() {
+        let xml = "<HOPS><HOP><NAME>Saaz</NAME><ALPHA>3.5</ALPHA><AMOUNT>0.14174762</AMOUNT>\
+                   <USE>Dry Hop</USE><TIME>4320</TIME>\
+                   <DISPLAY_AMOUNT>1 kg</DISPLAY_AMOUNT></HOP></HOPS>\n\
+                   <YEASTS><YEAST><NAME>US-05</NAME><AMOUNT>0.0115</AMOUNT>\
+                   <AMOUNT_IS_WEIGHT>TRUE</AMOUNT_IS_WEIGHT><MIN_TEMPERATURE>15</MIN_TEMPERATURE>\
+                   </YEAST></YEASTS>\n\
+                   <RECIPES><RECIPE><NAME>Best</NAME><CARBONATION_TEMP>4.4</CARBONATION_TEMP>\
+                   <CARBONATION_USED>Kegged at 20psi</CARBONATION_USED></RECIPE></RECIPES>";
+        let mut doc = xml::read_document(xml.as_bytes()).unwrap();
+        assert_eq!(doc.hops["Saaz"].render(System::Us),
+                   vec![("ALPHA", "3.5 %".to_string()),
+                        ("AMOUNT", "5.00 oz".to_string()),
+                        ("TIME", "3 days".to_string())]);
+
+        generate_document(&mut doc, System::Us);
+        let hop = doc.hops["Saaz"].display.clone().unwrap();
+        assert_eq!(hop.display_amount.unwrap(), "5.00 oz");
+        assert_eq!(hop.display_time.unwrap(), "3 days");
+        let yeast = doc.yeasts["US-05"].display.clone().unwrap();
+        assert_eq!(yeast.display_amount.unwrap(), "0.41 oz");
+        assert_eq!(yeast.display_min_temp.unwrap(), "59.0 °F");
+        assert_eq!(yeast.display_max_temp, None);
+        let recipe = doc.recipes["Best"].display.clone().unwrap();
+        assert_eq!(recipe.display_carb_temp.unwrap(), "39.9 °F");
+        assert_eq!(recipe.carbonation_used.unwrap(), "Kegged at 20psi");
+        assert!(reconcile_document(&mut doc, Policy::Report).is_empty());
+
+        generate_document(&mut doc, System::Metric);
+        let yeast = doc.yeasts["US-05"].display.clone().unwrap();
+        assert_eq!(yeast.display_amount.unwrap(), "11.5 g");
+        assert!(reconcile_document(&mut doc, Policy::Report).is_empty());
+    }
+}
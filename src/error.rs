@@ -1,7 +1,50 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
+use std::fmt;
+
 use quick_xml::errors as xml;
 
+/// where in the input a record field was found
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Location {
+    /// path of the enclosing records, e.g. ``RECIPE `IPA` / HOP `Cascade` ``
+    pub path: String,
+    /// kind of the innermost record, e.g. `HOP`
+    pub kind: String,
+    /// name of the innermost record, empty if it was not read yet
+    pub name: String,
+    /// element name of the field, empty for the record itself
+    pub field: String,
+    /// line in the input, starting at 1; 0 if the record was not read from a file
+    pub line: usize,
+    /// column in the input in characters, starting at 1; 0 if the record was not read from a file
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // records that were not read from a file have no line and column
+        let mut res = String::new();
+        if self.line > 0 {
+            res = format!("line {}, column {}", self.line, self.column);
+        }
+        if !self.path.is_empty() {
+            if !res.is_empty() {
+                res.push_str(" in ");
+            }
+            res.push_str(&self.path);
+        }
+        if !self.field.is_empty() {
+            if !res.is_empty() {
+                res.push_str(", ");
+            }
+            res.push_str("field ");
+            res.push_str(&self.field);
+        }
+        f.write_str(&res)
+    }
+}
+
 error_chain! {
 
     errors {
@@ -10,6 +53,11 @@ error_chain! {
             description("Unable to parse something")
             display("Unable to parse `{}` as {}", unknown, what)
         }
+        /// an error in a record, with its location in the input
+        Invalid(location:Box<Location>, reason:String) {
+            description("Invalid record")
+            display("{}: {}", location, reason)
+        }
     }
     
     links {
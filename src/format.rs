@@ -0,0 +1,228 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::fmt;
+use std::path::Path;
+
+use serde_json;
+use serde_json::Value;
+
+use error::*;
+
+/// the top-level keys of the json serialization of a `Document` or `RecordSet`
+const KINDS: &[&str] = &["Equipments", "Fermentables", "Hops", "Miscs", "Recipes", "Waters",
+                         "Yeasts", "Styles", "Mashs"];
+
+/// a file format the library can read, and except for BeerSmith also write
+#[derive(EnumString, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `beerXML`
+    #[strum(serialize="xml")]
+    Xml,
+    /// json serialization of the data structures
+    #[strum(serialize="json")]
+    Json,
+    /// BeerJSON 1.0
+    #[strum(serialize="beerjson")]
+    BeerJson,
+    /// Brewfather recipe or batch json
+    #[strum(serialize="brewfather")]
+    Brewfather,
+    /// BeerSmith `.bsmx`, read only
+    #[strum(serialize="bsmx")]
+    Bsmx,
+    /// yaml serialization of the data structures
+    #[strum(serialize="yaml")]
+    Yaml,
+    /// toml serialization of the data structures
+    #[strum(serialize="toml")]
+    Toml,
+}
+
+impl Format {
+    /// the format for a file extension, in any case; Brewfather files use the plain json
+    /// extension and are only recognised by their content, see `for_file`
+    pub fn from_extension(ext: &str) -> Option<Format> {
+        match ext.to_lowercase().as_str() {
+            "xml" => Some(Format::Xml),
+            "json" => Some(Format::Json),
+            "beerjson" => Some(Format::BeerJson),
+            "bsmx" => Some(Format::Bsmx),
+            "yaml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    /// the format for a file name, based on its extension
+    pub fn from_path(filename: &Path) -> Result<Format> {
+        let ext = match filename.extension() {
+            Some(ext) => ext.to_string_lossy(),
+            None => return Err("no file extension found".into()),
+        };
+        match Format::from_extension(&ext) {
+            Some(format) => Ok(format),
+            None => Err(format!("unknown file extension {}", ext).into()),
+        }
+    }
+
+    /// `true` for the formats that are stored as json
+    pub fn is_json(&self) -> bool {
+        matches!(*self, Format::Json | Format::BeerJson | Format::Brewfather)
+    }
+
+    /// guess the format from the content of a file
+    pub fn detect(content: &[u8]) -> Option<Format> {
+        let text = String::from_utf8_lossy(content);
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        if text.starts_with('<') {
+            // BeerXML elements are upper case, BeerSmith ones like `Recipes` are not
+            return Some(if root_element(text).chars().any(|c| c.is_ascii_lowercase()) {
+                Format::Bsmx
+            } else {
+                Format::Xml
+            });
+        }
+        if text.starts_with('{') || text.starts_with('[') {
+            if let Ok(v) = serde_json::from_str::<Value>(text) {
+                return detect_json(&v);
+            }
+        }
+        let line = text.lines().map(str::trim).find(|l| !l.is_empty() && !l.starts_with('#'))?;
+        if line.starts_with("---") {
+            return Some(Format::Yaml);
+        }
+        if line.starts_with('[') {
+            return Some(Format::Toml);
+        }
+        match (line.find('='), line.find(':')) {
+            (Some(e), Some(c)) if e < c => Some(Format::Toml),
+            (Some(_), None) => Some(Format::Toml),
+            (_, Some(_)) => Some(Format::Yaml),
+            (None, None) => None,
+        }
+    }
+
+    /// the format of a file: the extension decides, except that the content tells the json
+    /// formats apart and is used when the extension is missing or unknown
+    pub fn for_file(filename: &Path, content: &[u8]) -> Result<Format> {
+        match (Format::from_path(filename), Format::detect(content)) {
+            (Ok(Format::Json), Some(f)) if f.is_json() => Ok(f),
+            (Ok(f), _) | (Err(_), Some(f)) => Ok(f),
+            (Err(e), None) => Err(e),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Format::Xml => "xml",
+            Format::Json => "json",
+            Format::BeerJson => "beerjson",
+            Format::Brewfather => "brewfather",
+            Format::Bsmx => "bsmx",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+        })
+    }
+}
+
+/// the name of the root element of an xml text, after the declaration, comments and doctype
+fn root_element(mut text: &str) -> &str {
+    loop {
+        let end = if text.starts_with("<?") {
+            text.find("?>").map(|i| i + 2)
+        } else if text.starts_with("<!--") {
+            text.find("-->").map(|i| i + 3)
+        } else if text.starts_with("<!") {
+            text.find('>').map(|i| i + 1)
+        } else {
+            break;
+        };
+        match end {
+            Some(end) => text = text[end..].trim_start(),
+            None => return "",
+        }
+    }
+    let name = text.trim_start_matches('<');
+    let end = name.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(name.len());
+    &name[..end]
+}
+
+fn detect_json(v: &Value) -> Option<Format> {
+    match *v {
+        Value::Object(ref m) if m.contains_key("beerjson") => Some(Format::BeerJson),
+        Value::Object(ref m) if m.keys().all(|k| KINDS.contains(&k.as_str())) => Some(Format::Json),
+        Value::String(ref s) if s == "Empty" => Some(Format::Json),
+        _ if is_brewfather(v) => Some(Format::Brewfather),
+        _ => None,
+    }
+}
+
+/// a Brewfather recipe or batch, or a list of them: objects with a `_type`, a batch number,
+/// an embedded recipe or a list of fermentables
+fn is_brewfather(v: &Value) -> bool {
+    match *v {
+        Value::Object(ref m) => {
+            m.contains_key("_type") || m.contains_key("batchNo") ||
+            m.get("recipe").iter().any(|r| r.is_object()) ||
+            m.get("fermentables").iter().any(|f| f.is_array())
+        }
+        Value::Array(ref v) => !v.is_empty() && v.iter().all(is_brewfather),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn detect_data_files() {
+        for entry in fs::read_dir("data").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("xml") {
+                continue;
+            }
+            let content = fs::read(&path).unwrap();
+            assert_eq!(Format::detect(&content), Some(Format::Xml), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn detect_content() {
+        assert_eq!(Format::detect(b"\xef\xbb\xbf<?xml version=\"1.0\"?><HOPS/>"), Some(Format::Xml));
+        assert_eq!(Format::detect(b"<Recipes><Data><Hops><F_H_NAME>Saaz</F_H_NAME>"),
+                   Some(Format::Bsmx));
+        assert_eq!(Format::detect(b"{\"Hops\": {}}"), Some(Format::Json));
+        assert_eq!(Format::detect(b"{\"beerjson\": {\"version\": 1.0}}"), Some(Format::BeerJson));
+        assert_eq!(Format::detect(b"[{\"_type\": \"recipe\", \"name\": \"Pale Ale\"}]"),
+                   Some(Format::Brewfather));
+        assert_eq!(Format::detect(b"{\"name\": \"Batch\", \"batchNo\": 7}"),
+                   Some(Format::Brewfather));
+        assert_eq!(Format::detect(b"{\"name\": \"Pale Ale\", \"batchSize\": 20}"), None);
+        assert_eq!(Format::detect(b"<?xml version=\"1.0\"?>\n<!-- <F_H_NAME> -->\n<RECIPES>\
+                                    <RECIPE><NOTES>&lt;F_H_NAME&gt; <F_X/></NOTES>"),
+                   Some(Format::Xml));
+        assert_eq!(Format::to_string(&Format::BeerJson), "beerjson");
+        assert_eq!("bsmx".parse::<Format>().unwrap(), Format::Bsmx);
+        assert_eq!(Format::detect(b"---\nHops:\n  Saaz: {}"), Some(Format::Yaml));
+        assert_eq!(Format::detect(b"# hops\n[Hops.\"Saaz\"]\nalpha = 3.5"), Some(Format::Toml));
+        assert_eq!(Format::detect(b"document = \"Hops\""), Some(Format::Toml));
+        assert_eq!(Format::detect(b""), None);
+    }
+
+    #[test]
+    fn file_format() {
+        let bf = b"{\"name\": \"Pale Ale\", \"fermentables\": []}";
+        assert_eq!(Format::for_file(Path::new("recipe.XML"), b"").unwrap(), Format::Xml);
+        assert_eq!(Format::for_file(Path::new("recipe.json"), bf).unwrap(), Format::Brewfather);
+        assert_eq!(Format::for_file(Path::new("recipe.bsmx.txt"), b"<Recipes><F_R_NAME>")
+                       .unwrap(),
+                   Format::Bsmx);
+        assert!(Format::for_file(Path::new("recipe"), b"").is_err());
+    }
+}
@@ -3,8 +3,10 @@
 #![warn(missing_docs)]
 #![recursion_limit="128"]
 
-//! `beerXML`/json/yaml/toml parsing and generating library
+//! `beerXML`/json/BeerJSON/yaml/toml parsing and generating library with BeerSmith import and Brewfather import and export
 
+extern crate encoding_rs;
+extern crate linked_hash_map;
 extern crate quick_xml;
 extern crate serde;
 extern crate serde_json;
@@ -21,55 +23,281 @@ extern crate serde_derive;
 #[macro_use]
 extern crate strum_macros;
 
+use std::fs::{self, File};
+use std::io::{Read, Write};
 use std::path::Path;
 
-use data::RecordSet;
+use data::{Document, RecordSet};
 use error::*;
+use options::WriteOptions;
+
+pub use format::Format;
+pub use report::ParseReport;
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use options::Order;
+
     #[test]
     fn it_works() {}
+
+    #[test]
+    fn read_and_write_streams() {
+        let xml = fs::read_to_string("data/hops.xml").unwrap();
+        let set = read_from(xml.as_bytes(), None).unwrap();
+        let names = |set: RecordSet| Document::from(set).hops.keys().cloned().collect::<Vec<_>>();
+        for format in &[Format::Json, Format::BeerJson, Format::Yaml, Format::Toml, Format::Xml] {
+            let mut buf = vec![];
+            write_to(&mut buf, &set, *format, &WriteOptions::default()).unwrap();
+            let back = read_from(&buf[..], None).unwrap();
+            assert_eq!(names(back), names(set.clone()), "{:?}", format);
+        }
+        assert!(write_to(&mut vec![], &set, Format::Bsmx, &WriteOptions::default()).is_err());
+        assert!(read_from(&b"no idea"[..], None).is_err());
+    }
+
+    #[test]
+    fn output_is_byte_stable() {
+        let set = read_file(Path::new("data/hops.xml")).unwrap();
+        let reversed = match set.clone() {
+            RecordSet::Hops(hops) => RecordSet::Hops(hops.into_iter().rev().collect()),
+            set => panic!("expected hops, got {:?}", set),
+        };
+        let by_name = WriteOptions {
+            order: Order::Name,
+            ..WriteOptions::default()
+        };
+        let write = |set: &RecordSet, options: &WriteOptions, format: Format| {
+            let mut buf = vec![];
+            write_to(&mut buf, set, format, options).unwrap();
+            buf
+        };
+        let default = WriteOptions::default();
+        for format in &[Format::Xml, Format::Json, Format::BeerJson, Format::Yaml, Format::Toml] {
+            assert_eq!(write(&set, &default, *format),
+                       write(&set, &default, *format),
+                       "{:?}",
+                       format);
+            assert_eq!(write(&set, &by_name, *format),
+                       write(&reversed, &by_name, *format),
+                       "{:?}",
+                       format);
+        }
+    }
+
+    #[test]
+    fn recipe_ageing_and_carbonation_round_trip() {
+        let xml = "<RECIPES><RECIPE><NAME>Best</NAME><VERSION>1</VERSION><TYPE>All Grain</TYPE>\
+                   <BREWER>Joost</BREWER><BATCH_SIZE>20</BATCH_SIZE><BOIL_SIZE>26</BOIL_SIZE>\
+                   <BOIL_TIME>60</BOIL_TIME><FERMENTATION_STAGES>3</FERMENTATION_STAGES>\
+                   <PRIMARY_AGE>7</PRIMARY_AGE><PRIMARY_TEMP>19</PRIMARY_TEMP>\
+                   <SECONDARY_AGE>14</SECONDARY_AGE><SECONDARY_TEMP>16</SECONDARY_TEMP>\
+                   <TERTIARY_AGE>7</TERTIARY_AGE><TERTIARY_TEMP>12</TERTIARY_TEMP>\
+                   <AGE>30</AGE><AGE_TEMP>10</AGE_TEMP><DATE>3 Dec 2017</DATE>\
+                   <CARBONATION>2.4</CARBONATION><FORCED_CARBONATION>TRUE</FORCED_CARBONATION>\
+                   <PRIMING_SUGAR_NAME>Dextrose</PRIMING_SUGAR_NAME>\
+                   <CARBONATION_TEMP>20</CARBONATION_TEMP>\
+                   <PRIMING_SUGAR_EQUIV>1.1</PRIMING_SUGAR_EQUIV>\
+                   <KEG_PRIMING_FACTOR>0.5</KEG_PRIMING_FACTOR>\
+                   <TASTE_NOTES>Malty</TASTE_NOTES><TASTE_RATING>38</TASTE_RATING>\
+                   </RECIPE></RECIPES>";
+        let doc = read_document_from(xml.as_bytes(), Some(Format::Xml)).unwrap();
+        let recipe = serde_json::to_value(&doc.recipes["Best"]).unwrap();
+        assert_eq!(recipe["fermentation_stages"], 3);
+        assert_eq!(recipe["forced_carbonation"], true);
+        assert_eq!(recipe["taste_notes"], "Malty");
+        for format in &[Format::Xml, Format::Json, Format::Yaml, Format::Toml] {
+            let mut buf = vec![];
+            write_document_to(&mut buf, &doc, *format, &WriteOptions::default()).unwrap();
+            let back = read_document_from(&buf[..], Some(*format)).unwrap();
+            assert_eq!(serde_json::to_value(&back.recipes["Best"]).unwrap(), recipe, "{:?}", format);
+        }
+
+        // an explicit FALSE is kept too, not dropped as if it were missing
+        let xml = xml.replace("<FORCED_CARBONATION>TRUE", "<FORCED_CARBONATION>FALSE");
+        let doc = read_document_from(xml.as_bytes(), Some(Format::Xml)).unwrap();
+        assert_eq!(doc.recipes["Best"].forced_carbonation, Some(false));
+        let mut buf = vec![];
+        write_document_to(&mut buf, &doc, Format::Xml, &WriteOptions::default()).unwrap();
+        let written = String::from_utf8(buf).unwrap();
+        assert!(written.contains("<FORCED_CARBONATION>FALSE</FORCED_CARBONATION>"));
+        let back = read_document_from(written.as_bytes(), Some(Format::Xml)).unwrap();
+        assert_eq!(back.recipes["Best"].forced_carbonation, Some(false));
+    }
 }
 
-/// read a `beerXML`, json, yaml or toml file
+/// read a `beerXML`, json, BeerJSON (`.beerjson`), Brewfather, BeerSmith (`.bsmx`), yaml or toml file;
+/// the format follows from the extension, or from the content where that is ambiguous or unknown
 pub fn read_file(filename: &Path) -> Result<RecordSet> {
-    if let Some(ext) = filename.extension() {
-        match ext.to_str().unwrap() {
-            "xml" => xml::read_file(filename),
-            "json" => json::read_file(filename),
-            "yaml" => yaml::read_file(filename),
-            "toml" => toml::read_file(filename),
-            e => Err(format!("unknown file extension {}", e).into()),
-        }
-    } else {
-        Err("no file extension found".into())
+    let content = fs::read(filename)?;
+    read_bytes(&content, Format::for_file(filename, &content)?)
+}
+
+/// read a file in the given `Format`, whatever its extension
+pub fn read_file_as(filename: &Path, format: Format) -> Result<RecordSet> {
+    read_bytes(&fs::read(filename)?, format)
+}
+
+/// read from a reader, e.g. stdin or a byte slice, in the given `Format` or in the format
+/// detected from the content
+pub fn read_from<R>(mut reader: R, format: Option<Format>) -> Result<RecordSet>
+    where R: Read
+{
+    let mut content = vec![];
+    reader.read_to_end(&mut content)?;
+    let format = detect(&content, format)?;
+    read_bytes(&content, format)
+}
+
+fn read_bytes(reader: &[u8], format: Format) -> Result<RecordSet> {
+    match format {
+        Format::Xml => xml::read(reader),
+        Format::Json => json::read(reader),
+        Format::BeerJson => beerjson::read(reader),
+        Format::Brewfather => brewfather::read(reader),
+        Format::Bsmx => bsmx::read(reader),
+        Format::Yaml => yaml::read(reader),
+        Format::Toml => toml::read(reader),
     }
 }
 
-/// write a `beerXML`, json, yaml or toml file
+/// write a `beerXML`, json, BeerJSON (`.beerjson`), yaml or toml file
 pub fn write_file(filename: &Path, set: &RecordSet) -> Result<()> {
-    if let Some(ext) = filename.extension() {
-        match ext.to_str().unwrap() {
-            "xml" => xml::write_file(filename, set),
-            "json" => json::write_file(filename, set),
-            "yaml" => yaml::write_file(filename, set),
-            "toml" => toml::write_file(filename, set),
-            e => Err(format!("unknown file extension {}", e).into()),
-        }
-    } else {
-        Err("no file extension found".into())
+    write_file_with(filename, set, &WriteOptions::default())
+}
+
+/// write a `beerXML`, json, BeerJSON (`.beerjson`), yaml or toml file using the given `WriteOptions`
+pub fn write_file_with(filename: &Path, set: &RecordSet, options: &WriteOptions) -> Result<()> {
+    write_file_as(filename, set, Format::from_path(filename)?, options)
+}
+
+/// write a file in the given `Format` using the given `WriteOptions`, whatever its extension
+pub fn write_file_as(filename: &Path, set: &RecordSet, format: Format, options: &WriteOptions) -> Result<()> {
+    check_writable(format)?;
+    let mut f = File::create(filename)?;
+    write_to(&mut f, set, format, options)
+}
+
+/// write to a writer, e.g. stdout, in the given `Format` using the given `WriteOptions`
+pub fn write_to<W>(writer: &mut W, set: &RecordSet, format: Format, options: &WriteOptions) -> Result<()>
+    where W: Write
+{
+    match format {
+        Format::Xml => xml::write_with(writer, set, options),
+        Format::Json => json::write_with(writer, set, options),
+        Format::BeerJson => beerjson::write_with(writer, set, options),
+        Format::Brewfather => brewfather::write_with(writer, set, options),
+        Format::Bsmx => check_writable(format),
+        Format::Yaml => yaml::write_with(writer, set, options),
+        Format::Toml => toml::write_with(writer, set, options),
+    }
+}
+
+/// read a `beerXML`, json, BeerJSON (`.beerjson`), Brewfather, BeerSmith (`.bsmx`), yaml or toml file
+/// that may hold several record kinds; the format is found as for `read_file`
+pub fn read_document_file(filename: &Path) -> Result<Document> {
+    let content = fs::read(filename)?;
+    read_document_bytes(&content, Format::for_file(filename, &content)?)
+}
+
+/// read a file in the given `Format` that may hold several record kinds, whatever its extension
+pub fn read_document_file_as(filename: &Path, format: Format) -> Result<Document> {
+    read_document_bytes(&fs::read(filename)?, format)
+}
+
+/// read a `Document` from a reader in the given `Format` or in the format detected from the content
+pub fn read_document_from<R>(mut reader: R, format: Option<Format>) -> Result<Document>
+    where R: Read
+{
+    let mut content = vec![];
+    reader.read_to_end(&mut content)?;
+    let format = detect(&content, format)?;
+    read_document_bytes(&content, format)
+}
+
+fn read_document_bytes(reader: &[u8], format: Format) -> Result<Document> {
+    match format {
+        Format::Xml => xml::read_document(reader),
+        Format::Json => json::read_document(reader),
+        Format::BeerJson => beerjson::read_document(reader),
+        Format::Brewfather => brewfather::read_document(reader),
+        Format::Bsmx => bsmx::read_document(reader),
+        Format::Yaml => yaml::read_document(reader),
+        Format::Toml => toml::read_document(reader),
+    }
+}
+
+/// write a `Document` as a `beerXML`, json, BeerJSON (`.beerjson`), yaml or toml file
+pub fn write_document_file(filename: &Path, doc: &Document, options: &WriteOptions) -> Result<()> {
+    write_document_file_as(filename, doc, Format::from_path(filename)?, options)
+}
+
+/// write a `Document` in the given `Format`, whatever the extension of the file
+pub fn write_document_file_as(filename: &Path, doc: &Document, format: Format, options: &WriteOptions) -> Result<()> {
+    check_writable(format)?;
+    let mut f = File::create(filename)?;
+    write_document_to(&mut f, doc, format, options)
+}
+
+/// write a `Document` to a writer in the given `Format` using the given `WriteOptions`
+pub fn write_document_to<W>(writer: &mut W, doc: &Document, format: Format, options: &WriteOptions) -> Result<()>
+    where W: Write
+{
+    match format {
+        Format::Xml => xml::write_document(writer, doc, options),
+        Format::Json => json::write_document(writer, doc, options),
+        Format::BeerJson => beerjson::write_document(writer, doc, options),
+        Format::Brewfather => brewfather::write_document(writer, doc, options),
+        Format::Bsmx => check_writable(format),
+        Format::Yaml => yaml::write_document(writer, doc, options),
+        Format::Toml => toml::write_document(writer, doc, options),
+    }
+}
+
+fn check_writable(format: Format) -> Result<()> {
+    match format {
+        Format::Bsmx => Err("writing BeerSmith files is not supported".into()),
+        _ => Ok(()),
+    }
+}
+
+/// the given format, or the one detected from the content
+fn detect(content: &[u8], format: Option<Format>) -> Result<Format> {
+    match format {
+        Some(format) => Ok(format),
+        None => Format::detect(content).ok_or_else(|| "could not detect the file format".into()),
     }
 }
 
 /// data structures
 pub mod data;
+/// showing quantities in preferred units and generating the display fields
+pub mod display;
 /// error handling
 pub mod error;
+/// file format selection
+pub mod format;
+/// reading and writing options
+pub mod options;
+/// filling in and checking values against their display fields
+pub mod reconcile;
+/// what the reader had to work around
+pub mod report;
+/// brewing units and quantities
+pub mod units;
+/// checks against the BeerXML 1.0 specification
+pub mod validate;
 /// xml parsing and generating
 pub mod xml;
 /// json parsing and generating
 pub mod json;
+/// BeerJSON 1.0 parsing and generating
+pub mod beerjson;
+/// BeerSmith (`.bsmx`) import
+pub mod bsmx;
+/// Brewfather recipe and batch json import and export
+pub mod brewfather;
 /// toml parsing and generating
 pub mod toml;
 /// yaml parsing and generating
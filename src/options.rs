@@ -0,0 +1,146 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::borrow::Cow;
+
+use data::{Document, RecordSet};
+use display;
+use units::System;
+
+/// order in which named records are written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+    /// the order in which the records were read or added
+    Input,
+    /// sorted by record name
+    Name,
+}
+
+/// options controlling how a `RecordSet` is written
+///
+/// Ingredient lists inside a recipe always keep their own order, `order` only
+/// applies to the named record collections.
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// write the optional display and estimated fields (BeerXML Appendix A); default `true`
+    pub display: bool,
+    /// generate the display fields from the values in the units of this system, replacing
+    /// the ones read, instead of writing them as they are; default `None`
+    pub units: Option<System>,
+    /// order of the named records; default `Order::Input`
+    pub order: Order,
+    /// XML only: number of spaces per nesting level, `0` writes everything on one line; default `2`
+    pub indent: usize,
+    /// XML only: write multiline text as a CDATA section instead of escaping it; default `false`
+    pub cdata: bool,
+    /// XML only: encoding to declare and write the output in, `None` omits the declaration
+    /// and writes UTF-8; encodings that are not ASCII compatible, like UTF-16, can not be
+    /// written; default `Some("UTF-8")`
+    pub encoding: Option<String>,
+}
+
+impl Default for WriteOptions {
+    fn default() -> WriteOptions {
+        WriteOptions {
+            display: true,
+            units: None,
+            order: Order::Input,
+            indent: 2,
+            cdata: false,
+            encoding: Some("UTF-8".into()),
+        }
+    }
+}
+
+impl WriteOptions {
+    /// apply the options to a `RecordSet`, only cloning it when something needs to change
+    pub fn prepare_set<'a>(&self, set: &'a RecordSet) -> Cow<'a, RecordSet> {
+        if self.display && self.units.is_none() && self.order == Order::Input {
+            return Cow::Borrowed(set);
+        }
+        let mut set = set.clone();
+        if !self.display {
+            set.strip_display();
+        } else if let Some(system) = self.units {
+            display::generate(&mut set, system);
+        }
+        if self.order == Order::Name {
+            set.sort_by_name();
+        }
+        Cow::Owned(set)
+    }
+
+    /// apply the options to a `Document`, only cloning it when something needs to change
+    pub fn prepare_document<'a>(&self, doc: &'a Document) -> Cow<'a, Document> {
+        if self.display && self.units.is_none() && self.order == Order::Input {
+            return Cow::Borrowed(doc);
+        }
+        let mut doc = doc.clone();
+        if !self.display {
+            doc.strip_display();
+        } else if let Some(system) = self.units {
+            display::generate_document(&mut doc, system);
+        }
+        if self.order == Order::Name {
+            doc.sort_by_name();
+        }
+        Cow::Owned(doc)
+    }
+}
+
+/// the ways a number may be written besides the plain BeerXML `1234.5`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberOptions {
+    /// a decimal comma, optionally with dots grouping the thousands, as in `1.234,5`;
+    /// default `true`
+    pub decimal_comma: bool,
+    /// a trailing percent sign, as in `5.5 %`; default `true`
+    pub percent: bool,
+    /// whitespace around the number; default `true`
+    pub whitespace: bool,
+}
+
+impl Default for NumberOptions {
+    fn default() -> NumberOptions {
+        NumberOptions {
+            decimal_comma: true,
+            percent: true,
+            whitespace: true,
+        }
+    }
+}
+
+/// options controlling how input is read
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// XML only: encoding used when the input is not valid UTF-8 and does not declare
+    /// another encoding, e.g. `ISO-8859-1`; default `windows-1252`
+    pub legacy_encoding: String,
+    /// XML only: fail on unknown elements, missing required fields and optional values
+    /// that do not parse, instead of skipping them; default `false`
+    pub strict: bool,
+    /// XML only: read required values that do not parse, like `<ALPHA>high</ALPHA>`, as
+    /// their default and report them as coerced, instead of failing; default `false`
+    pub lenient_values: bool,
+    /// XML only: match enumerated values like `USE` or `FORM` ignoring case, spaces and
+    /// dashes, and accept common aliases like `FWH`, instead of the exact BeerXML
+    /// spelling; default `true`
+    pub lenient_enums: bool,
+    /// XML only: keep enumerated values that are not known as they were read, so they are
+    /// written back out unchanged, instead of failing; default `true`
+    pub unknown_enums: bool,
+    /// XML only: other ways numbers may be written, each one read is reported as coerced
+    pub numbers: NumberOptions,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            legacy_encoding: "windows-1252".into(),
+            strict: false,
+            lenient_values: false,
+            lenient_enums: true,
+            unknown_enums: true,
+            numbers: NumberOptions::default(),
+        }
+    }
+}
@@ -0,0 +1,464 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! filling in and checking values against their display fields (BeerXML Appendix A)
+//!
+//! A display field like `DISPLAY_AMOUNT` holds the same value as `AMOUNT`, in the units
+//! of the program that wrote it, e.g. `5.00 oz` for `0.1417`. Programs that only fill in
+//! the display fields, or that let the two drift apart, are found and fixed here.
+//!
+//! A required value of `0` counts as missing, as that is what a lenient read leaves
+//! behind for a missing field. Display strings that do not parse as a quantity, or that
+//! measure something else, e.g. `1 pkg` of yeast, are skipped.
+
+use std::fmt;
+use std::mem;
+
+use data::*;
+use error::Location;
+use units::{Dimension, Quantity};
+
+/// what to do with the values the display fields say something about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// only report missing and inconsistent values
+    Report,
+    /// fill in missing values from the display fields, report inconsistent ones
+    Fill,
+    /// fill in missing values and replace inconsistent ones with the display fields
+    Replace,
+}
+
+/// how a value and its display field disagree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingKind {
+    /// the value is missing
+    Missing,
+    /// the value differs from the display field by more than its rounding
+    Inconsistent,
+}
+
+/// a value that does not agree with its display field
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// how they disagree
+    pub kind: FindingKind,
+    /// the record and the field of the value
+    pub location: Location,
+    /// the value before reconciling, in the BeerXML unit
+    pub value: Option<f64>,
+    /// the display field as written
+    pub display: String,
+    /// the quantity of the display field
+    pub quantity: Quantity,
+    /// `true` if the value was set from the display field
+    pub changed: bool,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let base = self.quantity.dimension().base();
+        let display = Quantity::new(self.quantity.to_base(), base);
+        match self.value {
+            Some(value) => {
+                write!(f,
+                       "{}: {:.4} does not match `{}`, which is {:.4}",
+                       self.location,
+                       Quantity::new(value, base),
+                       self.display,
+                       display)?
+            }
+            None => write!(f, "{}: missing, `{}` is {:.4}", self.location, self.display, display)?,
+        }
+        match (self.changed, self.kind) {
+            (false, _) => Ok(()),
+            (true, FindingKind::Missing) => f.write_str(", filled in"),
+            (true, FindingKind::Inconsistent) => f.write_str(", replaced"),
+        }
+    }
+}
+
+/// `true` if `value` in the base unit rounds to the display field, which is written with
+/// `decimals` digits after the decimal point
+fn agrees(value: f64, quantity: &Quantity, decimals: i32) -> bool {
+    let shown = quantity.unit.from_base(value);
+    (shown - quantity.value).abs() <= 10f64.powi(-decimals) + 1e-9
+}
+
+/// digits after the decimal point of the number a display field starts with
+fn decimals(display: &str) -> i32 {
+    let number = display.trim()
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .next()
+        .unwrap_or("");
+    number.rfind(['.', ',']).map_or(0, |p| (number.len() - p - 1) as i32)
+}
+
+/// collects findings while walking the records
+struct Reconciler {
+    policy: Policy,
+    records: Vec<(&'static str, String)>,
+    findings: Vec<Finding>,
+}
+
+impl Reconciler {
+    fn record<F>(&mut self, kind: &'static str, name: &str, check: F)
+        where F: FnOnce(&mut Reconciler)
+    {
+        self.records.push((kind, name.into()));
+        check(self);
+        self.records.pop();
+    }
+
+    fn location(&self, field: &str) -> Location {
+        let path: Vec<String> = self.records
+            .iter()
+            .map(|&(kind, ref name)| if name.is_empty() {
+                kind.to_string()
+            } else {
+                format!("{} `{}`", kind, name)
+            })
+            .collect();
+        let (kind, name) = self.records.last().cloned().unwrap_or_default();
+        Location {
+            path: path.join(" / "),
+            kind: kind.into(),
+            name,
+            field: field.into(),
+            ..Location::default()
+        }
+    }
+
+    /// reconcile an optional value with its display field
+    fn value_o<T>(&mut self,
+                  field: &str,
+                  display: Option<&String>,
+                  dimension: Dimension,
+                  value: &mut Option<T>)
+        where T: Copy + From<f64> + Into<f64>
+    {
+        let display = match display {
+            Some(display) => display,
+            None => return,
+        };
+        let quantity = match display.parse::<Quantity>() {
+            Ok(ref q) if q.dimension() == dimension => *q,
+            _ => return,
+        };
+        let kind = match value.map(Into::into) {
+            None => FindingKind::Missing,
+            Some(v) if !agrees(v, &quantity, decimals(display)) => FindingKind::Inconsistent,
+            Some(_) => return,
+        };
+        let changed = match kind {
+            FindingKind::Missing => self.policy != Policy::Report,
+            FindingKind::Inconsistent => self.policy == Policy::Replace,
+        };
+        self.findings.push(Finding {
+            kind,
+            location: self.location(field),
+            value: value.map(Into::into),
+            display: display.clone(),
+            quantity,
+            changed,
+        });
+        if changed {
+            *value = Some(T::from(quantity.to_base()));
+        }
+    }
+
+    /// reconcile a required value with its display field; `0` counts as missing, unless the
+    /// display field is zero as well, like the `TIME` of a flameout hop
+    fn value<T>(&mut self,
+                field: &str,
+                display: Option<&String>,
+                dimension: Dimension,
+                value: &mut T)
+        where T: Copy + From<f64> + Into<f64>
+    {
+        let shown = display.and_then(|d| d.parse::<Quantity>().ok()).map(|q| q.to_base());
+        let missing = (*value).into() == 0.0 && shown.unwrap_or_default() != 0.0;
+        let mut v = if missing { None } else { Some(*value) };
+        self.value_o(field, display, dimension, &mut v);
+        if let Some(v) = v {
+            *value = v;
+        }
+    }
+
+    fn hop(&mut self, h: &mut Hop) {
+        let name = h.name.clone();
+        self.record("HOP", &name, |r| if let Some(ref d) = h.display {
+            r.value("AMOUNT", d.display_amount.as_ref(), Dimension::Mass, &mut h.amount);
+            r.value("TIME", d.display_time.as_ref(), Dimension::Time, &mut h.time);
+        })
+    }
+
+    fn fermentable(&mut self, f: &mut Fermentable) {
+        let name = f.name.clone();
+        self.record("FERMENTABLE", &name, |r| if let Some(ref d) = f.display {
+            r.value("AMOUNT", d.display_amount.as_ref(), Dimension::Mass, &mut f.amount);
+            r.value("COLOR", d.display_color.as_ref(), Dimension::Color, &mut f.color);
+        })
+    }
+
+    fn yeast(&mut self, y: &mut Yeast) {
+        let name = y.name.clone();
+        let amount = if y.amount_is_weight { Dimension::Mass } else { Dimension::Volume };
+        self.record("YEAST", &name, |r| if let Some(ref d) = y.display {
+            r.value("AMOUNT", d.display_amount.as_ref(), amount, &mut y.amount);
+            r.value_o("MIN_TEMPERATURE",
+                      d.display_min_temp.as_ref(),
+                      Dimension::Temperature,
+                      &mut y.min_temperature);
+            r.value_o("MAX_TEMPERATURE",
+                      d.display_max_temp.as_ref(),
+                      Dimension::Temperature,
+                      &mut y.max_temperature);
+        })
+    }
+
+    fn misc(&mut self, m: &mut Misc) {
+        let name = m.name.clone();
+        let amount = if m.amount_is_weight { Dimension::Mass } else { Dimension::Volume };
+        self.record("MISC", &name, |r| if let Some(ref d) = m.display {
+            r.value("AMOUNT", d.display_amount.as_ref(), amount, &mut m.amount);
+            r.value("TIME", d.display_time.as_ref(), Dimension::Time, &mut m.time);
+        })
+    }
+
+    fn water(&mut self, w: &mut Water) {
+        let name = w.name.clone();
+        self.record("WATER", &name, |r| if let Some(ref d) = w.display {
+            r.value("AMOUNT", d.display_amount.as_ref(), Dimension::Volume, &mut w.amount);
+        })
+    }
+
+    fn style(&mut self, s: &mut Style) {
+        let name = s.name.clone();
+        self.record("STYLE", &name, |r| if let Some(ref d) = s.display {
+            r.value("OG_MIN", d.display_og_min.as_ref(), Dimension::Gravity, &mut s.og_min);
+            r.value("OG_MAX", d.display_og_max.as_ref(), Dimension::Gravity, &mut s.og_max);
+            r.value("FG_MIN", d.display_fg_min.as_ref(), Dimension::Gravity, &mut s.fg_min);
+            r.value("FG_MAX", d.display_fg_max.as_ref(), Dimension::Gravity, &mut s.fg_max);
+            r.value("COLOR_MIN", d.display_color_min.as_ref(), Dimension::Color, &mut s.color_min);
+            r.value("COLOR_MAX", d.display_color_max.as_ref(), Dimension::Color, &mut s.color_max);
+        })
+    }
+
+    fn mash_step(&mut self, s: &mut MashStep) {
+        let name = s.name.clone();
+        self.record("MASH_STEP", &name, |r| if let Some(ref d) = s.display {
+            r.value("STEP_TEMP",
+                    d.display_step_temp.as_ref(),
+                    Dimension::Temperature,
+                    &mut s.step_temp);
+            r.value_o("INFUSE_AMOUNT",
+                      d.display_infuse_amt.as_ref(),
+                      Dimension::Volume,
+                      &mut s.infuse_amount);
+        })
+    }
+
+    fn mash(&mut self, m: &mut Mash) {
+        let name = m.name.clone();
+        self.record("MASH", &name, |r| {
+            if let Some(ref d) = m.display {
+                r.value("GRAIN_TEMP",
+                        d.display_grain_temp.as_ref(),
+                        Dimension::Temperature,
+                        &mut m.grain_temp);
+                r.value_o("TUN_TEMP",
+                          d.display_tun_temp.as_ref(),
+                          Dimension::Temperature,
+                          &mut m.tun_temp);
+                r.value_o("SPARGE_TEMP",
+                          d.display_sparge_temp.as_ref(),
+                          Dimension::Temperature,
+                          &mut m.sparge_temp);
+                r.value_o("TUN_WEIGHT",
+                          d.display_tun_weight.as_ref(),
+                          Dimension::Mass,
+                          &mut m.tun_weight);
+            }
+            for s in &mut m.mash_steps {
+                r.mash_step(s);
+            }
+        })
+    }
+
+    fn equipment(&mut self, e: &mut Equipment) {
+        let name = e.name.clone();
+        self.record("EQUIPMENT", &name, |r| if let Some(ref d) = e.display {
+            let volume = Dimension::Volume;
+            r.value("BOIL_SIZE", d.display_boil_size.as_ref(), volume, &mut e.boil_size);
+            r.value("BATCH_SIZE", d.display_batch_size.as_ref(), volume, &mut e.batch_size);
+            r.value_o("TUN_VOLUME", d.display_tun_volume.as_ref(), volume, &mut e.tun_volume);
+            r.value_o("TUN_WEIGHT",
+                      d.display_tun_weight.as_ref(),
+                      Dimension::Mass,
+                      &mut e.tun_weight);
+            r.value_o("TOP_UP_WATER",
+                      d.display_top_up_water.as_ref(),
+                      volume,
+                      &mut e.top_up_water);
+            r.value_o("TRUB_CHILLER_LOSS",
+                      d.display_trub_chiller_loss.as_ref(),
+                      volume,
+                      &mut e.trub_chiller_loss);
+            r.value_o("LAUTER_DEADSPACE",
+                      d.display_lauter_deadspace.as_ref(),
+                      volume,
+                      &mut e.lauter_deadspace);
+            r.value_o("TOP_UP_KETTLE",
+                      d.display_top_up_kettle.as_ref(),
+                      volume,
+                      &mut e.top_up_kettle);
+        })
+    }
+
+    fn recipe(&mut self, x: &mut Recipe) {
+        let name = x.name.clone();
+        self.record("RECIPE", &name, |r| {
+            if let Some(ref d) = x.display {
+                let temperature = Dimension::Temperature;
+                r.value("BATCH_SIZE",
+                        d.display_batch_size.as_ref(),
+                        Dimension::Volume,
+                        &mut x.batch_size);
+                r.value("BOIL_SIZE",
+                        d.display_boil_size.as_ref(),
+                        Dimension::Volume,
+                        &mut x.boil_size);
+                r.value_o("OG", d.display_og.as_ref(), Dimension::Gravity, &mut x.og);
+                r.value_o("FG", d.display_fg.as_ref(), Dimension::Gravity, &mut x.fg);
+                r.value_o("PRIMARY_TEMP",
+                          d.display_primary_temp.as_ref(),
+                          temperature,
+                          &mut x.primary_temp);
+                r.value_o("SECONDARY_TEMP",
+                          d.display_secondary_temp.as_ref(),
+                          temperature,
+                          &mut x.secondary_temp);
+                r.value_o("TERTIARY_TEMP",
+                          d.display_tertiary_temp.as_ref(),
+                          temperature,
+                          &mut x.tertiary_temp);
+                r.value_o("AGE_TEMP", d.display_age_temp.as_ref(), temperature, &mut x.age_temp);
+                r.value_o("CARBONATION_TEMP",
+                          d.display_carb_temp.as_ref(),
+                          temperature,
+                          &mut x.carbonation_temp);
+            }
+            r.style(&mut x.style);
+            if let Some(ref mut e) = x.equipment {
+                r.equipment(e);
+            }
+            if let Some(ref mut m) = x.mash {
+                r.mash(m);
+            }
+            for h in &mut x.hops {
+                r.hop(h);
+            }
+            for f in &mut x.fermentables {
+                r.fermentable(f);
+            }
+            for m in &mut x.miscs {
+                r.misc(m);
+            }
+            for y in &mut x.yeasts {
+                r.yeast(y);
+            }
+            for w in &mut x.waters {
+                r.water(w);
+            }
+        })
+    }
+}
+
+/// reconcile all records of a `Document` with their display fields
+pub fn reconcile_document(doc: &mut Document, policy: Policy) -> Vec<Finding> {
+    let mut r = Reconciler {
+        policy,
+        records: vec![],
+        findings: vec![],
+    };
+    for (_, x) in doc.equipments.iter_mut() {
+        r.equipment(x);
+    }
+    for (_, x) in doc.fermentables.iter_mut() {
+        r.fermentable(x);
+    }
+    for (_, x) in doc.hops.iter_mut() {
+        r.hop(x);
+    }
+    for (_, x) in doc.miscs.iter_mut() {
+        r.misc(x);
+    }
+    for (_, x) in doc.recipes.iter_mut() {
+        r.recipe(x);
+    }
+    for (_, x) in doc.waters.iter_mut() {
+        r.water(x);
+    }
+    for (_, x) in doc.yeasts.iter_mut() {
+        r.yeast(x);
+    }
+    for (_, x) in doc.styles.iter_mut() {
+        r.style(x);
+    }
+    for (_, x) in doc.mashs.iter_mut() {
+        r.mash(x);
+    }
+    r.findings
+}
+
+/// reconcile all records of a `RecordSet` with their display fields
+pub fn reconcile(set: &mut RecordSet, policy: Policy) -> Vec<Finding> {
+    let mut doc = Document::from(mem::replace(set, RecordSet::Empty));
+    let findings = reconcile_document(&mut doc, policy);
+    // a document made from a single record set converts back to one
+    *set = doc.into_record_set().unwrap();
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use units::{Mass, Temperature, Time};
+    use xml;
+
+    #[test]
+    fn fill_and_replace() {
+        let xml = "<HOPS><HOP><NAME>Saaz</NAME><ALPHA>3.5</ALPHA><AMOUNT>0.1</AMOUNT>\
+                   <TIME>60</TIME><DISPLAY_AMOUNT>5.00 oz</DISPLAY_AMOUNT>\
+                   <DISPLAY_TIME>60 min</DISPLAY_TIME></HOP>\
+                   <HOP><NAME>Citra</NAME><ALPHA>12</ALPHA><AMOUNT>0.05</AMOUNT><TIME>0</TIME>\
+                   <DISPLAY_AMOUNT>50.0 g</DISPLAY_AMOUNT><DISPLAY_TIME>0 min</DISPLAY_TIME>\
+                   </HOP></HOPS>\
+                   <YEASTS><YEAST><NAME>US-05</NAME><AMOUNT_IS_WEIGHT>TRUE</AMOUNT_IS_WEIGHT>\
+                   <DISPLAY_AMOUNT>1 pkg</DISPLAY_AMOUNT><DISP_MIN_TEMP>59.0 F</DISP_MIN_TEMP>\
+                   </YEAST></YEASTS>";
+        let (doc, _) = xml::read_document_with(xml.as_bytes(), &Default::default()).unwrap();
+
+        let mut report = doc.clone();
+        let findings = reconcile_document(&mut report, Policy::Report);
+        assert_eq!(findings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                   vec!["HOP `Saaz`, field AMOUNT: 0.1000 kg does not match `5.00 oz`, which is \
+                         0.1417 kg",
+                        "YEAST `US-05`, field MIN_TEMPERATURE: missing, `59.0 F` is 15.0000 °C"]);
+        assert_eq!(report.hops["Saaz"].amount, Mass(0.1));
+
+        let mut fill = doc.clone();
+        let findings = reconcile_document(&mut fill, Policy::Fill);
+        assert_eq!(fill.hops["Saaz"].amount, Mass(0.1));
+        assert_eq!(fill.yeasts["US-05"].min_temperature, Some(Temperature(15.0)));
+        // a flameout hop is at 0 minutes on purpose, so it is neither missing nor changed
+        assert_eq!(fill.hops["Citra"].time, Time(0.0));
+        assert!(findings.iter().all(|f| f.location.name != "Citra"));
+
+        let mut replace = doc.clone();
+        let findings = reconcile_document(&mut replace, Policy::Replace);
+        assert!(findings.iter().all(|f| f.changed));
+        assert!((replace.hops["Saaz"].amount.ounces() - 5.0).abs() < 1e-9);
+        assert!(reconcile_document(&mut replace, Policy::Report).is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::fmt;
+
+use error::Location;
+
+/// what the reader did with a part of the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// an element the reader does not know was skipped
+    Ignored,
+    /// a value that could not be read as is was changed or dropped
+    Coerced,
+    /// a required field was missing and got its default value
+    Defaulted,
+    /// a record with the same name as an earlier one replaced it
+    Duplicate,
+}
+
+/// a single thing the reader had to work around
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// what happened
+    pub kind: IssueKind,
+    /// where it happened
+    pub location: Location,
+    /// a human readable description
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// everything the reader had to work around while reading a document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseReport {
+    /// name of the encoding the input was read in
+    pub encoding: String,
+    /// the issues, in input order
+    pub issues: Vec<Issue>,
+}
+
+impl ParseReport {
+    /// `true` if the input was read without working around anything
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// the issues of one kind
+    pub fn of_kind(&self, kind: IssueKind) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(move |i| i.kind == kind)
+    }
+
+    /// add an issue
+    pub fn push(&mut self, kind: IssueKind, location: Location, message: String) {
+        self.issues.push(Issue {
+            kind,
+            location,
+            message,
+        });
+    }
+}
@@ -1,52 +1,94 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::io::Read;
+use std::io::{BufRead, Read};
 use std::fs::File;
 use std::path::Path;
-use std::collections::HashMap;
 
+use serde::de::DeserializeOwned;
 use serde_toml;
+use serde_toml::Value;
 
 use data::*;
 use error::*;
 
+/// a top-level entry: the `document = "Kind"` marker of older files, or a table of records
 #[derive(Deserialize)]
-struct PrivateDocument {
-    document: String,
-}
-
-fn read_str(s: &str) -> Result<RecordSet> {
-    let pd: PrivateDocument = serde_toml::from_str(s)?;
-    let document = pd.document.as_str();
-    let marker = format!("document = \"{}\"", document);
-    let s2 = s.replacen(&marker, "", 1);
-    match document {
-        "Fermentables" => {
-            let h: HashMap<String, Fermentable> = serde_toml::from_str(&s2)?;
-            Ok(RecordSet::Fermentables(h))
-        }
-        "Hops" => {
-            let h: HashMap<String, Hop> = serde_toml::from_str(&s2)?;
-            Ok(RecordSet::Hops(h))
-        }
-        "Miscs" => {
-            let h: HashMap<String, Misc> = serde_toml::from_str(&s2)?;
-            Ok(RecordSet::Miscs(h))
-        }
-        "Waters" => {
-            let h: HashMap<String, Water> = serde_toml::from_str(&s2)?;
-            Ok(RecordSet::Waters(h))
-        }
-        "Yeasts" => {
-            let h: HashMap<String, Yeast> = serde_toml::from_str(&s2)?;
-            Ok(RecordSet::Yeasts(h))
+#[serde(untagged)]
+enum Entry {
+    Kind(String),
+    Records(RecordMap<Value>),
+}
+
+/// the top-level tables of a `Document`
+const KINDS: &[&str] = &["Equipments", "Fermentables", "Hops", "Miscs", "Recipes", "Waters",
+                         "Yeasts", "Styles", "Mashs"];
+
+fn records<T>(records: RecordMap<Value>) -> Result<RecordMap<T>>
+    where T: DeserializeOwned + Named
+{
+    let mut res = RecordMap::new();
+    for (name, v) in records {
+        let mut x: T = v.try_into()?;
+        x.set_name(name.clone());
+        res.insert(name, x);
+    }
+    Ok(res)
+}
+
+/// try to read a `Document` from a string that contains toml
+pub fn read_document_str(s: &str) -> Result<Document> {
+    let mut top: RecordMap<Entry> = serde_toml::from_str(s)?;
+    // older files carry a `document = "Kind"` key with the records at the top level
+    if let Some(Entry::Kind(kind)) = top.remove("document") {
+        let mut r = RecordMap::new();
+        for (name, entry) in top {
+            match entry {
+                Entry::Records(fields) => {
+                    r.insert(name, Value::Table(fields.into_iter().collect()))
+                }
+                Entry::Kind(_) => return Err(format!("Toml: unexpected value for {}", name).into()),
+            };
         }
-        "Recipes" => {
-            let h: HashMap<String, Recipe> = serde_toml::from_str(&s2)?;
-            Ok(RecordSet::Recipes(h))
+        top = RecordMap::new();
+        top.insert(kind, Entry::Records(r));
+    } else if top.keys().all(|k| KINDS.contains(&k.as_str())) {
+        // straight into the records, going through `Value` loses the order of their extensions
+        return Ok(serde_toml::from_str(s)?);
+    }
+    let mut doc = Document::default();
+    for (kind, entry) in top {
+        let r = match entry {
+            Entry::Records(r) => r,
+            Entry::Kind(_) => return Err(format!("Toml: unexpected value for {}", kind).into()),
+        };
+        match kind.as_str() {
+            "Equipments" => doc.equipments = records(r)?,
+            "Fermentables" => doc.fermentables = records(r)?,
+            "Hops" => doc.hops = records(r)?,
+            "Miscs" => doc.miscs = records(r)?,
+            "Recipes" => doc.recipes = records(r)?,
+            "Waters" => doc.waters = records(r)?,
+            "Yeasts" => doc.yeasts = records(r)?,
+            "Styles" => doc.styles = records(r)?,
+            "Mashs" => doc.mashs = records(r)?,
+            e => return Err(format!("Toml: unknown document type {}", e).into()),
         }
-        e => Err(format!("Toml: Unimplemented document type {}", e).into()),
     }
+    Ok(doc)
+}
+
+/// try to read a `RecordSet` from a string that contains toml
+pub fn read_str(s: &str) -> Result<RecordSet> {
+    read_document_str(s)?.into_record_set()
+}
+
+/// try to read a `RecordSet` from a reader that contains toml
+pub fn read<B>(mut reader: B) -> Result<RecordSet>
+    where B: BufRead
+{
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+    read_str(&contents)
 }
 
 /// try to read a `RecordSet` from a toml file
@@ -56,3 +98,62 @@ pub fn read_file(filename: &Path) -> Result<RecordSet> {
     f.read_to_string(&mut contents)?;
     read_str(&contents)
 }
+
+/// try to read a `Document` from a reader that contains toml; a single-kind `RecordSet` is accepted as well
+pub fn read_document<B>(mut reader: B) -> Result<Document>
+    where B: BufRead
+{
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+    read_document_str(&contents)
+}
+
+/// try to read a `Document` from a toml file; a single-kind `RecordSet` file is accepted as well
+pub fn read_document_file(filename: &Path) -> Result<Document> {
+    let mut f = File::open(filename)?;
+    let mut contents = String::new();
+    f.read_to_string(&mut contents)?;
+    read_document_str(&contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json;
+
+    use super::*;
+    use toml;
+    use xml;
+
+    #[test]
+    fn round_trip_data_files() {
+        for entry in fs::read_dir("data").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("xml") {
+                continue;
+            }
+            let set = xml::read_file(&path).unwrap();
+            let mut buf = vec![];
+            toml::write(&mut buf, &set).unwrap();
+            let back = read_str(&String::from_utf8(buf).unwrap()).unwrap();
+            assert_eq!(serde_json::to_string(&back).unwrap(),
+                       serde_json::to_string(&set).unwrap(),
+                       "{}",
+                       path.display());
+        }
+    }
+
+    #[test]
+    fn read_legacy_envelope() {
+        let s = "document = \"Mashs\"\n\n\
+                 [Single]\nversion = 1\ngrain_temp = 20.0\n\n\
+                 [[Single.mash_steps]]\nname = \"Rest\"\nversion = 1\n\
+                 type = \"Infusion\"\nstep_temp = 66.0\nstep_time = 60.0\n";
+        match read_str(s).unwrap() {
+            RecordSet::Mashs(m) => assert_eq!(m["Single"].mash_steps[0].step_temp.0, 66.0),
+            set => panic!("unexpected {:?}", set),
+        }
+        assert!(read_str("document = \"Beers\"\n").is_err());
+    }
+}
@@ -1,63 +1,82 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
+use serde::Serialize;
 use serde_toml;
 
 use data::*;
 use error::*;
+use options::WriteOptions;
 
 /// try to write a `RecordSet` as toml to a writer
 pub fn write<T>(writer: &mut T, set: &RecordSet) -> Result<()>
     where T: Write
 {
-    let s = match *set {
-        RecordSet::Empty => return Ok(()),
-        RecordSet::Equipments(ref v) => {
-            write!(writer, "document = \"Equipments\"\n\n")?;
-            serde_toml::to_string(v)?
-        }
-        RecordSet::Fermentables(ref v) => {
-            write!(writer, "document = \"Fermentables\"\n\n")?;
-            serde_toml::to_string(v)?
-        }
-        RecordSet::Hops(ref v) => {
-            write!(writer, "document = \"Hops\"\n\n")?;
-            serde_toml::to_string(v)?
-        }
-        RecordSet::Yeasts(ref v) => {
-            write!(writer, "document = \"Yeasts\"\n\n")?;
-            serde_toml::to_string(v)?
-        }
-        RecordSet::Miscs(ref v) => {
-            write!(writer, "document = \"Miscs\"\n\n")?;
-            serde_toml::to_string(v)?
-        }
-        RecordSet::Recipes(ref v) => {
-            write!(writer, "document = \"Recipes\"\n\n")?;
-            serde_toml::to_string(v)?
-        }
-        RecordSet::Waters(ref v) => {
-            write!(writer, "document = \"Waters\"\n\n")?;
-            serde_toml::to_string(v)?
-        }
-        RecordSet::Styles(ref v) => {
-            write!(writer, "document = \"Styles\"\n\n")?;
-            serde_toml::to_string(v)?
-        }
-        RecordSet::Mashs(ref v) => {
-            write!(writer, "document = \"Mashs\"\n\n")?;
-            serde_toml::to_string(v)?
-        }
-    };
+    write_with(writer, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` as toml to a writer using the given `WriteOptions`
+pub fn write_with<T>(writer: &mut T, set: &RecordSet, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    write_set(writer, &options.prepare_set(set))
+}
+
+fn write_set<T>(writer: &mut T, set: &RecordSet) -> Result<()>
+    where T: Write
+{
+    match *set {
+        RecordSet::Empty => Ok(()),
+        RecordSet::Equipments(ref v) => write_kind(writer, "Equipments", v),
+        RecordSet::Fermentables(ref v) => write_kind(writer, "Fermentables", v),
+        RecordSet::Hops(ref v) => write_kind(writer, "Hops", v),
+        RecordSet::Yeasts(ref v) => write_kind(writer, "Yeasts", v),
+        RecordSet::Miscs(ref v) => write_kind(writer, "Miscs", v),
+        RecordSet::Recipes(ref v) => write_kind(writer, "Recipes", v),
+        RecordSet::Waters(ref v) => write_kind(writer, "Waters", v),
+        RecordSet::Styles(ref v) => write_kind(writer, "Styles", v),
+        RecordSet::Mashs(ref v) => write_kind(writer, "Mashs", v),
+    }
+}
+
+/// write the records as a `[Kind.name]` table per record, the same layout a `Document` uses
+fn write_kind<T, E>(writer: &mut T, kind: &'static str, v: &RecordMap<E>) -> Result<()>
+    where T: Write,
+          E: Serialize
+{
+    let mut envelope = BTreeMap::new();
+    envelope.insert(kind, v);
+    let s = serde_toml::to_string(&envelope)?;
     write!(writer, "{}", s)?;
     Ok(())
 }
 
 /// try to write a `RecordSet` as toml to a file
 pub fn write_file(filename: &Path, set: &RecordSet) -> Result<()> {
+    write_file_with(filename, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` as toml to a file using the given `WriteOptions`
+pub fn write_file_with(filename: &Path, set: &RecordSet, options: &WriteOptions) -> Result<()> {
+    let mut f = File::create(filename)?;
+    write_with(&mut f, set, options)
+}
+
+/// try to write a `Document` as toml to a writer
+pub fn write_document<T>(writer: &mut T, doc: &Document, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    let s = serde_toml::to_string(&*options.prepare_document(doc))?;
+    write!(writer, "{}", s)?;
+    Ok(())
+}
+
+/// try to write a `Document` as toml to a file
+pub fn write_document_file(filename: &Path, doc: &Document, options: &WriteOptions) -> Result<()> {
     let mut f = File::create(filename)?;
-    write(&mut f, set)
+    write_document(&mut f, doc, options)
 }
@@ -0,0 +1,102 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! conversions between the gravity scales: specific gravity, degrees Plato and Brix
+//!
+//! Degrees Plato are grams of extract per 100 grams of wort, as a hydrometer measures
+//! them. Degrees Brix are the same for sucrose, but a refractometer reads wort somewhat
+//! high, which the wort correction factor makes up for: Plato is Brix divided by it.
+//! The `Brix` unit, and so `Gravity::brix`, use the usual factor `WORT_CORRECTION`; a
+//! factor of `1.0` treats Brix as Plato.
+
+use super::{Gravity, Quantity, Unit};
+use super::system::decimals;
+
+/// the usual wort correction factor of a refractometer
+pub const WORT_CORRECTION: f64 = 1.04;
+
+/// specific gravity of wort with `plato` degrees Plato
+pub fn plato_to_sg(plato: f64) -> f64 {
+    1.0 + plato / (258.6 - plato / 258.2 * 227.1)
+}
+
+/// degrees Plato of wort with specific gravity `sg`
+pub fn sg_to_plato(sg: f64) -> f64 {
+    -616.868 + 1111.14 * sg - 630.272 * sg * sg + 135.997 * sg * sg * sg
+}
+
+/// degrees Plato of wort that reads `brix` degrees Brix with the given wort correction factor
+pub fn brix_to_plato(brix: f64, wort_correction: f64) -> f64 {
+    brix / wort_correction
+}
+
+/// degrees Brix that wort with `plato` degrees Plato reads with the given wort correction factor
+pub fn plato_to_brix(plato: f64, wort_correction: f64) -> f64 {
+    plato * wort_correction
+}
+
+/// specific gravity of wort that reads `brix` degrees Brix with the given wort correction factor
+pub fn brix_to_sg(brix: f64, wort_correction: f64) -> f64 {
+    plato_to_sg(brix_to_plato(brix, wort_correction))
+}
+
+/// degrees Brix that wort with specific gravity `sg` reads with the given wort correction factor
+pub fn sg_to_brix(sg: f64, wort_correction: f64) -> f64 {
+    plato_to_brix(sg_to_plato(sg), wort_correction)
+}
+
+impl Gravity {
+    /// gravity points, e.g. `50` for 1.050
+    pub fn points(self) -> f64 {
+        (self.0 - 1.0) * 1000.0
+    }
+
+    /// the gravity of wort that reads `brix` on a refractometer with the given wort
+    /// correction factor, e.g. `WORT_CORRECTION`
+    pub fn from_refractometer(brix: f64, wort_correction: f64) -> Gravity {
+        Gravity(brix_to_sg(brix, wort_correction))
+    }
+
+    /// what a refractometer with the given wort correction factor reads for this gravity
+    pub fn refractometer(self, wort_correction: f64) -> f64 {
+        sg_to_brix(self.0, wort_correction)
+    }
+
+    /// the gravity in `unit` for display, e.g. `12.0 °P` or `1.048 SG`, or `None` if the
+    /// unit is not a gravity unit
+    pub fn format(self, unit: Unit) -> Option<String> {
+        self.get(unit).map(|value| {
+            let q = Quantity::new(value, unit);
+            format!("{:.*}", decimals(&q), q)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, within: f64) -> bool {
+        (a - b).abs() < within
+    }
+
+    #[test]
+    fn scales() {
+        assert!(close(sg_to_plato(1.048), 11.91, 0.01));
+        assert!(close(plato_to_sg(12.0), 1.0484, 1e-4));
+        assert!(close(sg_to_plato(plato_to_sg(20.0)), 20.0, 0.01));
+        assert!(close(brix_to_plato(12.48, WORT_CORRECTION), 12.0, 0.01));
+        assert!(close(sg_to_brix(1.0484, WORT_CORRECTION), 12.48, 0.01));
+        assert!(close(brix_to_sg(12.0, 1.0), plato_to_sg(12.0), 1e-9));
+
+        let og = Gravity(1.050);
+        assert!(close(og.points(), 50.0, 1e-9));
+        assert!(close(Gravity::from_refractometer(og.refractometer(1.02), 1.02).0, 1.050, 1e-4));
+        assert!(close(og.brix(), og.refractometer(WORT_CORRECTION), 1e-9));
+        assert!(close(Gravity::from_brix(12.48).0, brix_to_sg(12.48, WORT_CORRECTION), 1e-9));
+        assert!(og.brix() > og.plato());
+        assert_eq!(og.format(Unit::Brix).unwrap(), "12.9 °Bx");
+        assert_eq!(og.format(Unit::SpecificGravity).unwrap(), "1.050 SG");
+        assert_eq!(og.format(Unit::Plato).unwrap(), "12.4 °P");
+        assert_eq!(og.format(Unit::Kilogram), None);
+    }
+}
@@ -0,0 +1,178 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::fmt;
+use std::num::ParseFloatError;
+use std::str;
+
+use super::{Dimension, Quantity, Unit};
+
+/// define a newtype over a value in the BeerXML unit of a dimension, with a constructor
+/// and a getter for each of the given units
+macro_rules! measure {
+    ($(#[$attr:meta])* $t:ident, $dimension:ident,
+     [$(($get:ident, $from:ident, $unit:ident)),*]) => {
+        $(#[$attr])*
+        #[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+        pub struct $t(pub f64);
+
+        impl $t {
+            /// what this measures
+            pub const DIMENSION: Dimension = Dimension::$dimension;
+
+            /// a value in `unit`, or `None` if the unit measures something else
+            pub fn new(value: f64, unit: Unit) -> Option<$t> {
+                if unit.dimension() == Dimension::$dimension {
+                    Some($t(unit.to_base(value)))
+                } else {
+                    None
+                }
+            }
+
+            /// the value in `unit`, or `None` if the unit measures something else
+            pub fn get(self, unit: Unit) -> Option<f64> {
+                if unit.dimension() == Dimension::$dimension {
+                    Some(unit.from_base(self.0))
+                } else {
+                    None
+                }
+            }
+
+            /// the value with its unit
+            pub fn quantity(self) -> Quantity {
+                Quantity::base(self.0, Dimension::$dimension)
+            }
+
+            $(
+                #[doc = "the value in "]
+                #[doc = stringify!($get)]
+                pub fn $get(self) -> f64 {
+                    Unit::$unit.from_base(self.0)
+                }
+
+                #[doc = "a value in "]
+                #[doc = stringify!($get)]
+                pub fn $from(value: f64) -> $t {
+                    $t(Unit::$unit.to_base(value))
+                }
+            )*
+        }
+
+        /// the bare value in the BeerXML unit, as it is written in BeerXML
+        impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl str::FromStr for $t {
+            type Err = ParseFloatError;
+
+            fn from_str(s: &str) -> ::std::result::Result<$t, ParseFloatError> {
+                Ok($t(s.parse()?))
+            }
+        }
+
+        impl From<f64> for $t {
+            fn from(value: f64) -> $t {
+                $t(value)
+            }
+        }
+
+        impl From<$t> for f64 {
+            fn from(value: $t) -> f64 {
+                value.0
+            }
+        }
+
+        impl From<$t> for Quantity {
+            fn from(value: $t) -> Quantity {
+                value.quantity()
+            }
+        }
+    };
+}
+
+measure!(
+    /// a mass, in kilograms
+    Mass, Mass,
+    [(kilograms, from_kilograms, Kilogram),
+     (grams, from_grams, Gram),
+     (pounds, from_pounds, Pound),
+     (ounces, from_ounces, Ounce)]);
+
+measure!(
+    /// a volume, in liters
+    Volume, Volume,
+    [(liters, from_liters, Liter),
+     (milliliters, from_milliliters, Milliliter),
+     (gallons, from_gallons, Gallon),
+     (imperial_gallons, from_imperial_gallons, ImperialGallon),
+     (quarts, from_quarts, Quart),
+     (fluid_ounces, from_fluid_ounces, FluidOunce)]);
+
+measure!(
+    /// a temperature, in degrees Celsius
+    Temperature, Temperature,
+    [(celsius, from_celsius, Celsius),
+     (fahrenheit, from_fahrenheit, Fahrenheit),
+     (kelvin, from_kelvin, Kelvin)]);
+
+measure!(
+    /// a duration, in minutes
+    Time, Time,
+    [(minutes, from_minutes, Minute),
+     (seconds, from_seconds, Second),
+     (hours, from_hours, Hour),
+     (days, from_days, Day)]);
+
+measure!(
+    /// the density of wort or beer, as specific gravity
+    Gravity, Gravity,
+    [(sg, from_sg, SpecificGravity),
+     (plato, from_plato, Plato),
+     (brix, from_brix, Brix)]);
+
+measure!(
+    /// the color of wort, beer or a grain, in SRM or the same Lovibond degrees
+    Color, Color,
+    [(srm, from_srm, Srm),
+     (ebc, from_ebc, Ebc),
+     (lovibond, from_lovibond, Lovibond)]);
+
+measure!(
+    /// a percentage
+    Percent, Percent,
+    [(percent, from_percent, Percent)]);
+
+impl Percent {
+    /// the percentage as a fraction, e.g. `0.75` for 75%
+    pub fn fraction(self) -> f64 {
+        self.0 / 100.0
+    }
+
+    /// a percentage from a fraction
+    pub fn from_fraction(fraction: f64) -> Percent {
+        Percent(fraction * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert!((Mass::from_ounces(1.0).grams() - 28.349523125).abs() < 1e-9);
+        assert!((Volume::from_gallons(5.0).liters() - 18.92705892).abs() < 1e-8);
+        assert_eq!(Temperature::from_fahrenheit(212.0), Temperature(100.0));
+        assert_eq!(Time::from_hours(1.5).minutes(), 90.0);
+        assert!((Gravity::from_plato(12.0).sg() - 1.0484).abs() < 1e-4);
+        assert!((Color(10.0).ebc() - 19.7).abs() < 1e-9);
+        assert_eq!(Percent(75.0).fraction(), 0.75);
+        assert_eq!(Mass::new(5.0, Unit::Liter), None);
+        assert_eq!(Volume(20.0).get(Unit::Milliliter), Some(20000.0));
+        assert_eq!(Mass(0.25).to_string(), "0.25");
+        assert_eq!(Mass(0.25).quantity().to_string(), "0.25 kg");
+        assert_eq!("66.5".parse::<Temperature>().unwrap(), Temperature(66.5));
+    }
+}
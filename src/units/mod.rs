@@ -0,0 +1,298 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! brewing units, and quantities as written in the BeerXML display fields
+//!
+//! BeerXML stores every value in one fixed unit per dimension: kilograms, liters, degrees
+//! Celsius, minutes, specific gravity, SRM and percent. Those are the base units here, and
+//! the data model holds its values in the matching newtypes, e.g. `Mass` or `Temperature`.
+//! The readers of other formats convert their units through `Unit` as well.
+//!
+//! Some values are still bare `f64`s on purpose:
+//!
+//! - bitterness in IBU, carbonation in volumes of CO2, ion concentrations in ppm, pH,
+//!   diastatic power and specific heat have no display units in BeerXML, so there is no
+//!   dimension for them here; the BeerJSON reader converts the few other units it allows
+//! - the ages of a recipe are in days while `Time` holds minutes, so a `Time` would change
+//!   the value written to JSON, YAML and TOML
+//! - the amount of a yeast or misc item is a mass or a volume depending on
+//!   `amount_is_weight`; their `mass` and `volume` methods give it typed
+
+use std::fmt;
+use std::str;
+
+use error::*;
+use self::gravity::{brix_to_sg, plato_to_sg, sg_to_brix, sg_to_plato, WORT_CORRECTION};
+
+pub use self::measures::*;
+pub use self::quantity::Quantity;
+pub use self::system::System;
+
+/// what a unit measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    /// mass, in kilograms
+    Mass,
+    /// volume, in liters
+    Volume,
+    /// temperature, in degrees Celsius
+    Temperature,
+    /// duration, in minutes
+    Time,
+    /// density of wort or beer, as specific gravity
+    Gravity,
+    /// color of wort or beer, in SRM
+    Color,
+    /// a percentage
+    Percent,
+}
+
+impl Dimension {
+    /// the unit BeerXML stores this dimension in
+    pub fn base(self) -> Unit {
+        match self {
+            Dimension::Mass => Unit::Kilogram,
+            Dimension::Volume => Unit::Liter,
+            Dimension::Temperature => Unit::Celsius,
+            Dimension::Time => Unit::Minute,
+            Dimension::Gravity => Unit::SpecificGravity,
+            Dimension::Color => Unit::Srm,
+            Dimension::Percent => Unit::Percent,
+        }
+    }
+}
+
+/// a unit a brewing quantity can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// kilogram
+    Kilogram,
+    /// gram
+    Gram,
+    /// milligram
+    Milligram,
+    /// avoirdupois pound
+    Pound,
+    /// avoirdupois ounce
+    Ounce,
+    /// liter
+    Liter,
+    /// milliliter
+    Milliliter,
+    /// hectoliter
+    Hectoliter,
+    /// US gallon
+    Gallon,
+    /// imperial gallon
+    ImperialGallon,
+    /// US quart
+    Quart,
+    /// US pint
+    Pint,
+    /// imperial quart
+    ImperialQuart,
+    /// imperial pint
+    ImperialPint,
+    /// US beer barrel, 31 US gallons
+    Barrel,
+    /// US cup
+    Cup,
+    /// US fluid ounce
+    FluidOunce,
+    /// imperial fluid ounce
+    ImperialFluidOunce,
+    /// US tablespoon
+    Tablespoon,
+    /// US teaspoon
+    Teaspoon,
+    /// degree Celsius
+    Celsius,
+    /// degree Fahrenheit
+    Fahrenheit,
+    /// kelvin
+    Kelvin,
+    /// second
+    Second,
+    /// minute
+    Minute,
+    /// hour
+    Hour,
+    /// day
+    Day,
+    /// week
+    Week,
+    /// specific gravity
+    SpecificGravity,
+    /// degree Plato
+    Plato,
+    /// degree Brix as a refractometer reads it, with the usual wort correction factor
+    /// `gravity::WORT_CORRECTION`; see `gravity` for other factors
+    Brix,
+    /// Standard Reference Method
+    Srm,
+    /// European Brewery Convention, about twice SRM
+    Ebc,
+    /// degree Lovibond, which BeerXML uses interchangeably with SRM
+    Lovibond,
+    /// percent
+    Percent,
+}
+
+/// every unit with the spellings it is read from, lowercase, the first one is written
+const SYMBOLS: &[(Unit, &[&str])] = &[(Unit::Kilogram, &["kg", "kgs", "kilogram", "kilograms"]),
+                                      (Unit::Gram, &["g", "gr", "gram", "grams"]),
+                                      (Unit::Milligram, &["mg", "milligram", "milligrams"]),
+                                      (Unit::Pound, &["lb", "lbs", "pound", "pounds"]),
+                                      (Unit::Ounce, &["oz", "ounce", "ounces"]),
+                                      (Unit::Liter, &["l", "liter", "liters", "litre", "litres"]),
+                                      (Unit::Milliliter, &["ml", "milliliter", "milliliters"]),
+                                      (Unit::Hectoliter, &["hl", "hectoliter", "hectoliters"]),
+                                      (Unit::Gallon, &["gal", "gallon", "gallons"]),
+                                      (Unit::ImperialGallon,
+                                       &["imp gal", "imperial gallon", "igal"]),
+                                      (Unit::Quart, &["qt", "quart", "quarts"]),
+                                      (Unit::Pint, &["pt", "pint", "pints"]),
+                                      (Unit::ImperialQuart, &["imp qt", "imperial quart", "iqt"]),
+                                      (Unit::ImperialPint, &["imp pt", "imperial pint", "ipt"]),
+                                      (Unit::Barrel, &["bbl", "barrel", "barrels"]),
+                                      (Unit::Cup, &["cup", "cups"]),
+                                      (Unit::FluidOunce, &["fl oz", "floz"]),
+                                      (Unit::ImperialFluidOunce, &["imp fl oz", "ifloz"]),
+                                      (Unit::Tablespoon, &["tbsp", "tablespoon", "tablespoons"]),
+                                      (Unit::Teaspoon, &["tsp", "teaspoon", "teaspoons"]),
+                                      (Unit::Celsius, &["°c", "c", "celsius"]),
+                                      (Unit::Fahrenheit, &["°f", "f", "fahrenheit"]),
+                                      (Unit::Kelvin, &["k", "kelvin"]),
+                                      (Unit::Second, &["s", "sec", "secs", "second", "seconds"]),
+                                      (Unit::Minute, &["min", "mins", "minute", "minutes"]),
+                                      (Unit::Hour, &["hr", "hrs", "h", "hour", "hours"]),
+                                      (Unit::Day, &["days", "day", "d"]),
+                                      (Unit::Week, &["weeks", "week", "wk", "wks"]),
+                                      (Unit::SpecificGravity, &["sg"]),
+                                      (Unit::Plato, &["°p", "p", "plato", "°plato"]),
+                                      (Unit::Brix, &["°bx", "bx", "brix", "°brix"]),
+                                      (Unit::Srm, &["srm"]),
+                                      (Unit::Ebc, &["ebc"]),
+                                      (Unit::Lovibond, &["°l", "lovibond", "°lovibond", "lovi"]),
+                                      (Unit::Percent, &["%", "percent"])];
+
+impl Unit {
+    /// what the unit measures
+    pub fn dimension(self) -> Dimension {
+        use self::Unit::*;
+        match self {
+            Kilogram | Gram | Milligram | Pound | Ounce => Dimension::Mass,
+            Liter | Milliliter | Hectoliter | Gallon | ImperialGallon | Quart | Pint |
+            ImperialQuart | ImperialPint | Barrel | Cup | FluidOunce | ImperialFluidOunce |
+            Tablespoon | Teaspoon => Dimension::Volume,
+            Celsius | Fahrenheit | Kelvin => Dimension::Temperature,
+            Second | Minute | Hour | Day | Week => Dimension::Time,
+            SpecificGravity | Plato | Brix => Dimension::Gravity,
+            Srm | Ebc | Lovibond => Dimension::Color,
+            Percent => Dimension::Percent,
+        }
+    }
+
+    /// the usual way to write the unit
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Liter => "L",
+            Unit::Milliliter => "mL",
+            Unit::Hectoliter => "hL",
+            Unit::Celsius => "°C",
+            Unit::Fahrenheit => "°F",
+            Unit::Kelvin => "K",
+            Unit::SpecificGravity => "SG",
+            Unit::Plato => "°P",
+            Unit::Brix => "°Bx",
+            Unit::Srm => "SRM",
+            Unit::Ebc => "EBC",
+            Unit::Lovibond => "°L",
+            unit => SYMBOLS.iter().find(|s| s.0 == unit).unwrap().1[0],
+        }
+    }
+
+    /// factor to the base unit as a ratio, for the units that are a plain multiple of it;
+    /// the divisor keeps e.g. grams exact
+    fn factor(self) -> Option<(f64, f64)> {
+        use self::Unit::*;
+        let factor = match self {
+            Kilogram | Liter | Minute | SpecificGravity | Srm | Lovibond | Percent => (1.0, 1.0),
+            Gram | Milliliter => (1.0, 1000.0),
+            Milligram => (1.0, 1000000.0),
+            Pound => (0.45359237, 1.0),
+            Ounce => (0.028349523125, 1.0),
+            Hectoliter => (100.0, 1.0),
+            Gallon => (3.785411784, 1.0),
+            ImperialGallon => (4.54609, 1.0),
+            Quart => (0.946352946, 1.0),
+            Pint => (0.473176473, 1.0),
+            ImperialQuart => (1.1365225, 1.0),
+            ImperialPint => (0.56826125, 1.0),
+            Barrel => (117.347765304, 1.0),
+            Cup => (0.2365882365, 1.0),
+            FluidOunce => (0.0295735295625, 1.0),
+            ImperialFluidOunce => (0.0284130625, 1.0),
+            Tablespoon => (0.01478676478125, 1.0),
+            Teaspoon => (0.00492892159375, 1.0),
+            Second => (1.0, 60.0),
+            Hour => (60.0, 1.0),
+            Day => (1440.0, 1.0),
+            Week => (10080.0, 1.0),
+            Ebc => (1.0, 1.97),
+            Celsius | Fahrenheit | Kelvin | Plato | Brix => return None,
+        };
+        Some(factor)
+    }
+
+    /// convert `value` in this unit to the base unit of its dimension
+    pub fn to_base(self, value: f64) -> f64 {
+        if let Some((multiplier, divisor)) = self.factor() {
+            return value * multiplier / divisor;
+        }
+        match self {
+            Unit::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            Unit::Kelvin => value - 273.15,
+            Unit::Plato => plato_to_sg(value),
+            Unit::Brix => brix_to_sg(value, WORT_CORRECTION),
+            _ => value,
+        }
+    }
+
+    /// convert `value` in the base unit of its dimension to this unit
+    pub fn from_base(self, value: f64) -> f64 {
+        if let Some((multiplier, divisor)) = self.factor() {
+            return value * divisor / multiplier;
+        }
+        match self {
+            Unit::Fahrenheit => value * 9.0 / 5.0 + 32.0,
+            Unit::Kelvin => value + 273.15,
+            Unit::Plato => sg_to_plato(value),
+            Unit::Brix => sg_to_brix(value, WORT_CORRECTION),
+            _ => value,
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl str::FromStr for Unit {
+    type Err = Error;
+
+    /// read a unit ignoring case, e.g. `oz`, `°F`, `Lbs` or `SG`
+    fn from_str(s: &str) -> Result<Unit> {
+        let symbol = s.trim().replace('º', "°").to_lowercase();
+        SYMBOLS.iter()
+            .find(|&&(_, symbols)| symbols.contains(&symbol.as_str()))
+            .map(|&(unit, _)| unit)
+            .ok_or_else(|| ErrorKind::ParseError("unit".into(), s.into()).into())
+    }
+}
+
+pub mod gravity;
+mod measures;
+mod quantity;
+mod system;
@@ -0,0 +1,122 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::fmt;
+use std::str;
+
+use error::*;
+use super::{Dimension, Unit};
+
+/// a value with its unit, e.g. `5.00 oz` or `1.050 SG`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    /// the value, in `unit`
+    pub value: f64,
+    /// the unit of the value
+    pub unit: Unit,
+}
+
+impl Quantity {
+    /// a new quantity
+    pub fn new(value: f64, unit: Unit) -> Quantity {
+        Quantity { value, unit }
+    }
+
+    /// a quantity in the unit BeerXML stores `dimension` in
+    pub fn base(value: f64, dimension: Dimension) -> Quantity {
+        Quantity::new(value, dimension.base())
+    }
+
+    /// what the quantity measures
+    pub fn dimension(&self) -> Dimension {
+        self.unit.dimension()
+    }
+
+    /// the value in the unit BeerXML stores its dimension in
+    pub fn to_base(&self) -> f64 {
+        self.unit.to_base(self.value)
+    }
+
+    /// the same quantity in another unit, or `None` if it measures something else
+    pub fn to(&self, unit: Unit) -> Option<Quantity> {
+        if unit.dimension() != self.dimension() {
+            return None;
+        }
+        Some(Quantity::new(unit.from_base(self.to_base()), unit))
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self.unit {
+            Unit::Day if self.value == 1.0 => "day",
+            Unit::Week if self.value == 1.0 => "week",
+            unit => unit.symbol(),
+        };
+        match f.precision() {
+            Some(p) => write!(f, "{:.*} {}", p, self.value, symbol),
+            None => write!(f, "{} {}", self.value, symbol),
+        }
+    }
+}
+
+impl str::FromStr for Quantity {
+    type Err = Error;
+
+    /// read a number followed by a unit, e.g. `5.00 oz`, `152.0 F`, `12,5 °P` or `3 days`
+    fn from_str(s: &str) -> Result<Quantity> {
+        let invalid = || Error::from(ErrorKind::ParseError("quantity".into(), s.into()));
+        let s = s.trim();
+        let end = s.find(|c: char| !(c.is_ascii_digit() || "+-.,".contains(c)))
+            .ok_or_else(invalid)?;
+        let number = &s[..end];
+        let value = match number.parse() {
+            Ok(value) => value,
+            Err(_) if number.matches(',').count() == 1 && !number.contains('.') => {
+                number.replace(',', ".").parse().map_err(|_| invalid())?
+            }
+            Err(_) => return Err(invalid()),
+        };
+        let unit = s[end..].parse().map_err(|_| invalid())?;
+        Ok(Quantity::new(value, unit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(s: &str) -> Quantity {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_display_strings() {
+        assert_eq!(q("5.00 oz"), Quantity::new(5.0, Unit::Ounce));
+        assert_eq!(q("1.050 SG"), Quantity::new(1.05, Unit::SpecificGravity));
+        assert_eq!(q("12.5 °P"), Quantity::new(12.5, Unit::Plato));
+        assert_eq!(q("8.0 SRM"), Quantity::new(8.0, Unit::Srm));
+        assert_eq!(q("152.0 F"), Quantity::new(152.0, Unit::Fahrenheit));
+        assert_eq!(q("3 days").dimension(), Dimension::Time);
+        assert_eq!(q("2,5kg"), Quantity::new(2.5, Unit::Kilogram));
+        assert_eq!(q("10 °L").unit, Unit::Lovibond);
+        assert_eq!(q("10 L").unit, Unit::Liter);
+        assert_eq!(q("10 Lovi").unit, Unit::Lovibond);
+        assert_eq!(q("2 bbl").unit, Unit::Barrel);
+        assert_eq!(q("5 igal").unit, Unit::ImperialGallon);
+        assert!("1 pkg".parse::<Quantity>().is_err());
+        assert!("oz".parse::<Quantity>().is_err());
+        assert!("1.050".parse::<Quantity>().is_err());
+    }
+
+    #[test]
+    fn convert() {
+        assert!((q("5.00 oz").to_base() - 0.14174762).abs() < 1e-8);
+        assert!((q("152.0 F").to_base() - 66.6667).abs() < 1e-4);
+        assert!((q("12 °P").to_base() - 1.0484).abs() < 1e-4);
+        assert!((q("1.048 SG").to(Unit::Plato).unwrap().value - 11.9).abs() < 0.1);
+        assert_eq!(q("3 days").to_base(), 4320.0);
+        assert_eq!(q("20 L").to(Unit::Kilogram), None);
+        assert_eq!(format!("{:.1}", q("5 gal").to(Unit::Liter).unwrap()), "18.9 L");
+        assert_eq!(q("1 day").to_string(), "1 day");
+    }
+}
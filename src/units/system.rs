@@ -0,0 +1,121 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::fmt;
+use std::str;
+
+use error::*;
+use super::{Dimension, Quantity, Unit};
+
+/// the units a brewer prefers to see quantities in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum System {
+    /// kilograms, liters, degrees Celsius and EBC
+    Metric,
+    /// pounds, ounces, US gallons, degrees Fahrenheit and SRM
+    Us,
+    /// pounds, ounces, imperial gallons, degrees Fahrenheit and SRM
+    Imperial,
+}
+
+impl System {
+    /// the unit to show `quantity` in, picked by its dimension and size
+    pub fn unit(self, quantity: &Quantity) -> Unit {
+        let base = quantity.to_base();
+        let at_least = |unit: Unit| base.abs() >= unit.to_base(1.0);
+        match (quantity.dimension(), self) {
+            (Dimension::Mass, System::Metric) if at_least(Unit::Kilogram) => Unit::Kilogram,
+            (Dimension::Mass, System::Metric) => Unit::Gram,
+            (Dimension::Mass, _) if at_least(Unit::Pound) => Unit::Pound,
+            (Dimension::Mass, _) => Unit::Ounce,
+            (Dimension::Volume, System::Metric) if at_least(Unit::Liter) => Unit::Liter,
+            (Dimension::Volume, System::Metric) => Unit::Milliliter,
+            (Dimension::Volume, System::Us) if at_least(Unit::Gallon) => Unit::Gallon,
+            (Dimension::Volume, System::Us) => Unit::FluidOunce,
+            (Dimension::Volume, System::Imperial) if at_least(Unit::ImperialGallon) => {
+                Unit::ImperialGallon
+            }
+            (Dimension::Volume, System::Imperial) => Unit::ImperialFluidOunce,
+            (Dimension::Temperature, System::Metric) => Unit::Celsius,
+            (Dimension::Temperature, _) => Unit::Fahrenheit,
+            (Dimension::Time, _) if at_least(Unit::Day) => Unit::Day,
+            (Dimension::Time, _) => Unit::Minute,
+            (Dimension::Gravity, _) => Unit::SpecificGravity,
+            (Dimension::Color, System::Metric) => Unit::Ebc,
+            (Dimension::Color, _) => Unit::Srm,
+            (Dimension::Percent, _) => Unit::Percent,
+        }
+    }
+
+    /// `quantity` in the unit this system shows it in, rounded for display, e.g. `5.00 oz`
+    pub fn format<Q: Into<Quantity>>(self, quantity: Q) -> String {
+        let quantity = quantity.into();
+        let unit = self.unit(&quantity);
+        // the unit is of the same dimension, so the conversion can not fail
+        let shown = quantity.to(unit).unwrap();
+        format!("{:.*}", decimals(&shown), shown)
+    }
+}
+
+/// digits after the decimal point to show a quantity with
+pub(crate) fn decimals(quantity: &Quantity) -> usize {
+    match quantity.unit {
+        Unit::SpecificGravity => 3,
+        Unit::Gram | Unit::Milliliter => 1,
+        Unit::Kilogram | Unit::Pound | Unit::Ounce | Unit::Liter | Unit::Gallon |
+        Unit::ImperialGallon | Unit::FluidOunce | Unit::ImperialFluidOunce => 2,
+        // whole minutes and days are written without decimals, e.g. `60 min` or `3 days`
+        Unit::Minute | Unit::Day if (quantity.value * 10.0).round() % 10.0 == 0.0 => 0,
+        _ => 1,
+    }
+}
+
+impl fmt::Display for System {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            System::Metric => "metric",
+            System::Us => "us",
+            System::Imperial => "imperial",
+        })
+    }
+}
+
+impl str::FromStr for System {
+    type Err = Error;
+
+    /// read a system ignoring case: `metric`, `us` or `imperial`
+    fn from_str(s: &str) -> Result<System> {
+        match s.trim().to_lowercase().as_str() {
+            "metric" | "si" => Ok(System::Metric),
+            "us" => Ok(System::Us),
+            "imperial" | "uk" => Ok(System::Imperial),
+            _ => Err(ErrorKind::ParseError("unit system".into(), s.into()).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use units::{Color, Gravity, Mass, Temperature, Time, Volume};
+
+    #[test]
+    fn format() {
+        assert_eq!(System::Us.format(Mass(0.14174762)), "5.00 oz");
+        assert_eq!(System::Us.format(Mass(2.0)), "4.41 lb");
+        assert_eq!(System::Metric.format(Mass(0.0115)), "11.5 g");
+        assert_eq!(System::Metric.format(Mass(4.5)), "4.50 kg");
+        assert_eq!(System::Us.format(Volume(18.92705892)), "5.00 gal");
+        assert_eq!(System::Imperial.format(Volume(22.73045)), "5.00 imp gal");
+        assert_eq!(System::Metric.format(Volume(0.005)), "5.0 mL");
+        assert_eq!(System::Us.format(Temperature(66.6667)), "152.0 °F");
+        assert_eq!(System::Metric.format(Temperature(66.6667)), "66.7 °C");
+        assert_eq!(System::Us.format(Time(60.0)), "60 min");
+        assert_eq!(System::Us.format(Time(4320.0)), "3 days");
+        assert_eq!(System::Us.format(Time(1440.0)), "1 day");
+        assert_eq!(System::Metric.format(Gravity(1.05)), "1.050 SG");
+        assert_eq!(System::Us.format(Color(8.0)), "8.0 SRM");
+        assert_eq!(System::Metric.format(Color(8.0)), "15.8 EBC");
+        assert_eq!("US".parse::<System>().unwrap(), System::Us);
+        assert!("cubits".parse::<System>().is_err());
+    }
+}
@@ -0,0 +1,537 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+//! checks of records against the BeerXML 1.0 specification
+//!
+//! Enum values that are not known are kept while reading and reported here; `validate_xml`
+//! also reports enum values that were read through an alias, missing required fields and
+//! unknown elements, next to the checks on the values themselves.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use data::*;
+use error::*;
+use options::ParseOptions;
+use report::IssueKind;
+use xml;
+
+/// how bad a violation is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// allowed, but probably not what was meant
+    Warning,
+    /// not allowed by the specification
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// a way in which a record does not follow the specification
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// how bad it is
+    pub severity: Severity,
+    /// the record and field, with line and column when validating xml
+    pub location: Location,
+    /// a human readable description
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.location, self.message)
+    }
+}
+
+/// collects violations while walking the records
+#[derive(Default)]
+struct Validator {
+    records: Vec<(&'static str, String)>,
+    violations: Vec<Violation>,
+}
+
+impl Validator {
+    fn record<F>(&mut self, kind: &'static str, name: &str, check: F)
+        where F: FnOnce(&mut Validator)
+    {
+        self.records.push((kind, name.into()));
+        if name.is_empty() {
+            self.error("NAME", "must not be empty".into());
+        }
+        check(self);
+        self.records.pop();
+    }
+
+    fn add(&mut self, severity: Severity, field: &str, message: String) {
+        let path: Vec<String> = self.records
+            .iter()
+            .map(|&(kind, ref name)| if name.is_empty() {
+                kind.to_string()
+            } else {
+                format!("{} `{}`", kind, name)
+            })
+            .collect();
+        let (kind, name) = self.records.last().cloned().unwrap_or_default();
+        let location = Location {
+            path: path.join(" / "),
+            kind: kind.into(),
+            name,
+            field: field.into(),
+            ..Location::default()
+        };
+        self.violations.push(Violation {
+            severity,
+            location,
+            message,
+        });
+    }
+
+    fn error(&mut self, field: &str, message: String) {
+        self.add(Severity::Error, field, message)
+    }
+
+    fn warning(&mut self, field: &str, message: String) {
+        self.add(Severity::Warning, field, message)
+    }
+
+    fn version(&mut self, version: i64) {
+        if version != 1 {
+            self.error("VERSION", format!("must be 1, not {}", version));
+        }
+    }
+
+    fn not_empty(&mut self, field: &str, value: &str) {
+        if value.is_empty() {
+            self.error(field, "must not be empty".into());
+        }
+    }
+
+    fn non_negative<T: Into<f64>>(&mut self, field: &str, value: T) {
+        let value = value.into();
+        if value < 0.0 {
+            self.error(field, format!("must not be negative, not {}", value));
+        }
+    }
+
+    fn non_negative_o<T: Into<f64>>(&mut self, field: &str, value: Option<T>) {
+        if let Some(value) = value {
+            self.non_negative(field, value);
+        }
+    }
+
+    fn percent<T: Into<f64>>(&mut self, field: &str, value: T) {
+        let value = value.into();
+        if !(0.0..=100.0).contains(&value) {
+            self.error(field, format!("must be a percentage between 0 and 100, not {}", value));
+        }
+    }
+
+    fn percent_o<T: Into<f64>>(&mut self, field: &str, value: Option<T>) {
+        if let Some(value) = value {
+            self.percent(field, value);
+        }
+    }
+
+    /// specific gravities are positive, and for beer between about 1.0 and 1.2
+    fn gravity<T: Into<f64>>(&mut self, field: &str, value: T) {
+        let value = value.into();
+        if value <= 0.0 {
+            self.error(field, format!("must be a positive specific gravity, not {}", value));
+        } else if !(0.98..=1.2).contains(&value) {
+            self.warning(field, format!("{} is an unusual specific gravity", value));
+        }
+    }
+
+    fn gravity_o<T: Into<f64>>(&mut self, field: &str, value: Option<T>) {
+        if let Some(value) = value {
+            self.gravity(field, value);
+        }
+    }
+
+    fn known<T: OpenEnum>(&mut self, field: &str, value: &T) {
+        if let Some(value) = value.unknown() {
+            self.error(field, format!("`{}` is not a BeerXML value", value));
+        }
+    }
+
+    fn known_o<T: OpenEnum>(&mut self, field: &str, value: &Option<T>) {
+        if let Some(ref value) = *value {
+            self.known(field, value);
+        }
+    }
+
+    fn range<T: Into<f64>>(&mut self, min_field: &str, min: T, max_field: &str, max: T) {
+        let (min, max) = (min.into(), max.into());
+        if min > max {
+            self.error(min_field, format!("{} is above {} {}", min, max_field, max));
+        }
+    }
+
+    fn range_o<T>(&mut self, min_field: &str, min: Option<T>, max_field: &str, max: Option<T>)
+        where T: Into<f64>
+    {
+        if let (Some(min), Some(max)) = (min, max) {
+            self.range(min_field, min, max_field, max);
+        }
+    }
+
+    fn hop(&mut self, h: &Hop) {
+        self.record("HOP", &h.name, |v| {
+            v.version(h.version);
+            v.percent("ALPHA", h.alpha);
+            v.non_negative("AMOUNT", h.amount);
+            v.known("USE", &h.use_);
+            v.non_negative("TIME", h.time);
+            v.known_o("TYPE", &h.type_);
+            v.known_o("FORM", &h.form);
+            v.percent_o("BETA", h.beta);
+            v.percent_o("HSI", h.hsi);
+            v.percent_o("HUMULENE", h.humulene);
+            v.percent_o("CARYOPHYLLENE", h.caryophyllene);
+            v.percent_o("COHUMULONE", h.cohumulone);
+            v.percent_o("MYRCENE", h.myrcene);
+        })
+    }
+
+    fn fermentable(&mut self, f: &Fermentable) {
+        self.record("FERMENTABLE", &f.name, |v| {
+            v.version(f.version);
+            v.known("TYPE", &f.type_);
+            v.non_negative("AMOUNT", f.amount);
+            v.percent("YIELD", f.yield_);
+            v.non_negative("COLOR", f.color);
+            v.percent_o("COARSE_FINE_DIFF", f.coarse_fine_diff);
+            v.percent_o("MOISTURE", f.moisture);
+            v.non_negative_o("DIASTATIC_POWER", f.diastatic_power);
+            v.percent_o("PROTEIN", f.protein);
+            v.percent_o("MAX_IN_BATCH", f.max_in_batch);
+            match f.type_ {
+                FermentableType::Extract => v.non_negative_o("IBU_GAL_PER_LB", f.ibu_gal_per_lb),
+                ref t if f.ibu_gal_per_lb.is_some() => {
+                    let message = format!("only applies to Extract, not {}", t);
+                    v.warning("IBU_GAL_PER_LB", message)
+                }
+                _ => (),
+            }
+        })
+    }
+
+    fn yeast(&mut self, y: &Yeast) {
+        self.record("YEAST", &y.name, |v| {
+            v.version(y.version);
+            v.known("TYPE", &y.type_);
+            v.known("FORM", &y.form);
+            v.non_negative("AMOUNT", y.amount);
+            v.range_o("MIN_TEMPERATURE",
+                      y.min_temperature,
+                      "MAX_TEMPERATURE",
+                      y.max_temperature);
+            v.known_o("FLOCCULATION", &y.flocculation);
+            v.percent_o("ATTENUATION", y.attenuation);
+        })
+    }
+
+    fn misc(&mut self, m: &Misc) {
+        self.record("MISC", &m.name, |v| {
+            v.version(m.version);
+            v.known("TYPE", &m.type_);
+            v.known("USE", &m.use_);
+            v.non_negative("AMOUNT", m.amount);
+            v.non_negative("TIME", m.time);
+        })
+    }
+
+    fn water(&mut self, w: &Water) {
+        self.record("WATER", &w.name, |v| {
+            v.version(w.version);
+            v.non_negative("AMOUNT", w.amount);
+            v.non_negative("CALCIUM", w.calcium);
+            v.non_negative("BICARBONATE", w.bicarbonate);
+            v.non_negative("SULFATE", w.sulfate);
+            v.non_negative("CHLORIDE", w.chloride);
+            v.non_negative("SODIUM", w.sodium);
+            v.non_negative("MAGNESIUM", w.magnesium);
+            if let Some(ph) = w.ph {
+                if !(0.0..=14.0).contains(&ph) {
+                    v.error("PH", format!("must be between 0 and 14, not {}", ph));
+                }
+            }
+        })
+    }
+
+    fn style(&mut self, s: &Style) {
+        self.record("STYLE", &s.name, |v| {
+            v.version(s.version);
+            v.not_empty("CATEGORY", &s.category);
+            v.not_empty("CATEGORY_NUMBER", &s.category_number);
+            v.not_empty("STYLE_LETTER", &s.style_letter);
+            v.not_empty("STYLE_GUIDE", &s.style_guide);
+            v.known("TYPE", &s.type_);
+            v.gravity("OG_MIN", s.og_min);
+            v.gravity("OG_MAX", s.og_max);
+            v.range("OG_MIN", s.og_min, "OG_MAX", s.og_max);
+            v.gravity("FG_MIN", s.fg_min);
+            v.gravity("FG_MAX", s.fg_max);
+            v.range("FG_MIN", s.fg_min, "FG_MAX", s.fg_max);
+            v.non_negative("IBU_MIN", s.ibu_min);
+            v.range("IBU_MIN", s.ibu_min, "IBU_MAX", s.ibu_max);
+            v.non_negative("COLOR_MIN", s.color_min);
+            v.range("COLOR_MIN", s.color_min, "COLOR_MAX", s.color_max);
+            v.non_negative_o("CARB_MIN", s.carb_min);
+            v.range_o("CARB_MIN", s.carb_min, "CARB_MAX", s.carb_max);
+            v.percent_o("ABV_MIN", s.abv_min);
+            v.percent_o("ABV_MAX", s.abv_max);
+            v.range_o("ABV_MIN", s.abv_min, "ABV_MAX", s.abv_max);
+        })
+    }
+
+    fn mash_step(&mut self, s: &MashStep) {
+        self.record("MASH_STEP", &s.name, |v| {
+            v.version(s.version);
+            v.known("TYPE", &s.type_);
+            v.non_negative("STEP_TIME", s.step_time);
+            v.non_negative_o("RAMP_TIME", s.ramp_time);
+            match s.infuse_amount {
+                Some(a) if a.0 != 0.0 && s.type_.to_string() != "Infusion" => {
+                    v.error("INFUSE_AMOUNT",
+                            format!("must be zero for a {} step, not {}", s.type_, a))
+                }
+                a => v.non_negative_o("INFUSE_AMOUNT", a),
+            }
+        })
+    }
+
+    fn mash(&mut self, m: &Mash) {
+        self.record("MASH", &m.name, |v| {
+            v.version(m.version);
+            if let Some(ph) = m.ph {
+                if !(0.0..=14.0).contains(&ph) {
+                    v.error("PH", format!("must be between 0 and 14, not {}", ph));
+                }
+            }
+            for s in &m.mash_steps {
+                v.mash_step(s);
+            }
+        })
+    }
+
+    fn equipment(&mut self, e: &Equipment) {
+        self.record("EQUIPMENT", &e.name, |v| {
+            v.version(e.version);
+            v.non_negative("BOIL_SIZE", e.boil_size);
+            v.non_negative("BATCH_SIZE", e.batch_size);
+            v.non_negative_o("TUN_VOLUME", e.tun_volume);
+            v.non_negative_o("TUN_WEIGHT", e.tun_weight);
+            v.non_negative_o("TOP_UP_WATER", e.top_up_water);
+            v.non_negative_o("TRUB_CHILLER_LOSS", e.trub_chiller_loss);
+            v.percent_o("EVAP_RATE", e.evap_rate);
+            v.non_negative_o("BOIL_TIME", e.boil_time);
+            v.non_negative_o("LAUTER_DEADSPACE", e.lauter_deadspace);
+            v.non_negative_o("TOP_UP_KETTLE", e.top_up_kettle);
+            v.non_negative_o("HOP_UTILIZATION", e.hop_utilization);
+        })
+    }
+
+    fn recipe(&mut self, r: &Recipe) {
+        self.record("RECIPE", &r.name, |v| {
+            v.version(r.version);
+            v.known("TYPE", &r.type_);
+            v.not_empty("BREWER", &r.brewer);
+            v.non_negative("BATCH_SIZE", r.batch_size);
+            v.non_negative("BOIL_SIZE", r.boil_size);
+            v.non_negative("BOIL_TIME", r.boil_time);
+            match (&r.type_, r.efficiency) {
+                (&RecipeType::Extract, _) => (),
+                (t, None) => v.error("EFFICIENCY", format!("is required for {}", t)),
+                (_, e) => v.percent_o("EFFICIENCY", e),
+            }
+            v.gravity_o("OG", r.og);
+            v.gravity_o("FG", r.fg);
+            v.non_negative_o("CARBONATION", r.carbonation);
+            v.style(&r.style);
+            if let Some(ref e) = r.equipment {
+                v.equipment(e);
+            }
+            if let Some(ref m) = r.mash {
+                v.mash(m);
+            }
+            for h in &r.hops {
+                v.hop(h);
+            }
+            for f in &r.fermentables {
+                v.fermentable(f);
+            }
+            for m in &r.miscs {
+                v.misc(m);
+            }
+            for y in &r.yeasts {
+                v.yeast(y);
+            }
+            for w in &r.waters {
+                v.water(w);
+            }
+        })
+    }
+
+    fn document(&mut self, doc: &Document) {
+        for (_, x) in &doc.equipments {
+            self.equipment(x);
+        }
+        for (_, x) in &doc.fermentables {
+            self.fermentable(x);
+        }
+        for (_, x) in &doc.hops {
+            self.hop(x);
+        }
+        for (_, x) in &doc.miscs {
+            self.misc(x);
+        }
+        for (_, x) in &doc.recipes {
+            self.recipe(x);
+        }
+        for (_, x) in &doc.waters {
+            self.water(x);
+        }
+        for (_, x) in &doc.yeasts {
+            self.yeast(x);
+        }
+        for (_, x) in &doc.styles {
+            self.style(x);
+        }
+        for (_, x) in &doc.mashs {
+            self.mash(x);
+        }
+    }
+}
+
+/// check all records of a `Document`
+pub fn validate_document(doc: &Document) -> Vec<Violation> {
+    let mut v = Validator::default();
+    v.document(doc);
+    v.violations
+}
+
+/// check all records of a `RecordSet`
+pub fn validate(set: &RecordSet) -> Vec<Violation> {
+    validate_document(&Document::from(set.clone()))
+}
+
+/// read xml from a `reader` and check it: next to the checks of `validate_document` this
+/// reports unknown elements, missing required fields and values that can not be read, with
+/// their line and column
+pub fn validate_xml<B>(reader: B) -> Result<Vec<Violation>>
+    where B: BufRead
+{
+    // keep reading past values that do not parse, they are reported as issues
+    let options = ParseOptions { lenient_values: true, ..ParseOptions::default() };
+    let (doc, report) = match xml::read_document_with(reader, &options) {
+        Ok(res) => res,
+        Err(Error(ErrorKind::Invalid(location, message), _)) => {
+            return Ok(vec![Violation {
+                               severity: Severity::Error,
+                               location: *location,
+                               message,
+                           }]);
+        }
+        Err(e) => return Err(e),
+    };
+    let mut violations: Vec<Violation> = report.issues
+        .into_iter()
+        .map(|i| {
+            let severity = match i.kind {
+                IssueKind::Ignored | IssueKind::Duplicate => Severity::Warning,
+                IssueKind::Coerced | IssueKind::Defaulted => Severity::Error,
+            };
+            Violation {
+                severity,
+                location: i.location,
+                message: i.message,
+            }
+        })
+        .collect();
+    // a missing or unreadable field also fails the checks on its default value
+    for v in validate_document(&doc) {
+        if !violations.iter().any(|x| x.location.path == v.location.path &&
+                                      x.location.field == v.location.field) {
+            violations.push(v);
+        }
+    }
+    Ok(violations)
+}
+
+/// read an xml file and check it, see `validate_xml`
+pub fn validate_xml_file(filename: &Path) -> Result<Vec<Violation>> {
+    let f = File::open(filename)?;
+    validate_xml(BufReader::new(f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_files_have_no_errors() {
+        for name in &["data/hops.xml", "data/grain.xml", "data/yeast.xml", "data/misc.xml"] {
+            let violations = validate_xml_file(Path::new(name)).unwrap();
+            let errors: Vec<_> = violations.iter()
+                .filter(|v| v.severity == Severity::Error)
+                .map(ToString::to_string)
+                .collect();
+            assert!(errors.is_empty(), "{}: {:?}", name, errors);
+        }
+    }
+
+    #[test]
+    fn finds_violations() {
+        let xml = "<RECIPES><RECIPE><NAME>IPA</NAME><VERSION>2</VERSION><TYPE>All Grain</TYPE>\n\
+                   <BREWER>Me</BREWER><BATCH_SIZE>20</BATCH_SIZE><BOIL_SIZE>25</BOIL_SIZE>\
+                   <BOIL_TIME>60</BOIL_TIME><OG>10.5</OG><MISCS/><YEASTS/><WATERS/><MASH/>\
+                   <STYLE><NAME>IPA</NAME><VERSION>1</VERSION></STYLE>\
+                   <HOPS><HOP><NAME>Cascade</NAME><VERSION>1</VERSION><ALPHA>105</ALPHA>\
+                   <AMOUNT>0.1</AMOUNT><USE>Boil</USE><TIME>60</TIME><FORM>Cryo</FORM></HOP></HOPS>\
+                   <FERMENTABLES><FERMENTABLE><NAME>Pils</NAME><VERSION>1</VERSION>\
+                   <TYPE>Grain</TYPE><AMOUNT>5</AMOUNT><YIELD>80</YIELD><COLOR>2</COLOR>\
+                   <IBU_GAL_PER_LB>10</IBU_GAL_PER_LB></FERMENTABLE></FERMENTABLES>\
+                   </RECIPE></RECIPES>";
+        let violations = validate_xml(xml.as_bytes()).unwrap();
+        let found = |field: &str| violations.iter().find(|v| v.location.field == field).unwrap();
+        assert_eq!(found("VERSION").to_string(),
+                   "error: RECIPE `IPA`, field VERSION: must be 1, not 2");
+        assert_eq!(found("EFFICIENCY").severity, Severity::Error);
+        assert_eq!(found("OG").severity, Severity::Warning);
+        assert_eq!(found("ALPHA").location.path, "RECIPE `IPA` / HOP `Cascade`");
+        assert_eq!(found("IBU_GAL_PER_LB").severity, Severity::Warning);
+        assert_eq!(found("FORM").message, "`Cryo` is not a BeerXML value");
+        let category = found("CATEGORY");
+        assert_eq!((category.location.line, category.message.as_str()),
+                   (2, "missing required field, using the default"));
+
+        let mut set = RecordSet::Hops(RecordMap::new());
+        if let RecordSet::Hops(ref mut hops) = set {
+            hops.insert("Saaz".into(), Hop { name: "Saaz".into(), version: 1, ..Hop::default() });
+        }
+        assert!(validate(&set).is_empty());
+
+        // a value that does not parse is reported, and the rest is still checked
+        let xml = "<STYLES><STYLE><NAME>Wheat</NAME><VERSION>1</VERSION>\n\
+                   <OG_MIN>high</OG_MIN><CARB_MAX>2.6></CARB_MAX><ABV_MAX>150</ABV_MAX>\
+                   </STYLE></STYLES>";
+        let violations = validate_xml(xml.as_bytes()).unwrap();
+        assert_eq!(violations[0].to_string(),
+                   "error: line 2, column 1 in STYLE `Wheat`, field OG_MIN: \
+                    dropped `high`, using the default: invalid float literal");
+        assert_eq!(violations[1].to_string(),
+                   "error: line 2, column 22 in STYLE `Wheat`, field CARB_MAX: \
+                    dropped `2.6>`: invalid float literal");
+        assert!(violations.iter().any(|v| v.location.field == "ABV_MAX"));
+    }
+}
@@ -0,0 +1,80 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use std::str;
+
+use encoding_rs::{Encoding, UTF_8};
+
+use error::*;
+
+/// the encoding named in the `<?xml ... encoding="..."?>` declaration, if any
+fn declared(bytes: &[u8]) -> Option<&'static Encoding> {
+    if !bytes.starts_with(b"<?xml") {
+        return None;
+    }
+    let end = bytes.windows(2).position(|w| w == b"?>")?;
+    let decl = str::from_utf8(&bytes[..end]).ok()?;
+    let rest = decl[decl.find("encoding")? + "encoding".len()..].trim_start();
+    let rest = rest.strip_prefix('=')?.trim_start();
+    let quote = rest.chars().next().filter(|&q| q == '"' || q == '\'')?;
+    let label = &rest[1..];
+    Encoding::for_label(&label.as_bytes()[..label.find(quote)?])
+}
+
+/// decode a whole XML document to a string with its declaration blanked out, so the
+/// parser only ever sees UTF-8 and columns still match the input
+///
+/// A byte order mark wins over the declaration; a document that claims or defaults
+/// to UTF-8 but is not valid UTF-8 is decoded with the `legacy` encoding instead.
+pub fn decode(bytes: &[u8], legacy: &str) -> Result<(String, &'static Encoding)> {
+    let legacy = match Encoding::for_label(legacy.as_bytes()) {
+        Some(legacy) => legacy,
+        None => return Err(format!("unknown encoding {}", legacy).into()),
+    };
+    let (encoding, bytes) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom)) => (encoding, &bytes[bom..]),
+        None => (declared(bytes).unwrap_or(UTF_8), bytes),
+    };
+    let encoding = if encoding == UTF_8 && str::from_utf8(bytes).is_err() {
+        info!("not valid UTF-8, reading as {}", legacy.name());
+        legacy
+    } else {
+        encoding
+    };
+    let (text, malformed) = encoding.decode_without_bom_handling(bytes);
+    if malformed {
+        warn!("Replacing malformed {} sequences", encoding.name());
+    }
+    let text = match text.find("?>") {
+        Some(end) if text.starts_with("<?xml") => {
+            " ".repeat(text[..end + 2].chars().count()) + &text[end + 2..]
+        }
+        _ => text.into_owned(),
+    };
+    Ok((text, encoding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_encodings() {
+        let latin1 = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><NAME>M\xfcnchner</NAME>";
+        let (text, encoding) = decode(latin1, "windows-1252").unwrap();
+        assert_eq!((text.trim_start(), encoding.name()), ("<NAME>Münchner</NAME>", "windows-1252"));
+
+        let undeclared = b"<NAME>Saaz (\x8eatec)</NAME>";
+        let (text, encoding) = decode(undeclared, "windows-1250").unwrap();
+        assert_eq!((text.as_str(), encoding.name()), ("<NAME>Saaz (Žatec)</NAME>", "windows-1250"));
+
+        let wrongly_declared = b"<?xml version='1.0' encoding='UTF-8'?><NAME>M\xfcnchner</NAME>";
+        let (text, _) = decode(wrongly_declared, "windows-1252").unwrap();
+        assert_eq!(text.trim_start(), "<NAME>Münchner</NAME>");
+
+        let utf8 = "\u{feff}<?xml version=\"1.0\"?><NAME>Žatec</NAME>";
+        let (text, encoding) = decode(utf8.as_bytes(), "windows-1252").unwrap();
+        assert_eq!((text.trim_start(), encoding.name()), ("<NAME>Žatec</NAME>", "UTF-8"));
+
+        assert!(decode(b"<NAME/>", "klingon").is_err());
+    }
+}
@@ -1,17 +1,14 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::io::BufRead;
-use std::str;
-
-use quick_xml::reader::Reader;
-
 use super::*;
 
-pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Equipment)>
-    where B: BufRead
-{
+/// the fields BeerXML requires
+const REQUIRED: &[&str] = &["NAME", "VERSION", "BOIL_SIZE", "BATCH_SIZE"];
+
+pub fn read(reader: &mut XmlReader) -> Result<(String, Equipment)> {
     let mut f = Equipment::default();
-    read_t(reader, b"EQUIPMENT", |reader, name| {
+    let mut d = EquipmentDisplay::default();
+    read_t(reader, b"EQUIPMENT", REQUIRED, |reader, name| {
             match name {
                 b"NAME" => f.name = read_value(reader, name)?,
                 b"VERSION" => f.version = read_value_t(reader, name)?,
@@ -21,16 +18,31 @@ pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Equipment)>
                 b"TUN_WEIGHT" => f.tun_weight = read_value_t_o(reader, name)?,
                 b"TUN_SPECIFIC_HEAT" => f.tun_specific_heat = read_value_t_o(reader, name)?,
                 b"TOP_UP_WATER" => f.top_up_water = read_value_t_o(reader, name)?,
+                b"TRUB_CHILLER_LOSS" => f.trub_chiller_loss = read_value_t_o(reader, name)?,
                 b"EVAP_RATE" => f.evap_rate = read_value_t_o(reader, name)?,
                 b"BOIL_TIME" => f.boil_time = read_value_t_o(reader, name)?,
                 b"CALC_BOIL_VOLUME" => f.calc_boil_volume = read_value_b_o(reader, name)?,
                 b"LAUTER_DEADSPACE" => f.lauter_deadspace = read_value_t_o(reader, name)?,
                 b"TOP_UP_KETTLE" => f.top_up_kettle = read_value_t_o(reader, name)?,
+                b"HOP_UTILIZATION" => f.hop_utilization = read_value_t_o(reader, name)?,
                 b"NOTES" => f.notes = read_value_o(reader, name)?,
-                _ => warn!("Ignoring: {}", str::from_utf8(name)?),
+                b"DISPLAY_BOIL_SIZE" => d.display_boil_size = read_value_o(reader, name)?,
+                b"DISPLAY_BATCH_SIZE" => d.display_batch_size = read_value_o(reader, name)?,
+                b"DISPLAY_TUN_VOLUME" => d.display_tun_volume = read_value_o(reader, name)?,
+                b"DISPLAY_TUN_WEIGHT" => d.display_tun_weight = read_value_o(reader, name)?,
+                b"DISPLAY_TOP_UP_WATER" => d.display_top_up_water = read_value_o(reader, name)?,
+                b"DISPLAY_TRUB_CHILLER_LOSS" => {
+                    d.display_trub_chiller_loss = read_value_o(reader, name)?
+                }
+                b"DISPLAY_LAUTER_DEADSPACE" => {
+                    d.display_lauter_deadspace = read_value_o(reader, name)?
+                }
+                b"DISPLAY_TOP_UP_KETTLE" => d.display_top_up_kettle = read_value_o(reader, name)?,
+                _ => read_extension(reader, name, &mut f.extensions)?,
             }
             Ok(())
         })
         ?;
+    f.display = display_o(d);
     Ok((f.name.clone(), f))
 }
@@ -1,21 +1,18 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::io::BufRead;
-use std::str;
-
-use quick_xml::reader::Reader;
-
 use super::*;
 
-pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Fermentable)>
-    where B: BufRead
-{
+/// the fields BeerXML requires
+const REQUIRED: &[&str] = &["NAME", "VERSION", "TYPE", "AMOUNT", "YIELD", "COLOR"];
+
+pub fn read(reader: &mut XmlReader) -> Result<(String, Fermentable)> {
     let mut f = Fermentable::default();
-    read_t(reader, b"FERMENTABLE", |reader, name| {
+    let mut d = FermentableDisplay::default();
+    read_t(reader, b"FERMENTABLE", REQUIRED, |reader, name| {
             match name {
                 b"NAME" => f.name = read_value(reader, name)?,
                 b"VERSION" => f.version = read_value_t(reader, name)?,
-                b"TYPE" => f.type_ = read_value_t(reader, name)?,
+                b"TYPE" => f.type_ = read_enum(reader, name)?,
                 b"AMOUNT" => f.amount = read_value_t(reader, name)?,
                 b"YIELD" => f.yield_ = read_value_t(reader, name)?,
                 b"COLOR" => f.color = read_value_t(reader, name)?,
@@ -30,14 +27,15 @@ pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Fermentable)>
                 b"MAX_IN_BATCH" => f.max_in_batch = read_value_t_o(reader, name)?,
                 b"RECOMMEND_MASH" => f.recommend_mash = read_value_b(reader, name)?,
                 b"IBU_GAL_PER_LB" => f.ibu_gal_per_lb = read_value_t_o(reader, name)?,
-                b"DISPLAY_AMOUNT" => f.display_amount = read_value_o(reader, name)?,
-                b"INVENTORY" => f.inventory = read_value_o(reader, name)?,
-                b"POTENTIAL" => f.potential = read_value_t_o(reader, name)?,
-                b"DISPLAY_COLOR" => f.display_color = read_value_t_o(reader, name)?,
-                _ => warn!("Ignoring: {}", str::from_utf8(name)?),
+                b"DISPLAY_AMOUNT" => d.display_amount = read_value_o(reader, name)?,
+                b"POTENTIAL" => d.potential = read_value_t_o(reader, name)?,
+                b"INVENTORY" => d.inventory = read_value_o(reader, name)?,
+                b"DISPLAY_COLOR" => d.display_color = read_value_o(reader, name)?,
+                _ => read_extension(reader, name, &mut f.extensions)?,
             }
             Ok(())
         })
         ?;
+    f.display = display_o(d);
     Ok((f.name.clone(), f))
 }
@@ -1,27 +1,24 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::io::BufRead;
-use std::str;
-
-use quick_xml::reader::Reader;
-
 use super::*;
 
-pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Hop)>
-    where B: BufRead
-{
+/// the fields BeerXML requires
+const REQUIRED: &[&str] = &["NAME", "VERSION", "ALPHA", "AMOUNT", "USE", "TIME"];
+
+pub fn read(reader: &mut XmlReader) -> Result<(String, Hop)> {
     let mut f = Hop::default();
-    read_t(reader, b"HOP", |reader, name| {
+    let mut d = HopDisplay::default();
+    read_t(reader, b"HOP", REQUIRED, |reader, name| {
             match name {
                 b"NAME" => f.name = read_value(reader, name)?,
                 b"VERSION" => f.version = read_value_t(reader, name)?,
                 b"ALPHA" => f.alpha = read_value_t(reader, name)?,
                 b"AMOUNT" => f.amount = read_value_t(reader, name)?,
-                b"USE" => f.use_ = read_value_t(reader, name)?,
+                b"USE" => f.use_ = read_enum(reader, name)?,
                 b"TIME" => f.time = read_value_t(reader, name)?,
                 b"NOTES" => f.notes = read_value_o(reader, name)?,
-                b"TYPE" => f.type_ = Some(read_value_t(reader, name)?),
-                b"FORM" => f.form = Some(read_value_t(reader, name)?),
+                b"TYPE" => f.type_ = Some(read_enum(reader, name)?),
+                b"FORM" => f.form = Some(read_enum(reader, name)?),
                 b"BETA" => f.beta = Some(read_value_t(reader, name)?),
                 b"HSI" => f.hsi = Some(read_value_t(reader, name)?),
                 b"ORIGIN" => f.origin = read_value_o(reader, name)?,
@@ -30,10 +27,14 @@ pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Hop)>
                 b"CARYOPHYLLENE" => f.caryophyllene = Some(read_value_t(reader, name)?),
                 b"COHUMULONE" => f.cohumulone = Some(read_value_t(reader, name)?),
                 b"MYRCENE" => f.myrcene = Some(read_value_t(reader, name)?),
-                _ => warn!("Ignoring: {}", str::from_utf8(name)?),
+                b"DISPLAY_AMOUNT" => d.display_amount = read_value_o(reader, name)?,
+                b"INVENTORY" => d.inventory = read_value_o(reader, name)?,
+                b"DISPLAY_TIME" => d.display_time = read_value_o(reader, name)?,
+                _ => read_extension(reader, name, &mut f.extensions)?,
             }
             Ok(())
         })
         ?;
+    f.display = display_o(d);
     Ok((f.name.clone(), f))
 }
@@ -1,17 +1,14 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::io::BufRead;
-use std::str;
-
-use quick_xml::reader::Reader;
-
 use super::*;
 
-pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Mash)>
-    where B: BufRead
-{
+/// the fields BeerXML requires
+const REQUIRED: &[&str] = &["NAME", "VERSION", "GRAIN_TEMP", "MASH_STEPS"];
+
+pub fn read(reader: &mut XmlReader) -> Result<(String, Mash)> {
     let mut f = Mash::default();
-    read_t(reader, b"MASH", |reader, name| {
+    let mut d = MashDisplay::default();
+    read_t(reader, b"MASH", REQUIRED, |reader, name| {
             match name {
                 b"NAME" => f.name = read_value(reader, name)?,
                 b"VERSION" => f.version = read_value_t(reader, name)?,
@@ -24,10 +21,15 @@ pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Mash)>
                 b"TUN_WEIGHT" => f.tun_weight = read_value_t_o(reader, name)?,
                 b"TUN_SPECIFIC_HEAT" => f.tun_specific_heat = read_value_t_o(reader, name)?,
                 b"EQUIP_ADJUST" => f.equip_adjust = read_value_b_o(reader, name)?,
-                _ => warn!("Ignoring: {}", str::from_utf8(name)?),
+                b"DISPLAY_GRAIN_TEMP" => d.display_grain_temp = read_value_o(reader, name)?,
+                b"DISPLAY_TUN_TEMP" => d.display_tun_temp = read_value_o(reader, name)?,
+                b"DISPLAY_SPARGE_TEMP" => d.display_sparge_temp = read_value_o(reader, name)?,
+                b"DISPLAY_TUN_WEIGHT" => d.display_tun_weight = read_value_o(reader, name)?,
+                _ => read_extension(reader, name, &mut f.extensions)?,
             }
             Ok(())
         })
         ?;
+    f.display = display_o(d);
     Ok((f.name.clone(), f))
 }
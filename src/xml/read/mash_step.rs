@@ -1,30 +1,34 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::io::BufRead;
-use std::str;
-
-use quick_xml::reader::Reader;
-
 use super::*;
 
-pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, MashStep)>
-    where B: BufRead
-{
+/// the fields BeerXML requires
+const REQUIRED: &[&str] = &["NAME", "VERSION", "TYPE", "STEP_TEMP", "STEP_TIME"];
+
+pub fn read(reader: &mut XmlReader) -> Result<(String, MashStep)> {
     let mut f = MashStep::default();
-    read_t(reader, b"MASH_STEP", |reader, name| {
+    let mut d = MashStepDisplay::default();
+    read_t(reader, b"MASH_STEP", REQUIRED, |reader, name| {
             match name {
                 b"NAME" => f.name = read_value(reader, name)?,
                 b"VERSION" => f.version = read_value_t(reader, name)?,
-                b"TYPE" => f.type_ = read_value_t(reader, name)?,
+                b"TYPE" => f.type_ = read_enum(reader, name)?,
                 b"INFUSE_AMOUNT" => f.infuse_amount = read_value_t_o(reader, name)?,
                 b"STEP_TEMP" => f.step_temp = read_value_t(reader, name)?,
                 b"STEP_TIME" => f.step_time = read_value_t(reader, name)?,
                 b"RAMP_TIME" => f.ramp_time = read_value_t_o(reader, name)?,
                 b"END_TEMP" => f.end_temp = read_value_t_o(reader, name)?,
-                _ => warn!("Ignoring: {}", str::from_utf8(name)?),
+                b"DISPLAY_STEP_TEMP" => d.display_step_temp = read_value_o(reader, name)?,
+                b"DISPLAY_INFUSE_AMT" => d.display_infuse_amt = read_value_o(reader, name)?,
+                b"DESCRIPTION" => d.description = read_value_o(reader, name)?,
+                b"WATER_GRAIN_RATIO" => d.water_grain_ratio = read_value_o(reader, name)?,
+                b"DECOCTION_AMT" => d.decoction_amt = read_value_o(reader, name)?,
+                b"INFUSE_TEMP" => d.infuse_temp = read_value_o(reader, name)?,
+                _ => read_extension(reader, name, &mut f.extensions)?,
             }
             Ok(())
         })
         ?;
+    f.display = display_o(d);
     Ok((f.name.clone(), f))
 }
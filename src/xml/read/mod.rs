@@ -1,27 +1,160 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
 use std::fs::File;
 use std::path::Path;
 use std::str;
-use std::fmt;
 
 use quick_xml::reader::Reader;
 use quick_xml::events::Event;
 
 use data::*;
 use error::*;
+use options::ParseOptions;
+use report::{IssueKind, ParseReport};
 
-fn read_value<B>(reader: &mut Reader<B>, name: &[u8]) -> Result<String>
-    where B: BufRead
-{
+/// a record that is being read
+#[derive(Default)]
+struct Record {
+    kind: String,
+    name: String,
+    /// tag of the last known element read, that the next unknown one comes after
+    after: String,
+    /// the element being read is not known and was kept as an extension
+    unknown: bool,
+}
+
+/// byte offsets of the start of every line in `text`
+fn line_starts(text: &[u8]) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(text.iter().enumerate().filter(|&(_, &c)| c == b'\n').map(|(i, _)| i + 1));
+    starts
+}
+
+/// an xml reader that keeps track of where it is in the input, to locate errors
+pub struct XmlReader<'a> {
+    reader: Reader<&'a [u8]>,
+    text: &'a [u8],
+    options: &'a ParseOptions,
+    records: Vec<Record>,
+    /// byte offset of the start of every line, to locate errors
+    line_starts: Vec<usize>,
+    /// byte offset of the last start tag
+    start: usize,
+    /// the last start tag was self-closing, so the element has no content to read
+    empty: bool,
+    report: ParseReport,
+}
+
+impl<'a> XmlReader<'a> {
+    fn new(text: &'a [u8], options: &'a ParseOptions) -> XmlReader<'a> {
+        let mut reader = Reader::from_reader(text);
+        reader.trim_text(true);
+        XmlReader {
+            reader,
+            text,
+            options,
+            records: vec![],
+            line_starts: line_starts(text),
+            start: 0,
+            empty: false,
+            report: ParseReport::default(),
+        }
+    }
+
+    fn read_event<'b>(&mut self, buf: &'b mut Vec<u8>) -> Result<Event<'b>> {
+        let event = self.reader.read_event(buf)?;
+        let position = self.reader.buffer_position();
+        match event {
+            Event::Start(ref e) => {
+                self.start = position - e.len() - 2;
+                self.empty = false;
+            }
+            Event::Empty(ref e) => {
+                self.start = position - e.len() - 3;
+                self.empty = true;
+            }
+            _ => (),
+        }
+        Ok(event)
+    }
+
+    /// the location of the element starting at byte `offset` in the current record
+    fn location(&self, offset: usize, field: &[u8]) -> Location {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let line_start = self.line_starts[line];
+        let path: Vec<String> = self.records
+            .iter()
+            .map(|r| if r.name.is_empty() {
+                r.kind.clone()
+            } else {
+                format!("{} `{}`", r.kind, r.name)
+            })
+            .collect();
+        let (kind, name) = match self.records.last() {
+            Some(r) => (r.kind.clone(), r.name.clone()),
+            None => (String::new(), String::new()),
+        };
+        Location {
+            path: path.join(" / "),
+            kind,
+            name,
+            field: String::from_utf8_lossy(field).into_owned(),
+            line: line + 1,
+            column: String::from_utf8_lossy(&self.text[line_start..offset]).chars().count() + 1,
+        }
+    }
+
+    /// the location of a record of the given `kind` and `name` starting at byte `offset`
+    fn record_location(&mut self, offset: usize, kind: &str, name: &str) -> Location {
+        self.records.push(Record {
+            kind: kind.into(),
+            name: name.into(),
+            ..Record::default()
+        });
+        let location = self.location(offset, b"");
+        self.records.pop();
+        location
+    }
+
+    /// note something that was worked around for the element starting at byte `offset`
+    fn report(&mut self, kind: IssueKind, offset: usize, field: &[u8], message: String) {
+        let location = self.location(offset, field);
+        self.report.push(kind, location, message);
+    }
+
+    fn invalid(&self, offset: usize, field: &[u8], reason: &str) -> Error {
+        ErrorKind::Invalid(Box::new(self.location(offset, field)), reason.into()).into()
+    }
+
+    /// attach the location to an error that does not have one yet
+    fn located(&self, offset: usize, field: &[u8], e: Error) -> Error {
+        if let ErrorKind::Invalid(..) = *e.kind() {
+            return e;
+        }
+        let reason = e.to_string();
+        let location = Box::new(self.location(offset, field));
+        Error::with_chain(e, ErrorKind::Invalid(location, reason))
+    }
+}
+
+fn read_value(reader: &mut XmlReader, name: &[u8]) -> Result<String> {
     let mut buf = vec![];
     let mut txt = String::new();
+    // a self-closing element has no content and no end tag
+    if reader.empty {
+        return Ok(txt);
+    }
     loop {
         match reader.read_event(&mut buf)? {
             Event::Text(ref e) => {
-                txt = e.unescape_and_decode(reader)?;
+                txt = e.unescape_and_decode(&reader.reader)?;
+            }
+            Event::CData(ref e) => {
+                txt = reader.reader.decode(e).into_owned();
             }
             Event::End(ref e) if e.name() == name => break,
             Event::Eof => break,
@@ -29,12 +162,15 @@ fn read_value<B>(reader: &mut Reader<B>, name: &[u8]) -> Result<String>
         }
         buf.clear();
     }
+    if name == b"NAME" {
+        if let Some(record) = reader.records.last_mut() {
+            record.name = txt.clone();
+        }
+    }
     Ok(txt)
 }
 
-fn read_value_o<B>(reader: &mut Reader<B>, name: &[u8]) -> Result<Option<String>>
-    where B: BufRead
-{
+fn read_value_o(reader: &mut XmlReader, name: &[u8]) -> Result<Option<String>> {
     let v = read_value(reader, name)?;
     if v.is_empty() {
         return Ok(None);
@@ -42,129 +178,258 @@ fn read_value_o<B>(reader: &mut Reader<B>, name: &[u8]) -> Result<Option<String>
     Ok(Some(v))
 }
 
-fn read_value_t<B, T>(reader: &mut Reader<B>, name: &[u8]) -> Result<T>
-    where B: BufRead,
-          T: str::FromStr,
+/// parse a value, falling back to reading it as a localized number
+fn parse<T>(reader: &mut XmlReader, name: &[u8], v: &str) -> ::std::result::Result<T, T::Err>
+    where T: str::FromStr
+{
+    let e = match v.parse::<T>() {
+        Ok(res) => return Ok(res),
+        Err(e) => e,
+    };
+    if let Some(n) = number::normalize(v, &reader.options.numbers) {
+        if let Ok(res) = n.parse::<T>() {
+            let start = reader.start;
+            reader.report(IssueKind::Coerced, start, name, format!("read `{}` as `{}`", v, n));
+            return Ok(res);
+        }
+    }
+    Err(e)
+}
+
+/// read a required value; an empty one is left at the default, `read_t` reports it as missing
+fn read_value_t<T>(reader: &mut XmlReader, name: &[u8]) -> Result<T>
+    where T: str::FromStr + Default,
           Error: ::std::convert::From<<T as str::FromStr>::Err>
 {
     let v = read_value(reader, name)?;
-    let res = v.parse::<T>()?;
-    Ok(res)
+    if v.is_empty() {
+        return Ok(T::default());
+    }
+    match parse(reader, name, &v) {
+        Ok(res) => Ok(res),
+        Err(e) => lenient_value(reader, name, &v, Error::from(e)),
+    }
 }
 
-fn read_value_t_o<B, T>(reader: &mut Reader<B>, name: &[u8]) -> Result<Option<T>>
-    where B: BufRead,
-          T: str::FromStr,
-          Error: ::std::convert::From<<T as str::FromStr>::Err>,
-          <T as str::FromStr>::Err: fmt::Debug
+/// in `lenient_values` mode a required value that does not parse is reported and defaulted
+fn lenient_value<T>(reader: &mut XmlReader, name: &[u8], v: &str, e: Error) -> Result<T>
+    where T: Default
+{
+    if !reader.options.lenient_values || reader.options.strict {
+        return Err(e);
+    }
+    let start = reader.start;
+    let message = format!("dropped `{}`, using the default: {}", v, e);
+    reader.report(IssueKind::Coerced, start, name, message);
+    Ok(T::default())
+}
+
+fn read_value_t_o<T>(reader: &mut XmlReader, name: &[u8]) -> Result<Option<T>>
+    where T: str::FromStr,
+          Error: ::std::convert::From<<T as str::FromStr>::Err>
 {
     let v = read_value(reader, name)?;
-    let res = match v.parse::<T>() {
+    let res = match parse::<T>(reader, name, &v) {
         Ok(res) => Some(res),
+        Err(_) if v.is_empty() => None,
+        Err(e) if reader.options.strict => return Err(e.into()),
         Err(e) => {
-            warn!("Ignoring parse error for optional field {} with value {}: {:?}",
-                  str::from_utf8(name)?,
-                  v,
-                  e);
+            let start = reader.start;
+            let message = format!("dropped `{}`: {}", v, Error::from(e));
+            reader.report(IssueKind::Coerced, start, name, message);
             None
         }
     };
     Ok(res)
 }
 
+fn read_enum<T: OpenEnum>(reader: &mut XmlReader, name: &[u8]) -> Result<T> {
+    let v = read_value(reader, name)?;
+    let res = if reader.options.lenient_enums {
+        T::parse_lenient(&v)
+    } else {
+        T::parse_exact(&v)
+    };
+    if res.unknown().is_some() {
+        if !reader.options.unknown_enums {
+            let what = String::from_utf8_lossy(name).into_owned();
+            return Err(ErrorKind::ParseError(what, v).into());
+        }
+    } else if res.to_string() != v {
+        let start = reader.start;
+        let message = format!("read `{}` as `{}`", v, res);
+        reader.report(IssueKind::Coerced, start, name, message);
+    }
+    Ok(res)
+}
 
-fn read_value_b<B>(reader: &mut Reader<B>, name: &[u8]) -> Result<bool>
-    where B: BufRead
-{
+fn read_value_b(reader: &mut XmlReader, name: &[u8]) -> Result<bool> {
     let v = read_value(reader, name)?;
     match v.to_lowercase().as_str() {
         "true" => Ok(true),
         "false" => Ok(false),
-        x => Err(format!("unknown boolean: {}", x).into()),
+        x => {
+            let e = format!("unknown boolean: {}", x).into();
+            lenient_value(reader, name, &v, e)
+        }
     }
 }
 
-fn read_value_b_o<B>(reader: &mut Reader<B>, name: &[u8]) -> Result<Option<bool>>
-    where B: BufRead
-{
+fn read_value_b_o(reader: &mut XmlReader, name: &[u8]) -> Result<Option<bool>> {
     let v = read_value(reader, name)?;
     match v.to_lowercase().as_str() {
         "true" => Ok(Some(true)),
-        _ => Ok(None),
+        "false" => Ok(Some(false)),
+        "" => Ok(None),
+        x if reader.options.strict => Err(format!("unknown boolean: {}", x).into()),
+        x => {
+            let start = reader.start;
+            let message = format!("dropped unknown boolean `{}`", x);
+            reader.report(IssueKind::Coerced, start, name, message);
+            Ok(None)
+        }
+    }
+}
+
+fn display_o<T>(display: T) -> Option<T>
+    where T: Default + PartialEq
+{
+    if display == T::default() {
+        None
+    } else {
+        Some(display)
     }
 }
 
-fn read_t<B, F>(reader: &mut Reader<B>, name: &[u8], mut do_element: F) -> Result<()>
-    where B: BufRead,
-          F: FnMut(&mut Reader<B>, &[u8]) -> Result<()>
+/// read the fields of a record of the given `kind`; errors are given the location of the
+/// field, and in strict mode all `required` fields have to be present, otherwise missing
+/// ones are reported as defaulted
+fn read_t<F>(reader: &mut XmlReader, kind: &[u8], required: &[&str], mut do_element: F) -> Result<()>
+    where F: FnMut(&mut XmlReader, &[u8]) -> Result<()>
 {
+    let offset = reader.start;
+    reader.records.push(Record {
+        kind: str::from_utf8(kind)?.into(),
+        ..Record::default()
+    });
+    let mut seen = vec![];
     let mut buf = vec![];
-    loop {
+    // a self-closing record has no fields and no end tag
+    let mut done = reader.empty;
+    while !done {
         match reader.read_event(&mut buf)? {
-            Event::Start(ref e) => {
+            Event::Start(ref e) | Event::Empty(ref e) => {
                 let name = e.name();
-                do_element(reader, name)?;
+                let start = reader.start;
+                let content = reader.reader.buffer_position();
+                do_element(reader, name).map_err(|e| reader.located(start, name, e))?;
+                // an element without content, like `<AMOUNT></AMOUNT>`, is as good as missing,
+                // and is not written back, so extensions can't be placed after it either
+                let present = has_content(&reader.text[content..reader.reader.buffer_position()]);
+                if let Some(record) = reader.records.last_mut() {
+                    if present && !record.unknown {
+                        record.after = String::from_utf8_lossy(name).into_owned();
+                    }
+                    record.unknown = false;
+                }
+                if present {
+                    seen.push(name.to_vec());
+                }
             }
-            Event::End(ref e) if e.name() == name => break,
-            Event::Eof => break,
+            Event::End(ref e) if e.name() == kind => done = true,
+            Event::Eof => done = true,
             _ => (),
         }
         buf.clear();
     }
+    for field in required.iter().filter(|f| !seen.iter().any(|s| s == f.as_bytes())) {
+        if reader.options.strict {
+            return Err(reader.invalid(offset, field.as_bytes(), "missing required field"));
+        }
+        let message = "missing required field, using the default".into();
+        reader.report(IssueKind::Defaulted, offset, field.as_bytes(), message);
+    }
+    reader.records.pop();
     Ok(())
 }
 
-fn read_map<B, F, T>(reader: &mut Reader<B>,
-                     elements_name: &'static str,
-                     element_name: &'static str,
-                     read_element: F)
-                     -> Result<HashMap<String, T>>
-    where B: BufRead,
-          F: Fn(&mut Reader<B>) -> Result<(String, T)>
+/// read an optional nested record, `None` if it is written empty as `<TAG/>`
+fn read_record_o<F, T>(reader: &mut XmlReader, read_record: F) -> Result<Option<T>>
+    where F: Fn(&mut XmlReader) -> Result<(String, T)>
+{
+    if reader.empty {
+        return Ok(None);
+    }
+    Ok(Some(read_record(reader)?.1))
+}
+
+/// `true` if the content of an element up to its end tag is more than whitespace
+fn has_content(text: &[u8]) -> bool {
+    let end = text.iter().rposition(|&c| c == b'<').unwrap_or(text.len());
+    text[..end].iter().any(|c| !c.is_ascii_whitespace())
+}
+
+/// read named records into `map`; a record with the name of an earlier one replaces it
+fn read_map<F, T>(reader: &mut XmlReader,
+                  elements_name: &'static str,
+                  element_name: &'static str,
+                  read_element: F,
+                  map: &mut RecordMap<T>)
+                  -> Result<()>
+    where F: Fn(&mut XmlReader) -> Result<(String, T)>
 {
+    let kind = element_name;
     let element_name = element_name.as_bytes();
     let elements_name = elements_name.as_bytes();
     let mut buf = vec![];
-    let mut map = HashMap::new();
+    // a self-closing list has no records and no end tag
+    if reader.empty {
+        return Ok(());
+    }
     loop {
         match reader.read_event(&mut buf)? {
-            Event::Start(ref e) if e.name() == element_name => {
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == element_name => {
+                let start = reader.start;
                 let (name, element) = read_element(reader)?;
+                if map.contains_key(&name) {
+                    let location = reader.record_location(start, kind, &name);
+                    let message = "duplicate name, replaces the earlier record".into();
+                    reader.report.push(IssueKind::Duplicate, location, message);
+                }
                 map.insert(name, element);
             }
-            Event::Start(ref e) => {
-                warn!("Ignoring: {}", str::from_utf8(e.name()).unwrap());
-            }
+            Event::Start(ref e) | Event::Empty(ref e) => read_unknown(reader, e.name())?,
             Event::End(ref e) if e.name() == elements_name => break,
             Event::Eof => break,
             _ => (),
         }
         buf.clear();
     }
-    Ok(map)
+    Ok(())
 }
 
-fn read_vec<B, F, T>(reader: &mut Reader<B>,
-                     elements_name: &'static str,
-                     element_name: &'static str,
-                     read_element: F)
-                     -> Result<Vec<T>>
-    where B: BufRead,
-          F: Fn(&mut Reader<B>) -> Result<(String, T)>
+fn read_vec<F, T>(reader: &mut XmlReader,
+                  elements_name: &'static str,
+                  element_name: &'static str,
+                  read_element: F)
+                  -> Result<Vec<T>>
+    where F: Fn(&mut XmlReader) -> Result<(String, T)>
 {
     let element_name = element_name.as_bytes();
     let elements_name = elements_name.as_bytes();
     let mut buf = vec![];
     let mut res = vec![];
+    // a self-closing list has no records and no end tag
+    if reader.empty {
+        return Ok(res);
+    }
     loop {
         match reader.read_event(&mut buf)? {
-            Event::Start(ref e) if e.name() == element_name => {
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == element_name => {
                 let (_, element) = read_element(reader)?;
                 res.push(element);
             }
-            Event::Start(ref e) => {
-                warn!("Ignoring: {}", str::from_utf8(e.name()).unwrap());
-            }
+            Event::Start(ref e) | Event::Empty(ref e) => read_unknown(reader, e.name())?,
             Event::End(ref e) if e.name() == elements_name => break,
             Event::Eof => break,
             _ => (),
@@ -174,93 +439,190 @@ fn read_vec<B, F, T>(reader: &mut Reader<B>,
     Ok(res)
 }
 
-/// try to read a `RecordSet` from a `reader`
+/// try to read a `RecordSet` from a `reader`; if the xml contains several record kinds only
+/// the kind read last is returned, use `read_document` to get all of them
 pub fn read<B>(reader: B) -> Result<RecordSet>
     where B: BufRead
 {
-    let mut reader = Reader::from_reader(reader);
-    reader.trim_text(true);
+    Ok(read_with(reader, &ParseOptions::default())?.0)
+}
+
+/// try to read a `RecordSet` from a `reader` using the given `ParseOptions`, keeping the
+/// kind read last as `read` does; also returns a report of what had to be worked around
+pub fn read_with<B>(reader: B, options: &ParseOptions) -> Result<(RecordSet, ParseReport)>
+    where B: BufRead
+{
+    let (doc, report, last) = read_bytes(reader, options)?;
+    Ok((last_set(doc, &last), report))
+}
+
+/// try to read a `Document` from a `reader`
+pub fn read_document<B>(reader: B) -> Result<Document>
+    where B: BufRead
+{
+    Ok(read_document_with(reader, &ParseOptions::default())?.0)
+}
+
+/// try to read a `Document` from a `reader` using the given `ParseOptions`; also returns
+/// a report of what had to be worked around
+pub fn read_document_with<B>(reader: B, options: &ParseOptions) -> Result<(Document, ParseReport)>
+    where B: BufRead
+{
+    let (doc, report, _) = read_bytes(reader, options)?;
+    Ok((doc, report))
+}
+
+/// decode and read a document; also returns the name of the record list read last
+fn read_bytes<B>(mut reader: B, options: &ParseOptions) -> Result<(Document, ParseReport, Vec<u8>)>
+    where B: BufRead
+{
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    let (text, encoding) = decode::decode(&bytes, &options.legacy_encoding)?;
+    let (doc, mut report, last) = read_decoded(text.as_bytes(), options)?;
+    report.encoding = encoding.name().into();
+    Ok((doc, report, last))
+}
+
+/// the record lists a document can hold
+const LISTS: [&[u8]; 9] = [b"FERMENTABLES", b"HOPS", b"YEASTS", b"MISCS", b"WATERS", b"RECIPES",
+                           b"STYLES", b"MASHS", b"EQUIPMENTS"];
+
+/// the records of a document that were read from the record list `name`
+fn last_set(doc: Document, name: &[u8]) -> RecordSet {
+    match name {
+        b"FERMENTABLES" => RecordSet::Fermentables(doc.fermentables),
+        b"HOPS" => RecordSet::Hops(doc.hops),
+        b"YEASTS" => RecordSet::Yeasts(doc.yeasts),
+        b"MISCS" => RecordSet::Miscs(doc.miscs),
+        b"WATERS" => RecordSet::Waters(doc.waters),
+        b"RECIPES" => RecordSet::Recipes(doc.recipes),
+        b"STYLES" => RecordSet::Styles(doc.styles),
+        b"MASHS" => RecordSet::Mashs(doc.mashs),
+        b"EQUIPMENTS" => RecordSet::Equipments(doc.equipments),
+        _ => RecordSet::Empty,
+    }
+}
+
+/// read a document that has been decoded to UTF-8
+fn read_decoded(text: &[u8], options: &ParseOptions) -> Result<(Document, ParseReport, Vec<u8>)> {
+    let mut reader = XmlReader::new(text, options);
     let mut buf = vec![];
-    let mut rs = RecordSet::Empty;
+    let mut doc = Document::default();
+    let mut last = vec![];
     loop {
-        match reader.read_event(&mut buf)? {
-            Event::Start(ref e) if e.name() == b"FERMENTABLES" => {
-                let f = read_map(&mut reader,
-                                 "FERMENTABLES",
-                                 "FERMENTABLE",
-                                 fermentable::read)
-                    ?;
-                rs = RecordSet::Fermentables(f);
-                // info!("Fermentables: {:?}", f);
+        let event = reader.read_event(&mut buf)?;
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) if LISTS.contains(&e.name()) => {
+                last = e.name().to_vec()
             }
-            Event::Start(ref e) if e.name() == b"HOPS" => {
-                let f = read_map(&mut reader, "HOPS", "HOP", hop::read)?;
-                rs = RecordSet::Hops(f);
-                // info!("Hops: {:?}", f);
+            _ => (),
+        }
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == b"FERMENTABLES" => {
+                read_map(&mut reader,
+                         "FERMENTABLES",
+                         "FERMENTABLE",
+                         fermentable::read,
+                         &mut doc.fermentables)?;
             }
-            Event::Start(ref e) if e.name() == b"YEASTS" => {
-                let f = read_map(&mut reader, "YEASTS", "YEAST", yeast::read)?;
-                rs = RecordSet::Yeasts(f);
-                // info!("Yeasts: {:?}", f);
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == b"HOPS" => {
+                read_map(&mut reader, "HOPS", "HOP", hop::read, &mut doc.hops)?;
             }
-            Event::Start(ref e) if e.name() == b"MISCS" => {
-                let f = read_map(&mut reader, "MISCS", "MISC", misc::read)?;
-                rs = RecordSet::Miscs(f);
-                // info!("Miscs: {:?}", f);
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == b"YEASTS" => {
+                read_map(&mut reader, "YEASTS", "YEAST", yeast::read, &mut doc.yeasts)?;
             }
-            Event::Start(ref e) if e.name() == b"WATERS" => {
-                let f = read_map(&mut reader, "WATERS", "WATER", water::read)?;
-                rs = RecordSet::Waters(f);
-                // info!("Waters: {:?}", f);
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == b"MISCS" => {
+                read_map(&mut reader, "MISCS", "MISC", misc::read, &mut doc.miscs)?;
             }
-            Event::Start(ref e) if e.name() == b"RECIPES" => {
-                let f = read_map(&mut reader, "RECIPES", "RECIPE", recipe::read)?;
-                rs = RecordSet::Recipes(f);
-                // info!("Recipes: {:?}", f);
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == b"WATERS" => {
+                read_map(&mut reader, "WATERS", "WATER", water::read, &mut doc.waters)?;
             }
-            Event::Start(ref e) if e.name() == b"STYLES" => {
-                let f = read_map(&mut reader, "STYLES", "STYLE", style::read)?;
-                rs = RecordSet::Styles(f);
-                // info!("Styles: {:?}", f);
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == b"RECIPES" => {
+                read_map(&mut reader, "RECIPES", "RECIPE", recipe::read, &mut doc.recipes)?;
             }
-            Event::Start(ref e) if e.name() == b"MASHS" => {
-                let f = read_map(&mut reader, "MASHS", "MASH", mash::read)?;
-                rs = RecordSet::Mashs(f);
-                // info!("Mashs: {:?}", f);
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == b"STYLES" => {
+                read_map(&mut reader, "STYLES", "STYLE", style::read, &mut doc.styles)?;
             }
-            Event::Start(ref e) if e.name() == b"EQUIPMENTS" => {
-                let f = read_map(&mut reader, "EQUIPMENTS", "EQUIPMENT", equipment::read)?;
-                rs = RecordSet::Equipments(f);
-                // info!("Equipments: {:?}", f);
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == b"MASHS" => {
+                read_map(&mut reader, "MASHS", "MASH", mash::read, &mut doc.mashs)?;
             }
-            Event::Start(ref e) => {
-                read_ignore(&mut reader, e.name())?;
+            Event::Start(ref e) | Event::Empty(ref e) if e.name() == b"EQUIPMENTS" => {
+                read_map(&mut reader,
+                         "EQUIPMENTS",
+                         "EQUIPMENT",
+                         equipment::read,
+                         &mut doc.equipments)?;
             }
+            Event::Start(ref e) | Event::Empty(ref e) => read_unknown(&mut reader, e.name())?,
             Event::Eof => break,
             _ => (),
         }
         buf.clear();
     }
-    Ok(rs)
+    Ok((doc, reader.report, last))
 }
 
-fn read_ignore<B>(reader: &mut Reader<B>, name: &[u8]) -> Result<()>
-    where B: BufRead
-{
-    warn!("Ignoring: {}", str::from_utf8(name)?);
+/// skip an element that is not known where it was found; an error in strict mode
+fn read_unknown(reader: &mut XmlReader, name: &[u8]) -> Result<()> {
+    let start = reader.start;
+    if reader.options.strict {
+        return Err(reader.invalid(start, name, "unknown element"));
+    }
+    reader.report(IssueKind::Ignored, start, name, "unknown element ignored".into());
+    read_ignore(reader, name)?;
+    Ok(())
+}
+
+/// keep an element of a record that is not known, e.g. a vendor extension, as raw xml;
+/// an error in strict mode
+fn read_extension(reader: &mut XmlReader, name: &[u8], extensions: &mut Extensions) -> Result<()> {
+    if reader.options.strict {
+        return Err(reader.invalid(reader.start, name, "unknown element"));
+    }
+    let content = if reader.empty {
+        None
+    } else {
+        let start = reader.reader.buffer_position();
+        let end = read_ignore(reader, name)?;
+        Some(str::from_utf8(&reader.text[start..end.max(start)])?.trim().into())
+    };
+    let after = match reader.records.last_mut() {
+        Some(record) => {
+            record.unknown = true;
+            record.after.clone()
+        }
+        None => String::new(),
+    };
+    extensions.push(Extension {
+        tag: str::from_utf8(name)?.into(),
+        after,
+        content,
+    });
+    Ok(())
+}
+
+/// skip an element and everything in it; returns the offset of its end tag
+fn read_ignore(reader: &mut XmlReader, name: &[u8]) -> Result<usize> {
     let mut buf = vec![];
+    let mut depth = 0;
+    if reader.empty {
+        return Ok(reader.reader.buffer_position());
+    }
     loop {
         match reader.read_event(&mut buf)? {
-            Event::Start(ref e) => {
-                warn!("Ignoring: {}", str::from_utf8(e.name())?);
+            Event::Start(ref e) if e.name() == name => depth += 1,
+            Event::End(ref e) if e.name() == name => {
+                if depth == 0 {
+                    return Ok(reader.reader.buffer_position() - e.len() - 3);
+                }
+                depth -= 1;
             }
-            Event::End(ref e) if e.name() == name => break,
-            Event::Eof => break,
+            Event::Eof => return Ok(reader.reader.buffer_position()),
             _ => (),
         }
         buf.clear();
     }
-    Ok(())
 }
 
 /// try to read a `RecordSet` from a file
@@ -270,6 +632,32 @@ pub fn read_file(filename: &Path) -> Result<RecordSet> {
     read(reader)
 }
 
+/// try to read a `RecordSet` from a file using the given `ParseOptions`; also returns a report
+/// of what had to be worked around
+pub fn read_file_with(filename: &Path, options: &ParseOptions) -> Result<(RecordSet, ParseReport)> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read_with(reader, options)
+}
+
+/// try to read a `Document` from a file
+pub fn read_document_file(filename: &Path) -> Result<Document> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read_document(reader)
+}
+
+/// try to read a `Document` from a file using the given `ParseOptions`; also returns a report
+/// of what had to be worked around
+pub fn read_document_file_with(filename: &Path,
+                               options: &ParseOptions)
+                               -> Result<(Document, ParseReport)> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read_document_with(reader, options)
+}
+
+mod decode;
 mod equipment;
 mod fermentable;
 mod hop;
@@ -280,3 +668,178 @@ mod recipe;
 mod style;
 mod mash;
 mod mash_step;
+mod number;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use units::{Percent, Time};
+
+    #[test]
+    fn read_several_kinds() {
+        let xml = "<HOPS><HOP><NAME>Cascade</NAME></HOP></HOPS>\n\
+                   <YEASTS><YEAST><NAME>Nottingham</NAME></YEAST></YEASTS>";
+        let doc = read_document(xml.as_bytes()).unwrap();
+        assert!(doc.hops.contains_key("Cascade"));
+        assert!(doc.yeasts.contains_key("Nottingham"));
+        match read(xml.as_bytes()).unwrap() {
+            RecordSet::Yeasts(yeasts) => assert!(yeasts.contains_key("Nottingham")),
+            set => panic!("expected the yeasts read last, got {:?}", set),
+        }
+    }
+
+    #[test]
+    fn recipe_keeps_repeated_hops_in_order() {
+        let xml = "<RECIPES><RECIPE><NAME>IPA</NAME><HOPS>\
+                   <HOP><NAME>Cascade</NAME><TIME>60</TIME></HOP>\
+                   <HOP><NAME>Magnum</NAME><TIME>30</TIME></HOP>\
+                   <HOP><NAME>Cascade</NAME><TIME>0</TIME></HOP>\
+                   </HOPS></RECIPE></RECIPES>";
+        let doc = read_document(xml.as_bytes()).unwrap();
+        let recipe = &doc.recipes["IPA"];
+        let names: Vec<&str> = recipe.hops.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["Cascade", "Magnum", "Cascade"]);
+        assert_eq!(recipe.hop("Cascade").unwrap().time, Time(60.0));
+    }
+
+    #[test]
+    fn read_legacy_encoding() {
+        let xml = b"<HOPS><HOP><NAME>Saaz (\x8eatec)</NAME><ORIGIN>\x8aumava</ORIGIN></HOP></HOPS>";
+        let options = ParseOptions { legacy_encoding: "windows-1250".into(), ..Default::default() };
+        let (doc, report) = read_document_with(&xml[..], &options).unwrap();
+        assert_eq!(report.encoding, "windows-1250");
+        assert_eq!(doc.hops["Saaz (Žatec)"].origin.as_ref().unwrap(), "Šumava");
+        let (_, report) = read_with("<HOPS/>".as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(report.encoding, "UTF-8");
+    }
+
+    #[test]
+    fn strict_errors_have_a_location() {
+        let strict = ParseOptions { strict: true, ..Default::default() };
+        let xml = "<RECIPES>\n<RECIPE><NAME>IPA</NAME><HOPS>\n  <HOP><NAME>Cascade</NAME><ALPHA>high</ALPHA></HOP>\n</HOPS></RECIPE></RECIPES>";
+        for options in &[ParseOptions::default(), strict.clone()] {
+            match *read_document_with(xml.as_bytes(), options).unwrap_err().kind() {
+                ErrorKind::Invalid(ref location, _) => {
+                    assert_eq!(location.path, "RECIPE `IPA` / HOP `Cascade`");
+                    assert_eq!((location.kind.as_str(), location.name.as_str()), ("HOP", "Cascade"));
+                    assert_eq!((location.field.as_str(), location.line, location.column),
+                               ("ALPHA", 3, 28));
+                }
+                ref e => panic!("unexpected error {}", e),
+            }
+        }
+
+        let unknown = "<HOPS><HOP><NAME>Saaz</NAME><SMELL>spicy</SMELL></HOP></HOPS>";
+        assert!(read_document(unknown.as_bytes()).is_ok());
+        let e = read_document_with(unknown.as_bytes(), &strict).unwrap_err();
+        assert_eq!(e.to_string(),
+                   "line 1, column 29 in HOP `Saaz`, field SMELL: unknown element");
+        let flag = "<HOPS><HOP><NAME>Saaz</NAME><FLAG/></HOP><FLAG/></HOPS>";
+        let (doc, report) = read_document_with(flag.as_bytes(), &Default::default()).unwrap();
+        assert_eq!(doc.hops["Saaz"].extensions[0].tag, "FLAG");
+        assert_eq!(report.of_kind(IssueKind::Ignored).count(), 1);
+        let e = read_document_with(flag.as_bytes(), &strict).unwrap_err();
+        assert_eq!(e.to_string(),
+                   "line 1, column 29 in HOP `Saaz`, field FLAG: unknown element");
+
+        let missing = "<?xml version=\"1.0\"?>\n<YEASTS>\n<YEAST><NAME>US-05</NAME><VERSION>1</VERSION>\
+                       <TYPE>Ale</TYPE><FORM>Dry</FORM></YEAST></YEASTS>";
+        let e = read_document_with(missing.as_bytes(), &strict).unwrap_err();
+        assert_eq!(e.to_string(),
+                   "line 3, column 1 in YEAST `US-05`, field AMOUNT: missing required field");
+        for amount in &["<AMOUNT/>", "<AMOUNT>  </AMOUNT>"] {
+            let empty = missing.replace("</FORM>", &format!("</FORM>{}", amount));
+            let e = read_document_with(empty.as_bytes(), &strict).unwrap_err();
+            assert!(e.to_string().ends_with("field AMOUNT: missing required field"), "{}", e);
+            let (_, report) = read_document_with(empty.as_bytes(), &Default::default()).unwrap();
+            assert!(report.of_kind(IssueKind::Defaulted).any(|i| i.location.field == "AMOUNT"));
+        }
+
+        let optional = "<EQUIPMENTS><EQUIPMENT><NAME>Pot</NAME><EVAP_RATE>n/a</EVAP_RATE>\
+                        </EQUIPMENT></EQUIPMENTS>";
+        let doc = read_document(optional.as_bytes()).unwrap();
+        assert!(doc.equipments["Pot"].evap_rate.is_none());
+        assert!(read_document_with(optional.as_bytes(), &strict).is_err());
+    }
+
+    #[test]
+    fn report_what_was_worked_around() {
+        let xml = "<HOPS>\n<HOP><NAME>Saaz</NAME><ALPHA>3.5</ALPHA></HOP><SMELL>spicy</SMELL>\n\
+                   <HOP><NAME>Saaz</NAME><VERSION>1</VERSION><ALPHA>4</ALPHA><AMOUNT>0.1</AMOUNT>\
+                   <USE>Boil</USE><TIME>60</TIME></HOP></HOPS>\n\
+                   <EQUIPMENTS><EQUIPMENT><NAME>Pot</NAME><EVAP_RATE>n/a</EVAP_RATE>\
+                   <CALC_BOIL_VOLUME>yes</CALC_BOIL_VOLUME></EQUIPMENT></EQUIPMENTS>";
+        let (doc, report) = read_document_with(xml.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(doc.hops["Saaz"].alpha, Percent(4.0));
+        assert!(!report.is_clean());
+
+        let ignored: Vec<_> = report.of_kind(IssueKind::Ignored).collect();
+        assert_eq!(ignored.len(), 1);
+        assert_eq!(ignored[0].to_string(),
+                   "line 2, column 47, field SMELL: unknown element ignored");
+
+        let defaulted: Vec<_> = report.of_kind(IssueKind::Defaulted)
+            .map(|i| i.location.field.as_str())
+            .collect();
+        assert_eq!(defaulted, vec!["VERSION", "AMOUNT", "USE", "TIME", "VERSION", "BOIL_SIZE",
+                                   "BATCH_SIZE"]);
+
+        let duplicate: Vec<_> = report.of_kind(IssueKind::Duplicate).collect();
+        assert_eq!(duplicate.len(), 1);
+        assert_eq!((duplicate[0].location.path.as_str(), duplicate[0].location.line),
+                   ("HOP `Saaz`", 3));
+
+        let coerced: Vec<_> = report.of_kind(IssueKind::Coerced)
+            .map(|i| (i.location.path.as_str(), i.location.field.as_str()))
+            .collect();
+        assert_eq!(coerced,
+                   vec![("EQUIPMENT `Pot`", "EVAP_RATE"), ("EQUIPMENT `Pot`", "CALC_BOIL_VOLUME")]);
+    }
+
+    #[test]
+    fn lenient_enums() {
+        let xml = "<HOPS><HOP><NAME>Citra</NAME><USE>FWH</USE><FORM>pellet</FORM>\
+                   <TYPE>Cryo</TYPE></HOP></HOPS>";
+        let (doc, report) = read_document_with(xml.as_bytes(), &ParseOptions::default()).unwrap();
+        let hop = &doc.hops["Citra"];
+        assert_eq!(hop.use_.to_string(), "First Wort");
+        assert_eq!(hop.form.as_ref().unwrap().to_string(), "Pellet");
+        assert_eq!(hop.type_.as_ref().and_then(|t| t.unknown()), Some("Cryo"));
+        let coerced: Vec<_> = report.of_kind(IssueKind::Coerced).map(|i| &i.message).collect();
+        assert_eq!(coerced, vec!["read `FWH` as `First Wort`", "read `pellet` as `Pellet`"]);
+
+        let mut out = Vec::new();
+        ::xml::write_document(&mut out, &doc, &Default::default()).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("<TYPE>Cryo</TYPE>"));
+
+        let exact = ParseOptions { lenient_enums: false, ..Default::default() };
+        let (doc, _) = read_document_with(xml.as_bytes(), &exact).unwrap();
+        assert_eq!(doc.hops["Citra"].use_.unknown(), Some("FWH"));
+        // a whirlpool is not an aroma addition, so it is kept as read
+        let whirlpool = xml.replace("FWH", "Whirlpool");
+        let doc = read_document(whirlpool.as_bytes()).unwrap();
+        assert_eq!(doc.hops["Citra"].use_.unknown(), Some("Whirlpool"));
+
+        let known = ParseOptions { unknown_enums: false, ..Default::default() };
+        let e = read_document_with(xml.as_bytes(), &known).unwrap_err();
+        assert_eq!(e.to_string(),
+                   "line 1, column 63 in HOP `Citra`, field TYPE: Unable to parse `Cryo` as TYPE");
+    }
+
+    #[test]
+    fn localized_numbers() {
+        let xml = "<HOPS><HOP><NAME>Saaz</NAME><ALPHA>3,5 %</ALPHA><AMOUNT>0.05</AMOUNT>\
+                   <BETA>4,5</BETA></HOP></HOPS>";
+        let (doc, report) = read_document_with(xml.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!((doc.hops["Saaz"].alpha, doc.hops["Saaz"].beta),
+                   (Percent(3.5), Some(Percent(4.5))));
+        let coerced: Vec<_> = report.of_kind(IssueKind::Coerced).map(|i| i.to_string()).collect();
+        assert_eq!(coerced,
+                   vec!["line 1, column 29 in HOP `Saaz`, field ALPHA: read `3,5 %` as `3.5`",
+                        "line 1, column 70 in HOP `Saaz`, field BETA: read `4,5` as `4.5`"]);
+
+        let mut plain = ParseOptions::default();
+        plain.numbers.decimal_comma = false;
+        assert!(read_document_with(xml.as_bytes(), &plain).is_err());
+    }
+}
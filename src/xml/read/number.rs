@@ -0,0 +1,55 @@
+// (c) 2017 Joost Yervante Damad <joost@damad.be>
+
+use options::NumberOptions;
+
+/// rewrite a number as written by a spreadsheet or a localized program, e.g. `5,5 %`,
+/// into one `str::parse` understands, or `None` if there is nothing to rewrite
+pub fn normalize(value: &str, options: &NumberOptions) -> Option<String> {
+    let mut v = value;
+    if options.whitespace {
+        v = v.trim();
+    }
+    if options.percent {
+        if let Some(rest) = v.strip_suffix('%') {
+            v = if options.whitespace { rest.trim_end() } else { rest };
+        }
+    }
+    let mut res = v.to_string();
+    // a single comma is the decimal separator, any dots before it group the thousands
+    if options.decimal_comma && v.matches(',').count() == 1 {
+        let comma = v.find(',').unwrap();
+        if !v[comma..].contains('.') {
+            res = v.replace('.', "").replace(',', ".");
+        }
+    }
+    if res == value {
+        None
+    } else {
+        Some(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localized_numbers() {
+        let all = NumberOptions::default();
+        assert_eq!(normalize("1.050", &all), None);
+        assert_eq!(normalize("1,050", &all), Some("1.050".into()));
+        assert_eq!(normalize(" 5,5 % ", &all), Some("5.5".into()));
+        assert_eq!(normalize("1.234,5", &all), Some("1234.5".into()));
+        assert_eq!(normalize("1,234.5", &all), None);
+        assert_eq!(normalize("1,2,3", &all), None);
+
+        let none = NumberOptions {
+            decimal_comma: false,
+            percent: false,
+            whitespace: false,
+        };
+        assert_eq!(normalize(" 5,5 % ", &none), None);
+        let percent = NumberOptions { percent: true, ..none };
+        assert_eq!(normalize("12%", &percent), Some("12".into()));
+    }
+}
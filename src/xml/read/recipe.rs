@@ -1,43 +1,86 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::io::BufRead;
-use quick_xml::reader::Reader;
-
 use super::*;
 
-pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Recipe)>
-    where B: BufRead
-{
+/// the fields BeerXML requires
+const REQUIRED: &[&str] = &["NAME", "VERSION", "TYPE", "STYLE", "BREWER", "BATCH_SIZE",
+                            "BOIL_SIZE", "BOIL_TIME", "HOPS", "FERMENTABLES", "MISCS", "YEASTS",
+                            "WATERS", "MASH"];
+
+pub fn read(reader: &mut XmlReader) -> Result<(String, Recipe)> {
     let mut f = Recipe::default();
-    read_t(reader, b"RECIPE", |reader, name| {
+    let mut e = RecipeEstimates::default();
+    let mut d = RecipeDisplay::default();
+    read_t(reader, b"RECIPE", REQUIRED, |reader, name| {
             match name {
                 b"NAME" => f.name = read_value(reader, name)?,
                 b"VERSION" => f.version = read_value_t(reader, name)?,
-                b"TYPE" => f.type_ = read_value_t(reader, name)?,
+                b"TYPE" => f.type_ = read_enum(reader, name)?,
                 b"STYLE" => f.style = style::read(reader)?.1,
-                b"EQUIPMENT" => f.equipment = Some(equipment::read(reader)?.1),
+                b"EQUIPMENT" => f.equipment = read_record_o(reader, equipment::read)?,
                 b"BREWER" => f.brewer = read_value_t(reader, name)?,
                 b"ASST_BREWER" => f.asst_brewer = read_value_o(reader, name)?,
                 b"BATCH_SIZE" => f.batch_size = read_value_t(reader, name)?,
                 b"BOIL_SIZE" => f.boil_size = read_value_t(reader, name)?,
                 b"BOIL_TIME" => f.boil_time = read_value_t(reader, name)?,
                 b"EFFICIENCY" => f.efficiency = Some(read_value_t(reader, name)?),
-                b"MASH" => f.mash = Some(mash::read(reader)?.1),
-                b"HOPS" => f.hops = read_map(reader, "HOPS", "HOP", hop::read)?,
+                b"MASH" => f.mash = read_record_o(reader, mash::read)?,
+                b"HOPS" => f.hops = read_vec(reader, "HOPS", "HOP", hop::read)?,
                 b"FERMENTABLES" => {
                     f.fermentables =
-                        read_map(reader, "FERMENTABLES", "FERMENTABLE", fermentable::read)?
+                        read_vec(reader, "FERMENTABLES", "FERMENTABLE", fermentable::read)?
                 }
-                b"MISCS" => f.miscs = read_map(reader, "MISCS", "MISC", misc::read)?,
-                b"YEASTS" => f.yeasts = read_map(reader, "YEASTS", "YEAST", yeast::read)?,
-                b"WATERS" => f.waters = read_map(reader, "WATERS", "WATER", water::read)?,
+                b"MISCS" => f.miscs = read_vec(reader, "MISCS", "MISC", misc::read)?,
+                b"YEASTS" => f.yeasts = read_vec(reader, "YEASTS", "YEAST", yeast::read)?,
+                b"WATERS" => f.waters = read_vec(reader, "WATERS", "WATER", water::read)?,
                 b"NOTES" => f.notes = read_value_o(reader, name)?,
                 b"OG" => f.og = Some(read_value_t(reader, name)?),
                 b"FG" => f.fg = Some(read_value_t(reader, name)?),
-                _ => read_ignore(reader, name)?,
+                b"TASTE_NOTES" => f.taste_notes = read_value_o(reader, name)?,
+                b"TASTE_RATING" => f.taste_rating = read_value_t_o(reader, name)?,
+                b"FERMENTATION_STAGES" => f.fermentation_stages = read_value_t_o(reader, name)?,
+                b"PRIMARY_AGE" => f.primary_age = read_value_t_o(reader, name)?,
+                b"PRIMARY_TEMP" => f.primary_temp = read_value_t_o(reader, name)?,
+                b"SECONDARY_AGE" => f.secondary_age = read_value_t_o(reader, name)?,
+                b"SECONDARY_TEMP" => f.secondary_temp = read_value_t_o(reader, name)?,
+                b"TERTIARY_AGE" => f.tertiary_age = read_value_t_o(reader, name)?,
+                b"TERTIARY_TEMP" => f.tertiary_temp = read_value_t_o(reader, name)?,
+                b"AGE" => f.age = read_value_t_o(reader, name)?,
+                b"AGE_TEMP" => f.age_temp = read_value_t_o(reader, name)?,
+                b"DATE" => f.date = read_value_o(reader, name)?,
+                b"CARBONATION" => f.carbonation = read_value_t_o(reader, name)?,
+                b"FORCED_CARBONATION" => f.forced_carbonation = read_value_b_o(reader, name)?,
+                b"PRIMING_SUGAR_NAME" => f.priming_sugar_name = read_value_o(reader, name)?,
+                b"CARBONATION_TEMP" => f.carbonation_temp = read_value_t_o(reader, name)?,
+                b"PRIMING_SUGAR_EQUIV" => f.priming_sugar_equiv = read_value_t_o(reader, name)?,
+                b"KEG_PRIMING_FACTOR" => f.keg_priming_factor = read_value_t_o(reader, name)?,
+                b"EST_OG" => e.est_og = read_value_o(reader, name)?,
+                b"EST_FG" => e.est_fg = read_value_o(reader, name)?,
+                b"EST_COLOR" => e.est_color = read_value_o(reader, name)?,
+                b"IBU" => e.ibu = read_value_o(reader, name)?,
+                b"IBU_METHOD" => e.ibu_method = read_value_o(reader, name)?,
+                b"EST_ABV" => e.est_abv = read_value_o(reader, name)?,
+                b"ABV" => e.abv = read_value_o(reader, name)?,
+                b"ACTUAL_EFFICIENCY" => e.actual_efficiency = read_value_o(reader, name)?,
+                b"CALORIES" => e.calories = read_value_o(reader, name)?,
+                b"DISPLAY_BATCH_SIZE" => d.display_batch_size = read_value_o(reader, name)?,
+                b"DISPLAY_BOIL_SIZE" => d.display_boil_size = read_value_o(reader, name)?,
+                b"DISPLAY_OG" => d.display_og = read_value_o(reader, name)?,
+                b"DISPLAY_FG" => d.display_fg = read_value_o(reader, name)?,
+                b"DISPLAY_PRIMARY_TEMP" => d.display_primary_temp = read_value_o(reader, name)?,
+                b"DISPLAY_SECONDARY_TEMP" => {
+                    d.display_secondary_temp = read_value_o(reader, name)?
+                }
+                b"DISPLAY_TERTIARY_TEMP" => d.display_tertiary_temp = read_value_o(reader, name)?,
+                b"DISPLAY_AGE_TEMP" => d.display_age_temp = read_value_o(reader, name)?,
+                b"DISPLAY_CARB_TEMP" => d.display_carb_temp = read_value_o(reader, name)?,
+                b"CARBONATION_USED" => d.carbonation_used = read_value_o(reader, name)?,
+                _ => read_extension(reader, name, &mut f.extensions)?,
             }
             Ok(())
         })
         ?;
+    f.estimates = display_o(e);
+    f.display = display_o(d);
     Ok((f.name.clone(), f))
 }
@@ -1,17 +1,16 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::io::BufRead;
-use std::str;
-
-use quick_xml::reader::Reader;
-
 use super::*;
 
-pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Style)>
-    where B: BufRead
-{
+/// the fields BeerXML requires
+const REQUIRED: &[&str] = &["NAME", "VERSION", "CATEGORY", "CATEGORY_NUMBER", "STYLE_LETTER",
+                            "STYLE_GUIDE", "TYPE", "OG_MIN", "OG_MAX", "FG_MIN", "FG_MAX",
+                            "IBU_MIN", "IBU_MAX", "COLOR_MIN", "COLOR_MAX"];
+
+pub fn read(reader: &mut XmlReader) -> Result<(String, Style)> {
     let mut f = Style::default();
-    read_t(reader, b"STYLE", |reader, name| {
+    let mut d = StyleDisplay::default();
+    read_t(reader, b"STYLE", REQUIRED, |reader, name| {
             match name {
                 b"NAME" => f.name = read_value(reader, name)?,
                 b"VERSION" => f.version = read_value_t(reader, name)?,
@@ -19,13 +18,13 @@ pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Style)>
                 b"CATEGORY_NUMBER" => f.category_number = read_value(reader, name)?,
                 b"STYLE_LETTER" => f.style_letter = read_value(reader, name)?,
                 b"STYLE_GUIDE" => f.style_guide = read_value(reader, name)?,
-                b"TYPE" => f.type_ = read_value_t(reader, name)?,
+                b"TYPE" => f.type_ = read_enum(reader, name)?,
                 b"OG_MIN" => f.og_min = read_value_t(reader, name)?,
                 b"OG_MAX" => f.og_max = read_value_t(reader, name)?,
                 b"FG_MIN" => f.fg_min = read_value_t(reader, name)?,
                 b"FG_MAX" => f.fg_max = read_value_t(reader, name)?,
-                b"UBI_MIN" => f.ibu_min = read_value_t(reader, name)?,
-                b"UBI_MAX" => f.ibu_max = read_value_t(reader, name)?,
+                b"IBU_MIN" | b"UBI_MIN" => f.ibu_min = read_value_t(reader, name)?,
+                b"IBU_MAX" | b"UBI_MAX" => f.ibu_max = read_value_t(reader, name)?,
                 b"COLOR_MIN" => f.color_min = read_value_t(reader, name)?,
                 b"COLOR_MAX" => f.color_max = read_value_t(reader, name)?,
                 b"CARB_MIN" => f.carb_min = read_value_t_o(reader, name)?,
@@ -36,10 +35,23 @@ pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Style)>
                 b"PROFILE" => f.profile = read_value_o(reader, name)?,
                 b"INGREDIENTS" => f.ingredients = read_value_o(reader, name)?,
                 b"EXAMPLES" => f.examples = read_value_o(reader, name)?,
-                _ => warn!("Ignoring: {}", str::from_utf8(name)?),
+                b"DISPLAY_OG_MIN" => d.display_og_min = read_value_o(reader, name)?,
+                b"DISPLAY_OG_MAX" => d.display_og_max = read_value_o(reader, name)?,
+                b"DISPLAY_FG_MIN" => d.display_fg_min = read_value_o(reader, name)?,
+                b"DISPLAY_FG_MAX" => d.display_fg_max = read_value_o(reader, name)?,
+                b"DISPLAY_COLOR_MIN" => d.display_color_min = read_value_o(reader, name)?,
+                b"DISPLAY_COLOR_MAX" => d.display_color_max = read_value_o(reader, name)?,
+                b"OG_RANGE" => d.og_range = read_value_o(reader, name)?,
+                b"FG_RANGE" => d.fg_range = read_value_o(reader, name)?,
+                b"IBU_RANGE" => d.ibu_range = read_value_o(reader, name)?,
+                b"CARB_RANGE" => d.carb_range = read_value_o(reader, name)?,
+                b"COLOR_RANGE" => d.color_range = read_value_o(reader, name)?,
+                b"ABV_RANGE" => d.abv_range = read_value_o(reader, name)?,
+                _ => read_extension(reader, name, &mut f.extensions)?,
             }
             Ok(())
         })
         ?;
+    f.display = display_o(d);
     Ok((f.name.clone(), f))
 }
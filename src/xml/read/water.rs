@@ -1,17 +1,15 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::io::BufRead;
-use std::str;
-
-use quick_xml::reader::Reader;
-
 use super::*;
 
-pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Water)>
-    where B: BufRead
-{
+/// the fields BeerXML requires
+const REQUIRED: &[&str] = &["NAME", "VERSION", "AMOUNT", "CALCIUM", "BICARBONATE", "SULFATE",
+                            "CHLORIDE", "SODIUM", "MAGNESIUM"];
+
+pub fn read(reader: &mut XmlReader) -> Result<(String, Water)> {
     let mut f = Water::default();
-    read_t(reader, b"WATER", |reader, name| {
+    let mut d = WaterDisplay::default();
+    read_t(reader, b"WATER", REQUIRED, |reader, name| {
             match name {
                 b"NAME" => f.name = read_value(reader, name)?,
                 b"VERSION" => f.version = read_value_t(reader, name)?,
@@ -24,10 +22,12 @@ pub fn read<B>(reader: &mut Reader<B>) -> Result<(String, Water)>
                 b"MAGNESIUM" => f.magnesium = read_value_t(reader, name)?,
                 b"PH" => f.ph = Some(read_value_t(reader, name)?),
                 b"NOTES" => f.notes = read_value_o(reader, name)?,
-                _ => warn!("Ignoring: {}", str::from_utf8(name)?),
+                b"DISPLAY_AMOUNT" => d.display_amount = read_value_o(reader, name)?,
+                _ => read_extension(reader, name, &mut f.extensions)?,
             }
             Ok(())
         })
         ?;
+    f.display = display_o(d);
     Ok((f.name.clone(), f))
 }
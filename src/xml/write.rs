@@ -1,257 +1,912 @@
 // (c) 2017 Joost Yervante Damad <joost@damad.be>
 
-use std::collections::HashMap;
+use std::borrow::Cow;
 use std::io::Write;
 use std::fmt::Display;
 use std::fs::File;
 use std::path::Path;
+use std::str;
+
+use encoding_rs::{Encoding, UTF_8};
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::writer::Writer;
 
 use data::*;
 use error::*;
+use options::WriteOptions;
 
-fn indent<T>(writer: &mut T, offset: usize) -> Result<()>
-    where T: Write
-{
-    for _ in 0..offset {
-        writer.write_all(b"  ")?;
+/// escape the characters that are not allowed in XML text content
+fn escape<'a>(value: &'a str) -> Cow<'a, str> {
+    if !value.contains(&['&', '<', '>'][..]) {
+        return Cow::Borrowed(value);
     }
-    Ok(())
+    let mut res = String::with_capacity(value.len() + 8);
+    for c in value.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            c => res.push(c),
+        }
+    }
+    Cow::Owned(res)
 }
 
-fn write_tag<T, U>(writer: &mut T, offset: usize, tag: &'static str, value: &U) -> Result<()>
-    where T: Write,
-          U: Display
+/// XML event writer keeping track of nesting depth and the write options
+struct XmlWriter<'a, W>
+    where W: Write
 {
-    indent(writer, offset + 1)?;
-    write!(writer, "<{}>{}</{}>\n", tag, value, tag)?;
-    Ok(())
+    writer: Writer<W>,
+    options: &'a WriteOptions,
+    depth: usize,
+    /// extensions of the records being written that still have to be written
+    extensions: Vec<Extensions>,
 }
 
-fn write_bool<T>(writer: &mut T, offset: usize, tag: &'static str, value: bool) -> Result<()>
-    where T: Write
+impl<'a, W> XmlWriter<'a, W>
+    where W: Write
 {
-    if value {
-        indent(writer, offset + 1)?;
-        write!(writer, "<{}>{}</{}>\n", tag, value, tag)?;
+    fn new(writer: W, options: &'a WriteOptions) -> XmlWriter<'a, W> {
+        XmlWriter {
+            writer: Writer::new(writer),
+            options,
+            depth: 0,
+            extensions: vec![],
+        }
     }
-    Ok(())
-}
 
+    fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
 
+    fn event(&mut self, event: Event) -> Result<()> {
+        self.writer.write_event(event)?;
+        Ok(())
+    }
 
-fn write_opt<T, U>(writer: &mut T,
-                   offset: usize,
-                   tag: &'static str,
-                   value: &Option<U>)
-                   -> Result<()>
-    where T: Write,
-          U: Display
-{
-    if let Some(ref value) = *value {
-        indent(writer, offset + 1)?;
-        write!(writer, "<{}>{}</{}>\n", tag, value, tag)?;
+    fn newline(&mut self) -> Result<()> {
+        if self.options.indent > 0 {
+            self.writer.write(b"\n")?;
+        }
+        Ok(())
     }
-    Ok(())
-}
 
-fn write_block<F, T>(writer: &mut T, offset: usize, tag: &'static str, containing: F) -> Result<()>
-    where T: Write,
-          F: Fn(&mut T, usize) -> Result<()>
-{
-    indent(writer, offset)?;
-    write!(writer, "<{}>\n", tag)?;
-    containing(writer, offset + 1)?;
-    indent(writer, offset)?;
-    write!(writer, "</{}>\n", tag)?;
-    Ok(())
+    fn indent(&mut self) -> Result<()> {
+        let n = self.depth * self.options.indent;
+        if n > 0 {
+            self.writer.write(&vec![b' '; n])?;
+        }
+        Ok(())
+    }
+
+    fn header(&mut self) -> Result<()> {
+        if let Some(ref encoding) = self.options.encoding {
+            let decl = BytesDecl::new(b"1.0", Some(encoding.as_bytes()), None);
+            self.event(Event::Decl(decl))?;
+            self.newline()?;
+        }
+        let comment = format!(" written by brewcalc {}: http://brewcalc.org/ ",
+                              env!("CARGO_PKG_VERSION"));
+        self.event(Event::Comment(BytesText::borrowed(comment.as_bytes())))?;
+        self.newline()
+    }
+
+    fn block<F>(&mut self, tag: &'static str, containing: F) -> Result<()>
+        where F: FnOnce(&mut Self) -> Result<()>
+    {
+        self.indent()?;
+        self.event(Event::Start(BytesStart::borrowed(tag.as_bytes(), tag.len())))?;
+        self.newline()?;
+        self.depth += 1;
+        containing(self)?;
+        self.depth -= 1;
+        self.indent()?;
+        self.event(Event::End(BytesEnd::borrowed(tag.as_bytes())))?;
+        self.newline()?;
+        self.extensions_after(tag)
+    }
+
+    /// a record block with the elements it was read with that are not known, each after the
+    /// known element it came after, or at the end when that one is not written
+    fn record<F>(&mut self, tag: &'static str, extensions: &Extensions, containing: F) -> Result<()>
+        where F: FnOnce(&mut Self) -> Result<()>
+    {
+        self.block(tag, |w| {
+            w.extensions.push(extensions.clone());
+            w.extensions_after("")?;
+            containing(w)?;
+            for e in w.extensions.pop().unwrap_or_default() {
+                w.extension(&e)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// write the extensions of the current record that came after the element `tag`
+    fn extensions_after(&mut self, tag: &str) -> Result<()> {
+        let now: Extensions = match self.extensions.last_mut() {
+            Some(pending) if pending.iter().any(|e| e.after == tag) => {
+                let (now, later) = pending.drain(..).partition(|e| e.after == tag);
+                *pending = later;
+                now
+            }
+            _ => return Ok(()),
+        };
+        for e in &now {
+            self.extension(e)?;
+        }
+        Ok(())
+    }
+
+    fn extension(&mut self, e: &Extension) -> Result<()> {
+        let start = BytesStart::borrowed(e.tag.as_bytes(), e.tag.len());
+        self.indent()?;
+        match e.content {
+            Some(ref content) => {
+                self.event(Event::Start(start))?;
+                self.writer.write(content.as_bytes())?;
+                self.event(Event::End(BytesEnd::borrowed(e.tag.as_bytes())))?;
+            }
+            None => self.event(Event::Empty(start))?,
+        }
+        self.newline()
+    }
+
+    fn tag<U>(&mut self, tag: &'static str, value: &U) -> Result<()>
+        where U: Display
+    {
+        let value = value.to_string();
+        self.indent()?;
+        self.event(Event::Start(BytesStart::borrowed(tag.as_bytes(), tag.len())))?;
+        if self.options.cdata && value.contains('\n') && !value.contains("]]>") {
+            self.event(Event::CData(BytesText::borrowed(value.as_bytes())))?;
+        } else {
+            self.event(Event::Text(BytesText::borrowed(escape(&value).as_bytes())))?;
+        }
+        self.event(Event::End(BytesEnd::borrowed(tag.as_bytes())))?;
+        self.newline()?;
+        self.extensions_after(tag)
+    }
+
+    /// a boolean, written as BeerXML spells it: `TRUE` or `FALSE`
+    fn bool(&mut self, tag: &'static str, value: bool) -> Result<()> {
+        self.tag(tag, &if value { "TRUE" } else { "FALSE" })
+    }
+
+    fn bool_o(&mut self, tag: &'static str, value: Option<bool>) -> Result<()> {
+        match value {
+            Some(value) => self.bool(tag, value),
+            None => Ok(()),
+        }
+    }
+
+    fn opt<U>(&mut self, tag: &'static str, value: &Option<U>) -> Result<()>
+        where U: Display
+    {
+        if let Some(ref value) = *value {
+            self.tag(tag, value)?;
+        }
+        Ok(())
+    }
+
+    fn display<D, F>(&mut self, display: &Option<D>, containing: F) -> Result<()>
+        where F: FnOnce(&mut Self, &D) -> Result<()>
+    {
+        match *display {
+            Some(ref d) if self.options.display => containing(self, d),
+            _ => Ok(()),
+        }
+    }
+
+    fn list<E, F>(&mut self, tag: &'static str, v: &[E], write_element: F) -> Result<()>
+        where F: Fn(&mut Self, &E) -> Result<()>
+    {
+        self.block(tag, |w| {
+            for x in v {
+                write_element(w, x)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn map<E, F>(&mut self, tag: &'static str, v: &RecordMap<E>, write_element: F) -> Result<()>
+        where F: Fn(&mut Self, &E) -> Result<()>
+    {
+        self.block(tag, |w| {
+            for x in v.values() {
+                write_element(w, x)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn map_o<E, F>(&mut self, tag: &'static str, v: &RecordMap<E>, write_element: F) -> Result<()>
+        where F: Fn(&mut Self, &E) -> Result<()>
+    {
+        if v.is_empty() {
+            return Ok(());
+        }
+        self.map(tag, v, write_element)
+    }
 }
 
-fn write_fermentable<T>(writer: &mut T, f: &Fermentable, offset: usize) -> Result<()>
-    where T: Write
+fn write_fermentable<W>(w: &mut XmlWriter<W>, f: &Fermentable) -> Result<()>
+    where W: Write
 {
-    write_block(writer, offset, "FERMENTABLE", |writer, offset| {
-        write_tag(writer, offset, "NAME", &f.name)?;
-        write_tag(writer, offset, "VERSION", &f.version)?;
-        write_tag(writer, offset, "TYPE", &f.type_.to_string())?;
-        write_tag(writer, offset, "AMOUNT", &f.amount)?;
-        write_tag(writer, offset, "YIELD", &f.yield_)?;
-        write_tag(writer, offset, "COLOR", &f.color)?;
-        write_bool(writer, offset, "ADD_AFTER_BOIL", f.add_after_boil)?;
-        write_opt(writer, offset, "ORIGIN", &f.origin)?;
-        write_opt(writer, offset, "SUPPLIER", &f.supplier)?;
-        write_opt(writer, offset, "NOTES", &f.notes)?;
-        write_opt(writer, offset, "COARSE_FINE_DIFF", &f.coarse_fine_diff)?;
-        write_opt(writer, offset, "MOISTURE", &f.moisture)?;
-        write_opt(writer, offset, "DIASTATIC_POWER", &f.diastatic_power)?;
-        write_opt(writer, offset, "PROTEIN", &f.protein)?;
-        write_opt(writer, offset, "MAX_IN_BATCH", &f.max_in_batch)?;
-        write_bool(writer, offset, "RECOMMEND_MASH", f.recommend_mash)?;
-        write_opt(writer, offset, "IBU_GAL_PER_LB", &f.ibu_gal_per_lb)?;
-        write_opt(writer, offset, "DISPLAY_AMOUNT", &f.display_amount)?;
-        write_opt(writer, offset, "INVENTORY", &f.inventory)?;
-        write_opt(writer, offset, "POTENTIAL", &f.potential)?;
-        write_opt(writer, offset, "DISPLAY_COLOR", &f.display_color)
+    w.record("FERMENTABLE", &f.extensions, |w| {
+        w.tag("NAME", &f.name)?;
+        w.tag("VERSION", &f.version)?;
+        w.tag("TYPE", &f.type_.to_string())?;
+        w.tag("AMOUNT", &f.amount)?;
+        w.tag("YIELD", &f.yield_)?;
+        w.tag("COLOR", &f.color)?;
+        w.bool("ADD_AFTER_BOIL", f.add_after_boil)?;
+        w.opt("ORIGIN", &f.origin)?;
+        w.opt("SUPPLIER", &f.supplier)?;
+        w.opt("NOTES", &f.notes)?;
+        w.opt("COARSE_FINE_DIFF", &f.coarse_fine_diff)?;
+        w.opt("MOISTURE", &f.moisture)?;
+        w.opt("DIASTATIC_POWER", &f.diastatic_power)?;
+        w.opt("PROTEIN", &f.protein)?;
+        w.opt("MAX_IN_BATCH", &f.max_in_batch)?;
+        w.bool("RECOMMEND_MASH", f.recommend_mash)?;
+        w.opt("IBU_GAL_PER_LB", &f.ibu_gal_per_lb)?;
+        w.display(&f.display, |w, d| {
+            w.opt("DISPLAY_AMOUNT", &d.display_amount)?;
+            w.opt("POTENTIAL", &d.potential)?;
+            w.opt("INVENTORY", &d.inventory)?;
+            w.opt("DISPLAY_COLOR", &d.display_color)
+        })
     })
 }
 
-fn write_hop<T>(writer: &mut T, h: &Hop, offset: usize) -> Result<()>
-    where T: Write
+fn write_hop<W>(w: &mut XmlWriter<W>, h: &Hop) -> Result<()>
+    where W: Write
 {
-    write_block(writer, offset, "HOP", |writer, offset| {
-        write_tag(writer, offset, "NAME", &h.name)?;
-        write_tag(writer, offset, "VERSION", &h.version)?;
-        write_tag(writer, offset, "ALPHA", &h.alpha)?;
-        write_tag(writer, offset, "AMOUNT", &h.amount)?;
-        write_tag(writer, offset, "USE", &h.use_.to_string())?;
-        write_tag(writer, offset, "TIME", &h.time)?;
-        write_opt(writer, offset, "NOTES", &h.notes)?;
+    w.record("HOP", &h.extensions, |w| {
+        w.tag("NAME", &h.name)?;
+        w.tag("VERSION", &h.version)?;
+        w.tag("ALPHA", &h.alpha)?;
+        w.tag("AMOUNT", &h.amount)?;
+        w.tag("USE", &h.use_.to_string())?;
+        w.tag("TIME", &h.time)?;
+        w.opt("NOTES", &h.notes)?;
         let type_ = h.type_.as_ref().map(|x| x.to_string());
-        write_opt(writer, offset, "TYPE", &type_)?;
+        w.opt("TYPE", &type_)?;
         let form = h.form.as_ref().map(|x| x.to_string());
-        write_opt(writer, offset, "FORM", &form)?;
-        write_opt(writer, offset, "BETA", &h.beta)?;
-        write_opt(writer, offset, "HSI", &h.hsi)?;
-        write_opt(writer, offset, "ORIGIN", &h.origin)?;
-        write_opt(writer, offset, "SUBSTITUTES", &h.substitutes)?;
-        write_opt(writer, offset, "HUMULENE", &h.humulene)?;
-        write_opt(writer, offset, "CARYOPHYLLENE", &h.caryophyllene)?;
-        write_opt(writer, offset, "COHUMULONE", &h.cohumulone)?;
-        write_opt(writer, offset, "MYRCENE", &h.myrcene)
+        w.opt("FORM", &form)?;
+        w.opt("BETA", &h.beta)?;
+        w.opt("HSI", &h.hsi)?;
+        w.opt("ORIGIN", &h.origin)?;
+        w.opt("SUBSTITUTES", &h.substitutes)?;
+        w.opt("HUMULENE", &h.humulene)?;
+        w.opt("CARYOPHYLLENE", &h.caryophyllene)?;
+        w.opt("COHUMULONE", &h.cohumulone)?;
+        w.opt("MYRCENE", &h.myrcene)?;
+        w.display(&h.display, |w, d| {
+            w.opt("DISPLAY_AMOUNT", &d.display_amount)?;
+            w.opt("INVENTORY", &d.inventory)?;
+            w.opt("DISPLAY_TIME", &d.display_time)
+        })
     })
 }
 
-fn write_yeast<T>(writer: &mut T, y: &Yeast, offset: usize) -> Result<()>
-    where T: Write
+fn write_yeast<W>(w: &mut XmlWriter<W>, y: &Yeast) -> Result<()>
+    where W: Write
 {
-    write_block(writer, offset, "YEAST", |writer, offset| {
-        write_tag(writer, offset, "NAME", &y.name)?;
-        write_tag(writer, offset, "VERSION", &y.version)?;
-        write_tag(writer, offset, "TYPE", &y.type_.to_string())?;
-        write_tag(writer, offset, "FORM", &y.form.to_string())?;
-        write_tag(writer, offset, "AMOUNT", &y.amount)?;
-        write_bool(writer, offset, "AMOUNT_IS_WEIGHT", y.amount_is_weight)?;
-        write_opt(writer, offset, "LABORATORY", &y.laboratory)?;
-        write_opt(writer, offset, "PRODUCT_ID", &y.product_id)?;
-        write_opt(writer, offset, "MIN_TEMPERATURE", &y.min_temperature)?;
-        write_opt(writer, offset, "MAX_TEMPERATURE", &y.max_temperature)?;
+    w.record("YEAST", &y.extensions, |w| {
+        w.tag("NAME", &y.name)?;
+        w.tag("VERSION", &y.version)?;
+        w.tag("TYPE", &y.type_.to_string())?;
+        w.tag("FORM", &y.form.to_string())?;
+        w.tag("AMOUNT", &y.amount)?;
+        w.bool("AMOUNT_IS_WEIGHT", y.amount_is_weight)?;
+        w.opt("LABORATORY", &y.laboratory)?;
+        w.opt("PRODUCT_ID", &y.product_id)?;
+        w.opt("MIN_TEMPERATURE", &y.min_temperature)?;
+        w.opt("MAX_TEMPERATURE", &y.max_temperature)?;
         let floc = y.flocculation.as_ref().map(|x| x.to_string());
-        write_opt(writer, offset, "FLOCCULATION", &floc)?;
-        write_opt(writer, offset, "ATTENUATION", &y.attenuation)?;
-        write_opt(writer, offset, "NOTES", &y.notes)?;
-        write_opt(writer, offset, "BEST_FOR", &y.best_for)?;
-        write_opt(writer, offset, "TIMES_CULTURED", &y.times_cultured)?;
-        write_opt(writer, offset, "MAX_REUSE", &y.max_reuse)?;
-        write_bool(writer, offset, "ADD_TO_SECONDARY", y.add_to_secondary)?;
-        write_opt(writer, offset, "DISPLAY_AMOUNT", &y.display_amount)?;
-        write_opt(writer, offset, "DISP_MIN_TEMP", &y.display_min_temp)?;
-        write_opt(writer, offset, "DISP_MAX_TEMP", &y.display_max_temp)?;
-        write_opt(writer, offset, "INVENTORY", &y.inventory)?;
-        write_opt(writer, offset, "CULTURE_DATE", &y.culture_date)
+        w.opt("FLOCCULATION", &floc)?;
+        w.opt("ATTENUATION", &y.attenuation)?;
+        w.opt("NOTES", &y.notes)?;
+        w.opt("BEST_FOR", &y.best_for)?;
+        w.opt("TIMES_CULTURED", &y.times_cultured)?;
+        w.opt("MAX_REUSE", &y.max_reuse)?;
+        w.bool("ADD_TO_SECONDARY", y.add_to_secondary)?;
+        w.display(&y.display, |w, d| {
+            w.opt("DISPLAY_AMOUNT", &d.display_amount)?;
+            w.opt("DISP_MIN_TEMP", &d.display_min_temp)?;
+            w.opt("DISP_MAX_TEMP", &d.display_max_temp)?;
+            w.opt("INVENTORY", &d.inventory)?;
+            w.opt("CULTURE_DATE", &d.culture_date)
+        })
     })
 }
-fn write_misc<T>(writer: &mut T, m: &Misc, offset: usize) -> Result<()>
-    where T: Write
+
+fn write_misc<W>(w: &mut XmlWriter<W>, m: &Misc) -> Result<()>
+    where W: Write
 {
-    write_block(writer, offset, "MISC", |writer, offset| {
-        write_tag(writer, offset, "NAME", &m.name)?;
-        write_tag(writer, offset, "VERSION", &m.version)?;
-        write_tag(writer, offset, "TYPE", &m.type_.to_string())?;
-        write_tag(writer, offset, "USE", &m.use_.to_string())?;
-        write_tag(writer, offset, "TIME", &m.time)?;
-        write_tag(writer, offset, "AMOUNT", &m.amount)?;
-        write_bool(writer, offset, "AMOUNT_IS_WEIGHT", m.amount_is_weight)?;
-        write_opt(writer, offset, "USE_FOR", &m.use_for)?;
-        write_opt(writer, offset, "NOTES", &m.notes)?;
-        write_opt(writer, offset, "DISPLAY_TIME", &m.display_time)?;
-        write_opt(writer, offset, "DISPLAY_AMOUNT", &m.display_amount)?;
-        write_opt(writer, offset, "INVENTORY", &m.inventory)
+    w.record("MISC", &m.extensions, |w| {
+        w.tag("NAME", &m.name)?;
+        w.tag("VERSION", &m.version)?;
+        w.tag("TYPE", &m.type_.to_string())?;
+        w.tag("USE", &m.use_.to_string())?;
+        w.tag("TIME", &m.time)?;
+        w.tag("AMOUNT", &m.amount)?;
+        w.bool("AMOUNT_IS_WEIGHT", m.amount_is_weight)?;
+        w.opt("USE_FOR", &m.use_for)?;
+        w.opt("NOTES", &m.notes)?;
+        w.display(&m.display, |w, d| {
+            w.opt("DISPLAY_AMOUNT", &d.display_amount)?;
+            w.opt("INVENTORY", &d.inventory)?;
+            w.opt("DISPLAY_TIME", &d.display_time)
+        })
     })
 }
-fn write_water<T>(writer: &mut T, w: &Water, offset: usize) -> Result<()>
-    where T: Write
+
+fn write_water<W>(w: &mut XmlWriter<W>, water: &Water) -> Result<()>
+    where W: Write
 {
-    write_block(writer, offset, "WATER", |writer, offset| {
-        write_tag(writer, offset, "NAME", &w.name)?;
-        write_tag(writer, offset, "VERSION", &w.version)?;
-        write_tag(writer, offset, "AMOUNT", &w.amount)?;
-        write_tag(writer, offset, "CALCIUM", &w.calcium)?;
-        write_tag(writer, offset, "BICARBONATE", &w.bicarbonate)?;
-        write_tag(writer, offset, "SULFATE", &w.sulfate)?;
-        write_tag(writer, offset, "CHLORIDE", &w.chloride)?;
-        write_tag(writer, offset, "SODIUM", &w.sodium)?;
-        write_tag(writer, offset, "MAGNESIUM", &w.magnesium)?;
-        write_opt(writer, offset, "PH", &w.ph)?;
-        write_opt(writer, offset, "NOTES", &w.notes)
+    w.record("WATER", &water.extensions, |w| {
+        w.tag("NAME", &water.name)?;
+        w.tag("VERSION", &water.version)?;
+        w.tag("AMOUNT", &water.amount)?;
+        w.tag("CALCIUM", &water.calcium)?;
+        w.tag("BICARBONATE", &water.bicarbonate)?;
+        w.tag("SULFATE", &water.sulfate)?;
+        w.tag("CHLORIDE", &water.chloride)?;
+        w.tag("SODIUM", &water.sodium)?;
+        w.tag("MAGNESIUM", &water.magnesium)?;
+        w.opt("PH", &water.ph)?;
+        w.opt("NOTES", &water.notes)?;
+        w.display(&water.display, |w, d| {
+            w.opt("DISPLAY_AMOUNT", &d.display_amount)
+        })
     })
 }
 
-fn write_recipe<T>(_writer: &mut T, _r: &Recipe, _offset: usize) -> Result<()>
-    where T: Write
+fn write_recipe<W>(w: &mut XmlWriter<W>, r: &Recipe) -> Result<()>
+    where W: Write
 {
-    unimplemented!();
+    w.record("RECIPE", &r.extensions, |w| {
+        w.tag("NAME", &r.name)?;
+        w.tag("VERSION", &r.version)?;
+        w.tag("TYPE", &r.type_.to_string())?;
+        write_style(w, &r.style)?;
+        if let Some(ref e) = r.equipment {
+            write_equipment(w, e)?;
+        }
+        w.tag("BREWER", &r.brewer)?;
+        w.opt("ASST_BREWER", &r.asst_brewer)?;
+        w.tag("BATCH_SIZE", &r.batch_size)?;
+        w.tag("BOIL_SIZE", &r.boil_size)?;
+        w.tag("BOIL_TIME", &r.boil_time)?;
+        w.opt("EFFICIENCY", &r.efficiency)?;
+        w.list("HOPS", &r.hops, write_hop)?;
+        w.list("FERMENTABLES", &r.fermentables, write_fermentable)?;
+        w.list("MISCS", &r.miscs, write_misc)?;
+        w.list("YEASTS", &r.yeasts, write_yeast)?;
+        w.list("WATERS", &r.waters, write_water)?;
+        if let Some(ref m) = r.mash {
+            write_mash(w, m)?;
+        }
+        w.opt("NOTES", &r.notes)?;
+        w.opt("TASTE_NOTES", &r.taste_notes)?;
+        w.opt("TASTE_RATING", &r.taste_rating)?;
+        w.opt("OG", &r.og)?;
+        w.opt("FG", &r.fg)?;
+        w.opt("FERMENTATION_STAGES", &r.fermentation_stages)?;
+        w.opt("PRIMARY_AGE", &r.primary_age)?;
+        w.opt("PRIMARY_TEMP", &r.primary_temp)?;
+        w.opt("SECONDARY_AGE", &r.secondary_age)?;
+        w.opt("SECONDARY_TEMP", &r.secondary_temp)?;
+        w.opt("TERTIARY_AGE", &r.tertiary_age)?;
+        w.opt("TERTIARY_TEMP", &r.tertiary_temp)?;
+        w.opt("AGE", &r.age)?;
+        w.opt("AGE_TEMP", &r.age_temp)?;
+        w.opt("DATE", &r.date)?;
+        w.opt("CARBONATION", &r.carbonation)?;
+        w.bool_o("FORCED_CARBONATION", r.forced_carbonation)?;
+        w.opt("PRIMING_SUGAR_NAME", &r.priming_sugar_name)?;
+        w.opt("CARBONATION_TEMP", &r.carbonation_temp)?;
+        w.opt("PRIMING_SUGAR_EQUIV", &r.priming_sugar_equiv)?;
+        w.opt("KEG_PRIMING_FACTOR", &r.keg_priming_factor)?;
+        w.display(&r.estimates, |w, e| {
+            w.opt("EST_OG", &e.est_og)?;
+            w.opt("EST_FG", &e.est_fg)?;
+            w.opt("EST_COLOR", &e.est_color)?;
+            w.opt("IBU", &e.ibu)?;
+            w.opt("IBU_METHOD", &e.ibu_method)?;
+            w.opt("EST_ABV", &e.est_abv)?;
+            w.opt("ABV", &e.abv)?;
+            w.opt("ACTUAL_EFFICIENCY", &e.actual_efficiency)?;
+            w.opt("CALORIES", &e.calories)
+        })?;
+        w.display(&r.display, |w, d| {
+            w.opt("DISPLAY_BATCH_SIZE", &d.display_batch_size)?;
+            w.opt("DISPLAY_BOIL_SIZE", &d.display_boil_size)?;
+            w.opt("DISPLAY_OG", &d.display_og)?;
+            w.opt("DISPLAY_FG", &d.display_fg)?;
+            w.opt("DISPLAY_PRIMARY_TEMP", &d.display_primary_temp)?;
+            w.opt("DISPLAY_SECONDARY_TEMP", &d.display_secondary_temp)?;
+            w.opt("DISPLAY_TERTIARY_TEMP", &d.display_tertiary_temp)?;
+            w.opt("DISPLAY_AGE_TEMP", &d.display_age_temp)?;
+            w.opt("DISPLAY_CARB_TEMP", &d.display_carb_temp)?;
+            w.opt("CARBONATION_USED", &d.carbonation_used)
+        })
+    })
 }
 
-fn write_style<T>(_writer: &mut T, _s: &Style, _offset: usize) -> Result<()>
-    where T: Write
+fn write_style<W>(w: &mut XmlWriter<W>, s: &Style) -> Result<()>
+    where W: Write
 {
-    unimplemented!();
+    w.record("STYLE", &s.extensions, |w| {
+        w.tag("NAME", &s.name)?;
+        w.tag("VERSION", &s.version)?;
+        w.tag("CATEGORY", &s.category)?;
+        w.tag("CATEGORY_NUMBER", &s.category_number)?;
+        w.tag("STYLE_LETTER", &s.style_letter)?;
+        w.tag("STYLE_GUIDE", &s.style_guide)?;
+        w.tag("TYPE", &s.type_.to_string())?;
+        w.tag("OG_MIN", &s.og_min)?;
+        w.tag("OG_MAX", &s.og_max)?;
+        w.tag("FG_MIN", &s.fg_min)?;
+        w.tag("FG_MAX", &s.fg_max)?;
+        w.tag("IBU_MIN", &s.ibu_min)?;
+        w.tag("IBU_MAX", &s.ibu_max)?;
+        w.tag("COLOR_MIN", &s.color_min)?;
+        w.tag("COLOR_MAX", &s.color_max)?;
+        w.opt("CARB_MIN", &s.carb_min)?;
+        w.opt("CARB_MAX", &s.carb_max)?;
+        w.opt("ABV_MIN", &s.abv_min)?;
+        w.opt("ABV_MAX", &s.abv_max)?;
+        w.opt("NOTES", &s.notes)?;
+        w.opt("PROFILE", &s.profile)?;
+        w.opt("INGREDIENTS", &s.ingredients)?;
+        w.opt("EXAMPLES", &s.examples)?;
+        w.display(&s.display, |w, d| {
+            w.opt("DISPLAY_OG_MIN", &d.display_og_min)?;
+            w.opt("DISPLAY_OG_MAX", &d.display_og_max)?;
+            w.opt("DISPLAY_FG_MIN", &d.display_fg_min)?;
+            w.opt("DISPLAY_FG_MAX", &d.display_fg_max)?;
+            w.opt("DISPLAY_COLOR_MIN", &d.display_color_min)?;
+            w.opt("DISPLAY_COLOR_MAX", &d.display_color_max)?;
+            w.opt("OG_RANGE", &d.og_range)?;
+            w.opt("FG_RANGE", &d.fg_range)?;
+            w.opt("IBU_RANGE", &d.ibu_range)?;
+            w.opt("CARB_RANGE", &d.carb_range)?;
+            w.opt("COLOR_RANGE", &d.color_range)?;
+            w.opt("ABV_RANGE", &d.abv_range)
+        })
+    })
 }
 
-fn write_mash<T>(_writer: &mut T, _s: &Mash, _offset: usize) -> Result<()>
-    where T: Write
+fn write_mash_step<W>(w: &mut XmlWriter<W>, s: &MashStep) -> Result<()>
+    where W: Write
 {
-    unimplemented!();
+    w.record("MASH_STEP", &s.extensions, |w| {
+        w.tag("NAME", &s.name)?;
+        w.tag("VERSION", &s.version)?;
+        w.tag("TYPE", &s.type_.to_string())?;
+        w.opt("INFUSE_AMOUNT", &s.infuse_amount)?;
+        w.tag("STEP_TEMP", &s.step_temp)?;
+        w.tag("STEP_TIME", &s.step_time)?;
+        w.opt("RAMP_TIME", &s.ramp_time)?;
+        w.opt("END_TEMP", &s.end_temp)?;
+        w.display(&s.display, |w, d| {
+            w.opt("DISPLAY_STEP_TEMP", &d.display_step_temp)?;
+            w.opt("DISPLAY_INFUSE_AMT", &d.display_infuse_amt)?;
+            w.opt("DESCRIPTION", &d.description)?;
+            w.opt("WATER_GRAIN_RATIO", &d.water_grain_ratio)?;
+            w.opt("DECOCTION_AMT", &d.decoction_amt)?;
+            w.opt("INFUSE_TEMP", &d.infuse_temp)
+        })
+    })
 }
 
-fn write_equipment<T>(_writer: &mut T, _s: &Equipment, _offset: usize) -> Result<()>
-    where T: Write
+fn write_mash<W>(w: &mut XmlWriter<W>, m: &Mash) -> Result<()>
+    where W: Write
+{
+    w.record("MASH", &m.extensions, |w| {
+        w.tag("NAME", &m.name)?;
+        w.tag("VERSION", &m.version)?;
+        w.tag("GRAIN_TEMP", &m.grain_temp)?;
+        w.list("MASH_STEPS", &m.mash_steps, write_mash_step)?;
+        w.opt("NOTES", &m.notes)?;
+        w.opt("TUN_TEMP", &m.tun_temp)?;
+        w.opt("SPARGE_TEMP", &m.sparge_temp)?;
+        w.opt("PH", &m.ph)?;
+        w.opt("TUN_WEIGHT", &m.tun_weight)?;
+        w.opt("TUN_SPECIFIC_HEAT", &m.tun_specific_heat)?;
+        w.bool_o("EQUIP_ADJUST", m.equip_adjust)?;
+        w.display(&m.display, |w, d| {
+            w.opt("DISPLAY_GRAIN_TEMP", &d.display_grain_temp)?;
+            w.opt("DISPLAY_TUN_TEMP", &d.display_tun_temp)?;
+            w.opt("DISPLAY_SPARGE_TEMP", &d.display_sparge_temp)?;
+            w.opt("DISPLAY_TUN_WEIGHT", &d.display_tun_weight)
+        })
+    })
+}
+
+fn write_equipment<W>(w: &mut XmlWriter<W>, e: &Equipment) -> Result<()>
+    where W: Write
 {
-    unimplemented!();
+    w.record("EQUIPMENT", &e.extensions, |w| {
+        w.tag("NAME", &e.name)?;
+        w.tag("VERSION", &e.version)?;
+        w.tag("BOIL_SIZE", &e.boil_size)?;
+        w.tag("BATCH_SIZE", &e.batch_size)?;
+        w.opt("TUN_VOLUME", &e.tun_volume)?;
+        w.opt("TUN_WEIGHT", &e.tun_weight)?;
+        w.opt("TUN_SPECIFIC_HEAT", &e.tun_specific_heat)?;
+        w.opt("TOP_UP_WATER", &e.top_up_water)?;
+        w.opt("TRUB_CHILLER_LOSS", &e.trub_chiller_loss)?;
+        w.opt("EVAP_RATE", &e.evap_rate)?;
+        w.opt("BOIL_TIME", &e.boil_time)?;
+        w.bool_o("CALC_BOIL_VOLUME", e.calc_boil_volume)?;
+        w.opt("LAUTER_DEADSPACE", &e.lauter_deadspace)?;
+        w.opt("TOP_UP_KETTLE", &e.top_up_kettle)?;
+        w.opt("HOP_UTILIZATION", &e.hop_utilization)?;
+        w.opt("NOTES", &e.notes)?;
+        w.display(&e.display, |w, d| {
+            w.opt("DISPLAY_BOIL_SIZE", &d.display_boil_size)?;
+            w.opt("DISPLAY_BATCH_SIZE", &d.display_batch_size)?;
+            w.opt("DISPLAY_TUN_VOLUME", &d.display_tun_volume)?;
+            w.opt("DISPLAY_TUN_WEIGHT", &d.display_tun_weight)?;
+            w.opt("DISPLAY_TOP_UP_WATER", &d.display_top_up_water)?;
+            w.opt("DISPLAY_TRUB_CHILLER_LOSS", &d.display_trub_chiller_loss)?;
+            w.opt("DISPLAY_LAUTER_DEADSPACE", &d.display_lauter_deadspace)?;
+            w.opt("DISPLAY_TOP_UP_KETTLE", &d.display_top_up_kettle)
+        })
+    })
 }
 
-fn write_map<E, F, T>(writer: &mut T,
-                      v: &HashMap<String, E>,
-                      offset: usize,
-                      name: &'static str,
-                      write_element: F)
-                      -> Result<()>
+/// write the XML to a buffer, then encode it as requested by the options
+fn write_encoded<T, F>(writer: &mut T, options: &WriteOptions, containing: F) -> Result<()>
     where T: Write,
-          F: Fn(&mut T, &E, usize) -> Result<()>
+          F: FnOnce(&mut XmlWriter<Vec<u8>>) -> Result<()>
 {
-    write_block(writer, offset, name, |writer, offset| {
-        for f in v.values() {
-            write_element(writer, f, offset + 1)?;
+    let encoding = match options.encoding {
+        Some(ref label) => {
+            Encoding::for_label(label.as_bytes())
+                .ok_or_else(|| format!("Unknown encoding: {}", label))?
         }
-        Ok(())
-    })
+        None => UTF_8,
+    };
+    // encoding_rs only encodes to ASCII compatible encodings, it writes UTF-8 for the others
+    if encoding.output_encoding() != encoding {
+        return Err(format!("Can not write XML in encoding {}", encoding.name()).into());
+    }
+    let mut w = XmlWriter::new(vec![], options);
+    w.header()?;
+    containing(&mut w)?;
+    let buf = w.into_inner();
+    if encoding == UTF_8 {
+        writer.write_all(&buf)?;
+    } else {
+        let (encoded, _, _) = encoding.encode(str::from_utf8(&buf)?);
+        writer.write_all(&encoded)?;
+    }
+    Ok(())
 }
 
 /// try to write a `RecordSet` to a `writer`
 pub fn write<T>(writer: &mut T, set: &RecordSet) -> Result<()>
     where T: Write
 {
-    writer.write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")?;
-    write!(writer,
-           "<!-- written by brewcalc {}: http://brewcalc.org/ -->\n",
-           env!("CARGO_PKG_VERSION"))
-        ?;
-    match *set {
+    write_with(writer, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` to a `writer` using the given `WriteOptions`
+pub fn write_with<T>(writer: &mut T, set: &RecordSet, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    let set = options.prepare_set(set);
+    write_encoded(writer, options, |w| match *set {
         RecordSet::Empty => Ok(()),
-        RecordSet::Equipments(ref v) => write_map(writer, v, 0, "EQUIPMENTS", write_equipment),
-        RecordSet::Fermentables(ref v) => write_map(writer, v, 0, "FERMENTABLES", write_fermentable),
-        
-        RecordSet::Hops(ref v) => write_map(writer, v, 0, "HOPS", write_hop),
-        RecordSet::Yeasts(ref v) => write_map(writer, v, 0, "YEASTS", write_yeast),
-        RecordSet::Miscs(ref v) => write_map(writer, v, 0, "MISCS", write_misc),
-        RecordSet::Recipes(ref v) => write_map(writer, v, 0, "RECIPES", write_recipe),
-        RecordSet::Waters(ref v) => write_map(writer, v, 0, "WATERS", write_water),
-        RecordSet::Styles(ref v) => write_map(writer, v, 0, "STYLES", write_style),
-        RecordSet::Mashs(ref v) => write_map(writer, v, 0, "MASHS", write_mash),
-    }
+        RecordSet::Equipments(ref v) => w.map("EQUIPMENTS", v, write_equipment),
+        RecordSet::Fermentables(ref v) => w.map("FERMENTABLES", v, write_fermentable),
+        RecordSet::Hops(ref v) => w.map("HOPS", v, write_hop),
+        RecordSet::Yeasts(ref v) => w.map("YEASTS", v, write_yeast),
+        RecordSet::Miscs(ref v) => w.map("MISCS", v, write_misc),
+        RecordSet::Recipes(ref v) => w.map("RECIPES", v, write_recipe),
+        RecordSet::Waters(ref v) => w.map("WATERS", v, write_water),
+        RecordSet::Styles(ref v) => w.map("STYLES", v, write_style),
+        RecordSet::Mashs(ref v) => w.map("MASHS", v, write_mash),
+    })
+}
+
+/// try to write a `Document` to a `writer`; every non-empty record kind is written as a separate top-level list
+pub fn write_document<T>(writer: &mut T, doc: &Document, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    let doc = options.prepare_document(doc);
+    write_encoded(writer, options, |w| {
+        w.map_o("EQUIPMENTS", &doc.equipments, write_equipment)?;
+        w.map_o("FERMENTABLES", &doc.fermentables, write_fermentable)?;
+        w.map_o("HOPS", &doc.hops, write_hop)?;
+        w.map_o("YEASTS", &doc.yeasts, write_yeast)?;
+        w.map_o("MISCS", &doc.miscs, write_misc)?;
+        w.map_o("WATERS", &doc.waters, write_water)?;
+        w.map_o("STYLES", &doc.styles, write_style)?;
+        w.map_o("MASHS", &doc.mashs, write_mash)?;
+        w.map_o("RECIPES", &doc.recipes, write_recipe)
+    })
 }
 
 /// try to write a `RecordSet` to a file
 pub fn write_file(filename: &Path, set: &RecordSet) -> Result<()> {
+    write_file_with(filename, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` to a file using the given `WriteOptions`
+pub fn write_file_with(filename: &Path, set: &RecordSet, options: &WriteOptions) -> Result<()> {
     let mut f = File::create(filename)?;
-    write(&mut f, set)
+    write_with(&mut f, set, options)
+}
+
+/// try to write a `Document` to a file
+pub fn write_document_file(filename: &Path, doc: &Document, options: &WriteOptions) -> Result<()> {
+    let mut f = File::create(filename)?;
+    write_document(&mut f, doc, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json;
+
+    use super::*;
+    use units::{Mass, System};
+    use xml::read;
+
+    fn round_trip(doc: &Document, options: &WriteOptions) -> Document {
+        let mut buf = vec![];
+        write_document(&mut buf, doc, options).unwrap();
+        read::read_document(&buf[..]).unwrap()
+    }
+
+    /// read `xml`, write it and read that back, which must give the same records
+    fn xml_round_trip(xml: &str) -> (Document, String) {
+        let doc = read::read_document(xml.as_bytes()).unwrap();
+        let mut buf = vec![];
+        write_document(&mut buf, &doc, &WriteOptions::default()).unwrap();
+        let back = read::read_document(&buf[..]).unwrap();
+        assert_eq!(serde_json::to_string(&back).unwrap(),
+                   serde_json::to_string(&doc).unwrap());
+        (doc, String::from_utf8(buf).unwrap())
+    }
+
+    fn hop(notes: &str) -> Document {
+        let hop = Hop {
+            name: "Saaz (Žatec) & Co".into(),
+            notes: Some(notes.into()),
+            ..Hop::default()
+        };
+        Document::from(RecordSet::Hops(vec![(hop.name.clone(), hop)].into_iter().collect()))
+    }
+
+    #[test]
+    fn escapes_text() {
+        let doc = hop("Malt & Hops\n<10 IBU");
+        let mut buf = vec![];
+        write_document(&mut buf, &doc, &WriteOptions::default()).unwrap();
+        let xml = String::from_utf8(buf).unwrap();
+        assert!(xml.contains("<NOTES>Malt &amp; Hops\n&lt;10 IBU</NOTES>"));
+        let back = round_trip(&doc, &WriteOptions::default());
+        assert_eq!(back.hops["Saaz (Žatec) & Co"].notes,
+                   Some("Malt & Hops\n<10 IBU".into()));
+    }
+
+    #[test]
+    fn cdata_indent_and_encoding() {
+        let doc = hop("Malt & Hops\n<10 IBU");
+        let options = WriteOptions {
+            indent: 0,
+            cdata: true,
+            encoding: Some("ISO-8859-1".into()),
+            ..WriteOptions::default()
+        };
+        let mut buf = vec![];
+        write_document(&mut buf, &doc, &options).unwrap();
+        assert!(buf.starts_with(b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><!--"));
+        assert!(!buf.windows(3).any(|w| w == b">\n<"));
+        assert!(buf.windows(20).any(|w| w == b"<![CDATA[Malt & Hops"));
+        let back = round_trip(&doc, &options);
+        assert_eq!(serde_json::to_string(&back).unwrap(),
+                   serde_json::to_string(&doc).unwrap());
+
+        for label in &["UTF-16", "UTF-16BE", "no such encoding"] {
+            let options = WriteOptions {
+                encoding: Some(label.to_string()),
+                ..WriteOptions::default()
+            };
+            assert!(write_document(&mut vec![], &doc, &options).is_err(), "{}", label);
+        }
+    }
+
+    #[test]
+    fn writes_booleans_in_capitals() {
+        let xml = "<FERMENTABLES><FERMENTABLE><NAME>Pils</NAME><VERSION>1</VERSION>\
+                   <TYPE>Grain</TYPE><AMOUNT>5</AMOUNT><YIELD>80</YIELD><COLOR>2</COLOR>\
+                   <RECOMMEND_MASH>true</RECOMMEND_MASH></FERMENTABLE></FERMENTABLES>";
+        let (_, written) = xml_round_trip(xml);
+        assert!(written.contains("<ADD_AFTER_BOIL>FALSE</ADD_AFTER_BOIL>"));
+        assert!(written.contains("<RECOMMEND_MASH>TRUE</RECOMMEND_MASH>"));
+
+        let xml = "<EQUIPMENTS><EQUIPMENT><NAME>Pot</NAME><VERSION>1</VERSION>\
+                   <BOIL_SIZE>26</BOIL_SIZE><BATCH_SIZE>20</BATCH_SIZE>\
+                   <CALC_BOIL_VOLUME>true</CALC_BOIL_VOLUME></EQUIPMENT></EQUIPMENTS>";
+        let (_, written) = xml_round_trip(xml);
+        assert!(written.contains("<CALC_BOIL_VOLUME>TRUE</CALC_BOIL_VOLUME>"));
+
+        let xml = "<MASHS><MASH><NAME>Single</NAME><VERSION>1</VERSION>\
+                   <GRAIN_TEMP>20</GRAIN_TEMP><MASH_STEPS/>\
+                   <EQUIP_ADJUST>false</EQUIP_ADJUST></MASH></MASHS>";
+        let (_, written) = xml_round_trip(xml);
+        assert!(written.contains("<EQUIP_ADJUST>FALSE</EQUIP_ADJUST>"));
+    }
+
+    #[test]
+    fn generates_display_fields() {
+        let mut doc = hop("");
+        doc.hops.get_mut("Saaz (Žatec) & Co").unwrap().amount = Mass::from_ounces(1.5);
+        let options = WriteOptions {
+            units: Some(System::Us),
+            ..WriteOptions::default()
+        };
+        let mut buf = vec![];
+        write_document(&mut buf, &doc, &options).unwrap();
+        let written = String::from_utf8(buf).unwrap();
+        assert!(written.contains("<DISPLAY_AMOUNT>1.50 oz</DISPLAY_AMOUNT>"));
+        assert!(written.contains("<DISPLAY_TIME>0 min</DISPLAY_TIME>"));
+    }
+
+    #[test]
+    fn display_switch() {
+        let xml = "<HOPS><HOP><NAME>Fuggles</NAME><VERSION>1</VERSION><ALPHA>4.5</ALPHA>\
+                   <AMOUNT>0.03</AMOUNT><USE>Boil</USE><TIME>60</TIME>\
+                   <DISPLAY_AMOUNT>30 g</DISPLAY_AMOUNT><INVENTORY>1 kg</INVENTORY>\
+                   <DISPLAY_TIME>60 min</DISPLAY_TIME></HOP></HOPS>\
+                   <RECIPES><RECIPE><NAME>Best</NAME><CARBONATION_TEMP>4.4</CARBONATION_TEMP>\
+                   <DISPLAY_CARB_TEMP>40 F</DISPLAY_CARB_TEMP>\
+                   <CARBONATION_USED>Kegged at 20psi</CARBONATION_USED></RECIPE></RECIPES>";
+        let doc = read::read_document(xml.as_bytes()).unwrap();
+        let display = doc.recipes["Best"].display.clone().unwrap();
+        assert_eq!(display.display_carb_temp.unwrap(), "40 F");
+        assert_eq!(display.carbonation_used.unwrap(), "Kegged at 20psi");
+        let write = |display| {
+            let mut buf = vec![];
+            let options = WriteOptions {
+                display,
+                ..WriteOptions::default()
+            };
+            write_document(&mut buf, &doc, &options).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+        let on = write(true);
+        let off = write(false);
+        for tag in &["<DISPLAY_AMOUNT>30 g<", "<INVENTORY>1 kg<", "<DISPLAY_TIME>60 min<",
+                     "<DISPLAY_CARB_TEMP>40 F<", "<CARBONATION_USED>Kegged at 20psi<"] {
+            assert!(on.contains(tag), "{}", tag);
+        }
+        assert!(!off.contains("DISPLAY_") && !off.contains("INVENTORY"));
+        assert!(!off.contains("CARBONATION_USED"));
+        assert!(off.contains("<AMOUNT>0.03</AMOUNT>"));
+    }
+
+    #[test]
+    fn writes_recipes_styles_mashes_and_equipment() {
+        let style = "<STYLE><NAME>Bitter</NAME><VERSION>1</VERSION><CATEGORY>English Pale Ale\
+                     </CATEGORY><TYPE>Ale</TYPE><OG_MIN>1.032</OG_MIN><OG_MAX>1.040</OG_MAX>\
+                     <UBI_MIN>25</UBI_MIN><UBI_MAX>35</UBI_MAX></STYLE>";
+        let equipment = "<EQUIPMENT><NAME>Pot</NAME><VERSION>1</VERSION>\
+                         <BOIL_SIZE>26</BOIL_SIZE><BATCH_SIZE>20</BATCH_SIZE></EQUIPMENT>";
+        let mash = "<MASH><NAME>Single</NAME><VERSION>1</VERSION><GRAIN_TEMP>20</GRAIN_TEMP>\
+                    <MASH_STEPS><MASH_STEP><NAME>Sacch</NAME><VERSION>1</VERSION>\
+                    <TYPE>Infusion</TYPE><STEP_TEMP>66</STEP_TEMP><STEP_TIME>60</STEP_TIME>\
+                    </MASH_STEP></MASH_STEPS></MASH>";
+        let xml = format!("<STYLES>{s}</STYLES><EQUIPMENTS>{e}</EQUIPMENTS><MASHS>{m}</MASHS>\
+                           <RECIPES><RECIPE><NAME>Best</NAME><VERSION>1</VERSION>\
+                           <TYPE>All Grain</TYPE>{s}{e}<BREWER>Joost</BREWER>\
+                           <BATCH_SIZE>20</BATCH_SIZE><BOIL_SIZE>26</BOIL_SIZE>\
+                           <BOIL_TIME>60</BOIL_TIME><HOPS><HOP><NAME>Fuggles</NAME>\
+                           <ALPHA>4.5</ALPHA><AMOUNT>0.03</AMOUNT><USE>Boil</USE>\
+                           <TIME>60</TIME></HOP></HOPS>{m}</RECIPE></RECIPES>",
+                          s = style,
+                          e = equipment,
+                          m = mash);
+        let (doc, written) = xml_round_trip(&xml);
+        assert_eq!(doc.styles["Bitter"].ibu_min, 25.0);
+        assert!(written.contains("<IBU_MIN>25</IBU_MIN>") && !written.contains("UBI_MIN"));
+        for tag in &["<STYLES>", "<EQUIPMENTS>", "<MASHS>", "<MASH_STEP>", "<RECIPE>"] {
+            assert!(written.contains(tag), "{}", tag);
+        }
+        let recipe = &doc.recipes["Best"];
+        assert_eq!(recipe.style.name, "Bitter");
+        assert_eq!(recipe.equipment.as_ref().unwrap().name, "Pot");
+        assert_eq!(recipe.mash.as_ref().unwrap().mash_steps[0].name, "Sacch");
+        assert_eq!(recipe.hops[0].name, "Fuggles");
+    }
+
+    #[test]
+    fn writes_equipment_losses_and_mash_step_details() {
+        let xml = "<EQUIPMENTS><EQUIPMENT><NAME>Pot</NAME><VERSION>1</VERSION>\
+                   <BOIL_SIZE>26</BOIL_SIZE><BATCH_SIZE>20</BATCH_SIZE>\
+                   <TRUB_CHILLER_LOSS>1.5</TRUB_CHILLER_LOSS>\
+                   <HOP_UTILIZATION>110</HOP_UTILIZATION></EQUIPMENT></EQUIPMENTS>\
+                   <MASHS><MASH><NAME>Decoction</NAME><VERSION>1</VERSION>\
+                   <GRAIN_TEMP>20</GRAIN_TEMP><MASH_STEPS><MASH_STEP><NAME>Rest</NAME>\
+                   <VERSION>1</VERSION><TYPE>Decoction</TYPE><STEP_TEMP>64</STEP_TEMP>\
+                   <STEP_TIME>30</STEP_TIME><DESCRIPTION>Pull a third</DESCRIPTION>\
+                   <WATER_GRAIN_RATIO>3 qt/lb</WATER_GRAIN_RATIO>\
+                   <DECOCTION_AMT>7.5 l</DECOCTION_AMT><INFUSE_TEMP>74 C</INFUSE_TEMP>\
+                   </MASH_STEP></MASH_STEPS></MASH></MASHS>";
+        let (doc, written) = xml_round_trip(xml);
+        let pot = &doc.equipments["Pot"];
+        assert_eq!(pot.trub_chiller_loss.map(f64::from), Some(1.5));
+        assert_eq!(pot.hop_utilization.map(f64::from), Some(110.0));
+        let step = &doc.mashs["Decoction"].mash_steps[0];
+        let d = step.display.as_ref().unwrap();
+        assert_eq!(d.decoction_amt, Some("7.5 l".into()));
+        assert_eq!(d.infuse_temp, Some("74 C".into()));
+        for tag in &["<TRUB_CHILLER_LOSS>1.5<", "<HOP_UTILIZATION>110<", "<WATER_GRAIN_RATIO>"] {
+            assert!(written.contains(tag), "{}", tag);
+        }
+    }
+
+    #[test]
+    fn round_trip_data_files() {
+        for entry in fs::read_dir("data").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("xml") {
+                continue;
+            }
+            let doc = read::read_document_file(&path).unwrap();
+            let back = round_trip(&doc, &WriteOptions::default());
+            assert_eq!(serde_json::to_string(&back).unwrap(),
+                       serde_json::to_string(&doc).unwrap(),
+                       "{}",
+                       path.display());
+        }
+    }
+
+    #[test]
+    fn keeps_extensions() {
+        let xml = "<HOPS><HOP><VENDOR_ID>1</VENDOR_ID><NAME>Saaz</NAME>\
+                   <BT_INVENTORY>0.5</BT_INVENTORY><ALPHA>3.5</ALPHA>\
+                   <BT_INVENTORY>0.7</BT_INVENTORY><PROMASH><AA>3.5</AA><AA>4</AA></PROMASH>\
+                   <VENDOR_FLAG/></HOP></HOPS>";
+        let doc = read::read_document(xml.as_bytes()).unwrap();
+        let extensions = &doc.hops["Saaz"].extensions;
+        let tags: Vec<(&str, &str)> =
+            extensions.iter().map(|e| (e.tag.as_str(), e.after.as_str())).collect();
+        assert_eq!(tags,
+                   [("VENDOR_ID", ""),
+                    ("BT_INVENTORY", "NAME"),
+                    ("BT_INVENTORY", "ALPHA"),
+                    ("PROMASH", "ALPHA"),
+                    ("VENDOR_FLAG", "ALPHA")]);
+        assert_eq!(extensions[3].content, Some("<AA>3.5</AA><AA>4</AA>".into()));
+        assert_eq!(extensions[4].content, None);
+
+        let mut buf = vec![];
+        write_document(&mut buf, &doc, &WriteOptions::default()).unwrap();
+        let written: String = String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(str::trim)
+            .collect();
+        assert!(written.contains("<HOP><VENDOR_ID>1</VENDOR_ID><NAME>Saaz</NAME>\
+                                  <BT_INVENTORY>0.5</BT_INVENTORY><VERSION>"));
+        assert!(written.contains("<ALPHA>3.5</ALPHA><BT_INVENTORY>0.7</BT_INVENTORY>\
+                                  <PROMASH><AA>3.5</AA><AA>4</AA></PROMASH><VENDOR_FLAG/>\
+                                  <AMOUNT>"));
+        let back = round_trip(&doc, &WriteOptions::default());
+        assert_eq!(&back.hops["Saaz"].extensions, extensions);
+
+        let json = serde_json::to_string(&doc).unwrap();
+        assert!(json.contains("\"extensions\":[{\"tag\":\"VENDOR_ID\",\"content\":\"1\"}"));
+    }
 }
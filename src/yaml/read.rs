@@ -23,3 +23,18 @@ pub fn read_file(filename: &Path) -> Result<RecordSet> {
     let reader = BufReader::new(f);
     read(reader)
 }
+
+/// try to read a `Document` from a reader that contains yaml
+pub fn read_document<B>(reader: B) -> Result<Document>
+    where B: BufRead
+{
+    let doc = serde_yaml::from_reader(reader)?;
+    Ok(doc)
+}
+
+/// try to read a `Document` from a file that contains yaml
+pub fn read_document_file(filename: &Path) -> Result<Document> {
+    let f = File::open(filename)?;
+    let reader = BufReader::new(f);
+    read_document(reader)
+}
@@ -8,17 +8,44 @@ use serde_yaml;
 
 use data::*;
 use error::*;
+use options::WriteOptions;
 
 /// try to write a `RecordSet` as yaml to a `writer`
 pub fn write<T>(writer: &mut T, set: &RecordSet) -> Result<()>
     where T: Write
 {
-    serde_yaml::to_writer(writer, set)?;
+    write_with(writer, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` as yaml to a `writer` using the given `WriteOptions`
+pub fn write_with<T>(writer: &mut T, set: &RecordSet, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    serde_yaml::to_writer(writer, &*options.prepare_set(set))?;
     Ok(())
 }
 
 /// try to write a `RecordSet` as yaml to a file
 pub fn write_file(filename: &Path, set: &RecordSet) -> Result<()> {
+    write_file_with(filename, set, &WriteOptions::default())
+}
+
+/// try to write a `RecordSet` as yaml to a file using the given `WriteOptions`
+pub fn write_file_with(filename: &Path, set: &RecordSet, options: &WriteOptions) -> Result<()> {
+    let mut f = File::create(filename)?;
+    write_with(&mut f, set, options)
+}
+
+/// try to write a `Document` to a writer as yaml
+pub fn write_document<T>(writer: &mut T, doc: &Document, options: &WriteOptions) -> Result<()>
+    where T: Write
+{
+    serde_yaml::to_writer(writer, &*options.prepare_document(doc))?;
+    Ok(())
+}
+
+/// try to write a `Document` to a yaml file
+pub fn write_document_file(filename: &Path, doc: &Document, options: &WriteOptions) -> Result<()> {
     let mut f = File::create(filename)?;
-    write(&mut f, set)
+    write_document(&mut f, doc, options)
 }